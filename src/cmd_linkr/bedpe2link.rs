@@ -0,0 +1,106 @@
+use clap::*;
+use intspan::*;
+use std::io::BufRead;
+
+// Create clap subcommand arguments
+pub fn make_subcommand() -> Command {
+    Command::new("bedpe2link")
+        .about("Convert BEDPE to links")
+        .after_help(
+            r###"
+* Columns of BEDPE: chrom1 start1 end1 chrom2 start2 end2 [name score strand1 strand2 ...]
+* Starts are 0-based, so they are shifted by 1
+* Strands other than `+` and `-` are omitted
+* Comment lines, `track` lines and lines of unpaired ends, with a chrom of `.`, are skipped
+* Lines with an invalid range are malformed
+
+Example:
+
+    linkr bedpe2link tests/linkr/II.bedpe
+
+"###,
+        )
+        .arg(
+            Arg::new("infiles")
+                .required(true)
+                .num_args(1..)
+                .index(1)
+                .help("Set the input files to use"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
+        .arg(
+            Arg::new("outfile")
+                .long("outfile")
+                .short('o')
+                .num_args(1)
+                .default_value("stdout")
+                .help("Output filename. [stdout] for screen"),
+        )
+}
+
+// command implementation
+pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
+    let mut writer = writer(args.get_one::<String>("outfile").unwrap())?;
+
+    let mut skipped = Skipped::new(args.get_flag("strict"));
+    for infile in args.get_many::<String>("infiles").unwrap() {
+        let reader = reader(infile)?;
+        'LINE: for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+            if is_comment(&line, true) {
+                continue 'LINE;
+            }
+
+            let parts: Vec<&str> = line.split('\t').collect();
+            if parts.len() < 6 {
+                skipped.skip(InputError::parse("BEDPE line", &line).at(infile, i + 1, None))?;
+                continue 'LINE;
+            }
+
+            // An unpaired end
+            if parts[0] == "." || parts[3] == "." {
+                continue 'LINE;
+            }
+
+            let mut rgs: Vec<String> = vec![];
+            for i in 0..2 {
+                // Rearrange each end into a BED6 line
+                let bed = [
+                    parts[i * 3],
+                    parts[i * 3 + 1],
+                    parts[i * 3 + 2],
+                    ".",
+                    "0",
+                    parts.get(8 + i).unwrap_or(&"."),
+                ]
+                .join("\t");
+
+                let range = match parse_rg(&bed, true) {
+                    Ok(range) => range,
+                    Err(_) => {
+                        skipped.skip(InputError::parse("BEDPE line", &line).at(
+                            infile,
+                            i + 1,
+                            None,
+                        ))?;
+                        continue 'LINE;
+                    }
+                };
+                rgs.push(range.to_string());
+            }
+
+            //----------------------------
+            // Output
+            //----------------------------
+            writer.write_all(format!("{}\n", rgs.join("\t")).as_ref())?;
+        } // end of line
+    }
+
+    skipped.report();
+
+    Ok(())
+}
@@ -25,6 +25,12 @@ pub fn make_subcommand() -> Command {
                 .action(ArgAction::SetTrue)
                 .help("Create highlights instead of links"),
         )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -40,7 +46,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Loading
     //----------------------------
-    let mut writer = writer(args.get_one::<String>("outfile").unwrap());
+    let mut writer = writer(args.get_one::<String>("outfile").unwrap())?;
     let is_highlight = args.get_flag("highlight");
 
     let mut colors = (1..=12)
@@ -49,18 +55,27 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     colors.reverse();
     let mut color_idx = 0;
 
+    let mut skipped = Skipped::new(args.get_flag("strict"));
     for infile in args.get_many::<String>("infiles").unwrap() {
-        let reader = reader(infile);
-        for line in reader.lines().map_while(Result::ok) {
-            let parts: Vec<&str> = line.split('\t').collect();
+        let reader = reader(infile)?;
+        for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+            if is_comment(&line, false) {
+                continue;
+            }
 
-            if is_highlight {
-                for part in parts {
-                    let range = Range::from_str(part);
-                    if !range.is_valid() {
-                        continue;
-                    }
+            let ranges = match parse_links(&line) {
+                Ok(links) => links
+                    .into_iter()
+                    .map(|(_, range)| range)
+                    .collect::<Vec<_>>(),
+                Err(e) => {
+                    skipped.skip(e.at(infile, i + 1, None))?;
+                    continue;
+                }
+            };
 
+            if is_highlight {
+                for range in &ranges {
                     //----------------------------
                     // Output
                     //----------------------------
@@ -82,18 +97,13 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
                     color_idx = 0;
                 }
             } else {
-                let count = parts.len();
+                let count = ranges.len();
 
-                // 2-combinations of parts forms a pair
+                // 2-combinations of ranges forms a pair
                 for i in 0..count {
-                    'PAIR: for j in i + 1..count {
+                    for j in i + 1..count {
                         let mut fields: Vec<String> = vec![];
-                        for idx in &[i, j] {
-                            let range = Range::from_str(parts[*idx]);
-                            if !range.is_valid() {
-                                continue 'PAIR;
-                            }
-
+                        for range in [&ranges[i], &ranges[j]] {
                             fields.push(range.chr().to_string());
                             if range.strand() == "-" {
                                 fields.push(range.end().to_string());
@@ -114,5 +124,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         } // end of line
     }
 
+    skipped.report();
+
     Ok(())
 }
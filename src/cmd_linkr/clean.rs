@@ -34,7 +34,7 @@ pub fn make_subcommand() -> Command {
                 .long("bundle")
                 .short('b')
                 .num_args(1)
-                .value_parser(value_parser!(i32))
+                .value_parser(value_parser!(i64))
                 .default_value("0")
                 .help("Bundle overlapped links. This value is the overlapping size. Suggested value is [500]"),
         )
@@ -45,6 +45,12 @@ pub fn make_subcommand() -> Command {
                 .action(ArgAction::SetTrue)
                 .help("Verbose mode"),
         )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -60,7 +66,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Loading
     //----------------------------
-    let bundle = *args.get_one::<i32>("bundle").unwrap();
+    let bundle = *args.get_one::<i64>("bundle").unwrap();
     let is_verbose = args.get_flag("verbose");
 
     // cache ranges
@@ -70,12 +76,28 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     // Load replaces
     //----------------------------
     let mut replaces: HashMap<String, String> = HashMap::new();
+    let mut skipped = Skipped::new(args.get_flag("strict"));
     if args.contains_id("replace") {
         if is_verbose {
             eprintln!("==> Load replaces");
         }
-        for line in read_lines(args.get_one::<String>("replace").unwrap()) {
-            build_range_of_part(&line, &mut range_of_part);
+        let infile = args.get_one::<String>("replace").unwrap();
+        for (i, line) in read_lines(infile)?.iter().enumerate() {
+            if is_comment(line, false) {
+                continue;
+            }
+
+            match parse_links(line) {
+                Ok(links) => {
+                    for (part, range) in links {
+                        range_of_part.entry(part.to_string()).or_insert(range);
+                    }
+                }
+                Err(e) => {
+                    skipped.skip(e.at(infile, i + 1, None))?;
+                    continue;
+                }
+            }
 
             let parts: Vec<&str> = line.split('\t').collect();
             if parts.len() == 2 {
@@ -93,9 +115,23 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
 
     let mut line_set: BTreeSet<String> = BTreeSet::new();
     for infile in args.get_many::<String>("infiles").unwrap() {
-        let reader = reader(infile);
-        for line in reader.lines().map_while(Result::ok) {
-            build_range_of_part(&line, &mut range_of_part);
+        let reader = reader(infile)?;
+        for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+            if is_comment(&line, false) {
+                continue;
+            }
+
+            match parse_links(&line) {
+                Ok(links) => {
+                    for (part, range) in links {
+                        range_of_part.entry(part.to_string()).or_insert(range);
+                    }
+                }
+                Err(e) => {
+                    skipped.skip(e.at(infile, i + 1, None))?;
+                    continue;
+                }
+            }
 
             let mut parts: Vec<String> = line.split('\t').map(String::from).collect();
             let count = parts.len();
@@ -160,6 +196,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
             line_set.insert(new_line);
         }
     }
+    skipped.report();
 
     //----------------------------
     // Remove nested links
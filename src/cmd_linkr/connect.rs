@@ -39,6 +39,12 @@ pub fn make_subcommand() -> Command {
                 .action(ArgAction::SetTrue)
                 .help("Verbose mode"),
         )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -67,14 +73,29 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     // petgraph use NodeIndex to store and identify nodes
     let mut idx_of_part: HashMap<String, NodeIndex> = HashMap::new();
 
+    let mut skipped = Skipped::new(args.get_flag("strict"));
     for infile in args.get_many::<String>("infiles").unwrap() {
         if is_verbose {
             eprintln!("==> Loading {:#?}", infile);
         }
 
-        let reader = reader(infile);
-        for line in reader.lines().map_while(Result::ok) {
-            build_range_of_part(&line, &mut range_of_part);
+        let reader = reader(infile)?;
+        for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+            if is_comment(&line, false) {
+                continue;
+            }
+
+            match parse_links(&line) {
+                Ok(links) => {
+                    for (part, range) in links {
+                        range_of_part.entry(part.to_string()).or_insert(range);
+                    }
+                }
+                Err(e) => {
+                    skipped.skip(e.at(infile, i + 1, None))?;
+                    continue;
+                }
+            }
 
             let mut parts: Vec<String> = line
                 .split('\t')
@@ -134,6 +155,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
             }
         } // end of line
     } // end of file
+    skipped.report();
 
     //----------------------------
     // Create cc and sort
@@ -181,7 +203,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         let mut ranges = parts
             .iter()
             .map(|part| {
-                let mut range = Range::from_str(part);
+                let mut range = Range::from_str_lenient(part);
                 *range.strand_mut() = "".to_string();
                 range
             })
@@ -35,6 +35,12 @@ pub fn make_subcommand() -> Command {
                 .value_parser(value_parser!(f32))
                 .help("Ratio of lengths differences. The suggested value is [0.8]"),
         )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -50,10 +56,12 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Loading
     //----------------------------
-    let mut writer = writer(args.get_one::<String>("outfile").unwrap());
+    let mut writer = writer(args.get_one::<String>("outfile").unwrap())?;
 
     let numbers = if args.contains_id("number") {
-        IntSpan::from(args.get_one::<String>("number").unwrap())
+        args.get_one::<String>("number")
+            .unwrap()
+            .parse::<IntSpan>()?
     } else {
         IntSpan::new()
     };
@@ -63,25 +71,33 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         -1.0
     };
 
+    let mut skipped = Skipped::new(args.get_flag("strict"));
     for infile in args.get_many::<String>("infiles").unwrap() {
-        let reader = reader(infile);
-        for line in reader.lines().map_while(Result::ok) {
+        let reader = reader(infile)?;
+        for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+            if is_comment(&line, false) {
+                continue;
+            }
+
+            let links = match parse_links(&line) {
+                Ok(links) => links,
+                Err(e) => {
+                    skipped.skip(e.at(infile, i + 1, None))?;
+                    continue;
+                }
+            };
+
             let parts: Vec<&str> = line.split('\t').collect();
 
-            if !numbers.is_empty() && !numbers.contains(parts.len() as i32) {
+            if !numbers.is_empty() && !numbers.contains(parts.len() as i64) {
                 continue;
             }
 
             if ratio > 0.0 {
-                let mut lengths: Vec<i32> = vec![];
-
-                for part in &parts {
-                    let range = Range::from_str(part);
-                    if !range.is_valid() {
-                        continue;
-                    }
-                    lengths.push(range.intspan().cardinality());
-                }
+                let lengths: Vec<i64> = links
+                    .iter()
+                    .map(|(_, range)| range.intspan().cardinality())
+                    .collect();
 
                 let min = lengths.iter().min().unwrap();
                 let max = lengths.iter().max().unwrap();
@@ -99,5 +115,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         } // end of line
     }
 
+    skipped.report();
+
     Ok(())
 }
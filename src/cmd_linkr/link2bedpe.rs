@@ -0,0 +1,99 @@
+use clap::*;
+use intspan::*;
+use std::io::BufRead;
+
+// Create clap subcommand arguments
+pub fn make_subcommand() -> Command {
+    Command::new("link2bedpe")
+        .about("Convert links to BEDPE")
+        .after_help(
+            r###"
+* Each 2-combination of ranges in a link forms a BEDPE line
+    chrom1 start1 end1 chrom2 start2 end2 name score strand1 strand2
+* Starts are written 0-based
+* `name` is `.` and `score` is `0`; missing strands are written as `.`
+* Invalid ranges are skipped
+
+Example:
+
+    linkr link2bedpe tests/linkr/II.links.tsv
+
+"###,
+        )
+        .arg(
+            Arg::new("infiles")
+                .required(true)
+                .num_args(1..)
+                .index(1)
+                .help("Set the input files to use"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
+        .arg(
+            Arg::new("outfile")
+                .long("outfile")
+                .short('o')
+                .num_args(1)
+                .default_value("stdout")
+                .help("Output filename. [stdout] for screen"),
+        )
+}
+
+// command implementation
+pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
+    let mut writer = writer(args.get_one::<String>("outfile").unwrap())?;
+
+    let mut skipped = Skipped::new(args.get_flag("strict"));
+    for infile in args.get_many::<String>("infiles").unwrap() {
+        let reader = reader(infile)?;
+        for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+            if is_comment(&line, false) {
+                continue;
+            }
+
+            let links = match parse_links(&line) {
+                Ok(links) => links,
+                Err(e) => {
+                    skipped.skip(e.at(infile, i + 1, None))?;
+                    continue;
+                }
+            };
+            let ranges: Vec<Range> = links.into_iter().map(|(_, range)| range).collect();
+
+            // 2-combinations of ranges forms a pair
+            let count = ranges.len();
+            for i in 0..count {
+                for j in i + 1..count {
+                    let mut fields: Vec<String> = vec![];
+                    for range in [&ranges[i], &ranges[j]] {
+                        fields.push(range.chr().to_string());
+                        fields.push((range.start() - 1).to_string());
+                        fields.push(range.end().to_string());
+                    }
+                    fields.push(".".to_string());
+                    fields.push("0".to_string());
+                    for range in [&ranges[i], &ranges[j]] {
+                        if range.strand().is_empty() {
+                            fields.push(".".to_string());
+                        } else {
+                            fields.push(range.strand().to_string());
+                        }
+                    }
+
+                    //----------------------------
+                    // Output
+                    //----------------------------
+                    writer.write_all(format!("{}\n", fields.join("\t")).as_ref())?;
+                }
+            }
+        } // end of line
+    }
+
+    skipped.report();
+
+    Ok(())
+}
@@ -1,7 +1,9 @@
 //! Subcommand modules for the `linkr` binary.
 
+pub mod bedpe2link;
 pub mod circos;
 pub mod clean;
 pub mod connect;
 pub mod filter;
+pub mod link2bedpe;
 pub mod sort;
@@ -14,6 +14,12 @@ pub fn make_subcommand() -> Command {
                 .index(1)
                 .help("Set the input files to use"),
         )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -31,20 +37,23 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     let mut line_set: BTreeSet<String> = BTreeSet::new();
 
+    let mut skipped = Skipped::new(args.get_flag("strict"));
     for infile in args.get_many::<String>("infiles").unwrap() {
-        let reader = reader(infile);
-        'LINE: for line in reader.lines().map_while(Result::ok) {
-            let parts: Vec<&str> = line.split('\t').collect();
+        let reader = reader(infile)?;
+        for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+            if is_comment(&line, false) {
+                continue;
+            }
 
-            for part in parts {
-                let range = Range::from_str(part);
-                if range.is_valid() {
-                    line_set.insert(line.clone());
-                    continue 'LINE;
+            match parse_links(&line) {
+                Ok(_) => {
+                    line_set.insert(line);
                 }
+                Err(e) => skipped.skip(e.at(infile, i + 1, None))?,
             }
         } // end of line
     }
+    skipped.report();
 
     //----------------------------
     // Sorting
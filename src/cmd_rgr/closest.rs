@@ -0,0 +1,215 @@
+use clap::*;
+use std::io::BufRead;
+
+// Create clap subcommand arguments
+pub fn make_subcommand() -> Command {
+    Command::new("closest")
+        .about("Append the closest ranges from other range files")
+        .after_help(
+            r###"
+* For each range in <target>, the closest ranges in <infiles> are appended
+    * One line for each closest range
+    * Appended fields: `closest` and `distance`
+    * If there are no ranges on the same chromosome, both fields are `.`
+* The distance of adjacent ranges is 1, and overlapping ranges have a distance of 0
+* The distance is negative for upstream ranges, according to the strand of the target range
+    * Ranges without a strand are treated as on the positive strand
+* `--field` applies to both <target> and <infiles>
+* `.bed` and `.bed.gz` files are read as BED; ranges come from the first three columns
+
+* --ties
+    * all: report all tied ranges
+    * first: report the first tied range, the one with the smallest start
+    * last: report the last tied range, the one with the largest start
+
+Example:
+
+    rgr closest tests/rgr/S288c.rg tests/rgr/genes.rg
+
+    rgr closest tests/rgr/S288c.rg tests/rgr/genes.rg -k 2 --ties first
+
+    rgr closest tests/rgr/ctg.range.tsv tests/rgr/ctg.range.tsv -H -f 3 --no-overlap
+
+"###,
+        )
+        .arg(
+            Arg::new("target")
+                .required(true)
+                .index(1)
+                .num_args(1)
+                .help("Target file to append the closest ranges to"),
+        )
+        .arg(
+            Arg::new("infiles")
+                .required(true)
+                .index(2)
+                .num_args(1..)
+                .help("Range files to search. Multiple files can be specified"),
+        )
+        .arg(
+            Arg::new("header")
+                .long("header")
+                .short('H')
+                .action(ArgAction::SetTrue)
+                .help("Treat the first line of each file as a header"),
+        )
+        .arg(
+            Arg::new("sharp")
+                .long("sharp")
+                .short('s')
+                .action(ArgAction::SetTrue)
+                .help("Include lines starting with `#` without changes (default: ignore them)"),
+        )
+        .arg(
+            Arg::new("field")
+                .long("field")
+                .short('f')
+                .num_args(1)
+                .value_parser(value_parser!(usize))
+                .help("Index of the range field. If not set, the first valid range will be used"),
+        )
+        .arg(
+            Arg::new("k")
+                .long("k")
+                .short('k')
+                .num_args(1)
+                .default_value("1")
+                .value_parser(value_parser!(usize))
+                .help("Report the k closest ranges"),
+        )
+        .arg(
+            Arg::new("ties")
+                .long("ties")
+                .num_args(1)
+                .default_value("all")
+                .value_parser([
+                    builder::PossibleValue::new("all"),
+                    builder::PossibleValue::new("first"),
+                    builder::PossibleValue::new("last"),
+                ])
+                .help("How to report tied ranges"),
+        )
+        .arg(
+            Arg::new("no_overlap")
+                .long("no-overlap")
+                .action(ArgAction::SetTrue)
+                .help("Ignore overlapping ranges"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
+        .arg(
+            Arg::new("outfile")
+                .long("outfile")
+                .short('o')
+                .num_args(1)
+                .default_value("stdout")
+                .help("Output filename. [stdout] for screen"),
+        )
+}
+
+// command implementation
+pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
+    //----------------------------
+    // Args
+    //----------------------------
+    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap())?;
+
+    let is_sharp = args.get_flag("sharp");
+    let is_header = args.get_flag("header");
+
+    let opt_idx_range = args.get_one::<usize>("field").copied().unwrap_or(0);
+
+    let opt_k = *args.get_one::<usize>("k").unwrap();
+    let opt_ties = args.get_one::<String>("ties").unwrap().as_str();
+    let is_overlap = !args.get_flag("no_overlap");
+
+    //----------------------------
+    // Loading
+    //----------------------------
+    let mut skipped = intspan::Skipped::new(args.get_flag("strict"));
+    let (index, _, _) = super::load_joined(
+        args.get_many::<String>("infiles").unwrap(),
+        is_header,
+        opt_idx_range,
+        &mut skipped,
+    )?;
+
+    //----------------------------
+    // Operating
+    //----------------------------
+    let target = args.get_one::<String>("target").unwrap();
+    let reader = intspan::reader(target)?;
+    let is_bed = intspan::is_bed(target);
+    'LINE: for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+        // Handle the header line
+        if is_header && i == 0 {
+            writer.write_fmt(format_args!("{}\t{}\t{}\n", line, "closest", "distance"))?;
+            continue 'LINE;
+        }
+
+        if intspan::is_comment(&line, is_bed) {
+            // Lines starting with '#' are kept with --sharp
+            if is_sharp && line.starts_with('#') {
+                writer.write_fmt(format_args!("{}\n", line))?;
+            }
+            continue 'LINE;
+        }
+
+        let rg = match intspan::extract_rg_bed(&line, opt_idx_range, is_bed) {
+            // Extract the range
+            Some(range) => range,
+            // Skip lines without a valid range
+            None => {
+                let field = if opt_idx_range == 0 {
+                    None
+                } else {
+                    Some(opt_idx_range)
+                };
+                skipped.skip(intspan::InputError::parse("range", &line).at(
+                    target,
+                    i + 1,
+                    field,
+                ))?;
+                continue 'LINE;
+            }
+        };
+
+        let mut hits = index.closest(&rg, opt_k, is_overlap);
+        match opt_ties {
+            "first" => hits.truncate(opt_k),
+            "last" => {
+                // Reverse the order of ties
+                hits.sort_by_key(|(r, _, d)| (*d, -r.start(), -r.end()));
+                hits.truncate(opt_k);
+            }
+            _ => {}
+        }
+
+        //----------------------------
+        // Output
+        //----------------------------
+        if hits.is_empty() {
+            writer.write_fmt(format_args!("{}\t.\t.\n", line))?;
+        }
+
+        for (range, closest, distance) in hits {
+            let is_left = range.end() < rg.start();
+            let is_upstream = if rg.strand() == "-" {
+                !is_left
+            } else {
+                is_left
+            };
+            let distance = if is_upstream { -distance } else { distance };
+
+            writer.write_fmt(format_args!("{}\t{}\t{}\n", line, closest, distance))?;
+        }
+    }
+
+    skipped.report();
+
+    Ok(())
+}
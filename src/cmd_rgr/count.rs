@@ -1,11 +1,6 @@
 use clap::*;
-use rust_lapper::{Interval, Lapper};
-use std::collections::BTreeMap;
 use std::io::BufRead;
 
-// Interval: represent a range from [start, stop), carrying val
-type Iv = Interval<u32, u32>; // the first type should be Unsigned
-
 // Create clap subcommand arguments
 pub fn make_subcommand() -> Command {
     Command::new("count")
@@ -13,6 +8,7 @@ pub fn make_subcommand() -> Command {
         .after_help(
             r###"
 * Lines without a valid range will not be output
+* `.bed` and `.bed.gz` files are read as BED; ranges come from the first three columns
 
 Example:
 
@@ -63,6 +59,12 @@ Example:
                 .num_args(1)
                 .help("Index of the range field. If not set, the first valid range will be used"),
         )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -78,7 +80,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Options
     //----------------------------
-    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap());
+    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap())?;
 
     let is_sharp = args.get_flag("sharp");
     let is_header = args.get_flag("header");
@@ -88,42 +90,37 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Loading
     //----------------------------
-    // seq_name => Vector of Intervals
-    let mut iv_of: BTreeMap<String, Vec<Iv>> = BTreeMap::new();
+    let mut ranges: Vec<(intspan::Range, ())> = vec![];
 
+    let mut skipped = intspan::Skipped::new(args.get_flag("strict"));
     for infile in args.get_many::<String>("infiles").unwrap() {
-        let reader = intspan::reader(infile);
-        for line in reader.lines().map_while(Result::ok) {
-            if line.starts_with('#') {
+        let reader = intspan::reader(infile)?;
+        let is_bed = intspan::is_bed(infile);
+        for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+            if intspan::is_comment(&line, is_bed) {
                 continue;
             }
 
-            let range = intspan::Range::from_str(&line);
-            if !range.is_valid() {
-                continue;
-            }
-
-            let iv = Iv {
-                start: *range.start() as u32,
-                stop: *range.end() as u32 + 1,
-                val: 0,
+            let range = match intspan::parse_rg(&line, is_bed) {
+                Ok(range) => range,
+                Err(e) => {
+                    skipped.skip(e.at(infile, i + 1, None))?;
+                    continue;
+                }
             };
-            let chr = range.chr();
-            iv_of.entry(chr.to_string()).or_default().push(iv);
+
+            ranges.push((range, ()));
         }
     }
 
-    // seq_name => Lapper
-    let mut lapper_of = BTreeMap::new();
-    for (chr, ivs) in iv_of {
-        let lapper = Lapper::new(ivs);
-        lapper_of.insert(chr, lapper);
-    }
+    let index = intspan::RangeIndex::from_ranges(ranges);
 
     //----------------------------
     // Operating
     //----------------------------
-    let reader = intspan::reader(args.get_one::<String>("target").unwrap());
+    let target = args.get_one::<String>("target").unwrap();
+    let reader = intspan::reader(target)?;
+    let is_bed = intspan::is_bed(target);
     'LINE: for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
         // Handle the header line
         if is_header && i == 0 {
@@ -139,18 +136,31 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
             continue 'LINE;
         }
 
-        let rg = match intspan::extract_rg(&line, opt_idx_range) {
+        // Skip empty lines, and the track and browser lines of BED files
+        if intspan::is_comment(&line, is_bed) {
+            continue 'LINE;
+        }
+
+        let rg = match intspan::extract_rg_bed(&line, opt_idx_range, is_bed) {
             // Extract the range
             Some(range) => range,
             // Skip lines without a valid range
-            None => continue 'LINE,
+            None => {
+                let field = if opt_idx_range == 0 {
+                    None
+                } else {
+                    Some(opt_idx_range)
+                };
+                skipped.skip(intspan::InputError::parse("range", &line).at(
+                    target,
+                    i + 1,
+                    field,
+                ))?;
+                continue 'LINE;
+            }
         };
 
-        let mut count = 0;
-        if lapper_of.contains_key(rg.chr()) {
-            let lapper = lapper_of.get(rg.chr()).unwrap();
-            count = lapper.count(*rg.start() as u32, *rg.end() as u32 + 1);
-        }
+        let count = index.count(&rg);
 
         //----------------------------
         // Output
@@ -158,5 +168,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         writer.write_all(format!("{}\t{}\n", line, count).as_ref())?;
     }
 
+    skipped.report();
+
     Ok(())
 }
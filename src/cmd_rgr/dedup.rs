@@ -1,6 +1,13 @@
 use clap::*;
-use std::collections::HashSet;
-use std::io::{BufRead, Write};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+
+// Number of temp files when hashes don't fit in memory
+const PARTITIONS: u64 = 64;
+// Rough size of a hash in `HashSet<u64>`
+const HASH_BYTES: usize = 32;
 
 // Create clap subcommand arguments
 pub fn make_subcommand() -> Command {
@@ -12,6 +19,10 @@ This command removes duplicate lines from .tsv file(s) in a single pass without
 Each line consumes 8 bytes (u64) of memory for hashing, making it memory-efficient.
 As a trade-off, this program cannot count the occurrences of duplicates.
 
+When the hashes exceed `--buffer-size`, the remaining lines are partitioned by their hashes
+into temp files, deduplicated partition by partition, and merged back in the original order.
+Set `TMPDIR` to place temp files in another directory.
+
 * If no fields are specified, the entire line is used as the key for deduplication.
 * If fields are specified, only the selected fields are used as the key.
 
@@ -38,6 +49,14 @@ Examples:
                 .num_args(1)
                 .help("Fields to use as the key"),
         )
+        .arg(
+            Arg::new("buffer")
+                .long("buffer-size")
+                .short('S')
+                .num_args(1)
+                .default_value("1G")
+                .help("Size of the memory buffer, with an optional suffix of K, M or G"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -53,7 +72,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Args
     //----------------------------
-    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap());
+    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap())?;
 
     let opt_fields: intspan::IntSpan = if args.contains_id("fields") {
         intspan::fields_to_ints(args.get_one::<String>("fields").unwrap())
@@ -61,37 +80,111 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         intspan::IntSpan::new()
     };
 
+    let max_hashes = intspan::parse_size(args.get_one::<String>("buffer").unwrap())? / HASH_BYTES;
+
     //----------------------------
     // Ops
     //----------------------------
     let mut subject_set: HashSet<u64> = HashSet::new();
 
+    // Lines after the set is full, as (hash, line number, line)
+    let mut partitions: Vec<BufWriter<File>> = vec![];
+    let mut line_no: u64 = 0;
+
     for infile in args.get_many::<String>("infiles").unwrap() {
-        let reader = intspan::reader(infile);
+        let reader = intspan::reader(infile)?;
 
         for line in reader.lines().map_while(Result::ok) {
-            let subject = if opt_fields.is_empty() {
-                // whole line
-                xxhash_rust::xxh3::xxh3_64(&line.clone().into_bytes())
-            } else {
-                // Get elements at specified indices
-                let fields: Vec<&str> = line.split('\t').collect();
-                let subset: Vec<&str> = opt_fields
-                    .elements()
-                    .iter()
-                    .filter_map(|&i| fields.get(i as usize - 1))
-                    .copied()
-                    .collect();
-                let concat = subset.join("\t");
-                xxhash_rust::xxh3::xxh3_64(&concat.into_bytes())
-            };
-
-            if !subject_set.contains(&subject) {
+            let subject = hash_subject(&line, &opt_fields);
+
+            if subject_set.contains(&subject) {
+                continue;
+            }
+            if subject_set.len() < max_hashes {
                 writer.write_fmt(format_args!("{}\n", line))?;
                 subject_set.insert(subject);
+                continue;
+            }
+
+            if partitions.is_empty() {
+                for _ in 0..PARTITIONS {
+                    partitions.push(BufWriter::new(tempfile::tempfile()?));
+                }
+            }
+            partitions[(subject % PARTITIONS) as usize]
+                .write_fmt(format_args!("{}\t{}\t{}\n", subject, line_no, line))?;
+            line_no += 1;
+        }
+    }
+
+    if partitions.is_empty() {
+        return Ok(());
+    }
+    drop(subject_set);
+
+    // Lines in each partition are in the original order, so are the first occurrences
+    let mut kepts: Vec<File> = vec![];
+    for partition in partitions {
+        let mut file = partition.into_inner()?;
+        file.seek(SeekFrom::Start(0))?;
+
+        let mut part_set: HashSet<u64> = HashSet::new();
+        let mut kept = BufWriter::new(tempfile::tempfile()?);
+        for record in BufReader::new(file).lines().map_while(Result::ok) {
+            let (subject, rest) = record.split_once('\t').unwrap();
+            if part_set.insert(subject.parse::<u64>().unwrap()) {
+                kept.write_fmt(format_args!("{}\n", rest))?;
             }
         }
+
+        let mut file = kept.into_inner()?;
+        file.seek(SeekFrom::Start(0))?;
+        kepts.push(file);
+    }
+
+    // Merge partitions by line numbers
+    let mut sources: Vec<_> = kepts
+        .into_iter()
+        .map(|file| BufReader::new(file).lines().map_while(Result::ok))
+        .collect();
+    let mut heap: BinaryHeap<Reverse<(u64, String, usize)>> = BinaryHeap::new();
+    for (idx, source) in sources.iter_mut().enumerate() {
+        if let Some(record) = source.next() {
+            let (no, line) = from_record(&record);
+            heap.push(Reverse((no, line, idx)));
+        }
+    }
+    while let Some(Reverse((_, line, idx))) = heap.pop() {
+        if let Some(record) = sources[idx].next() {
+            let (no, next_line) = from_record(&record);
+            heap.push(Reverse((no, next_line, idx)));
+        }
+        writer.write_fmt(format_args!("{}\n", line))?;
     }
 
     Ok(())
 }
+
+fn hash_subject(line: &str, opt_fields: &intspan::IntSpan) -> u64 {
+    if opt_fields.is_empty() {
+        // whole line
+        xxhash_rust::xxh3::xxh3_64(line.as_bytes())
+    } else {
+        // Get elements at specified indices
+        let fields: Vec<&str> = line.split('\t').collect();
+        let subset: Vec<&str> = opt_fields
+            .elements()
+            .iter()
+            .filter_map(|&i| fields.get(i as usize - 1))
+            .copied()
+            .collect();
+        let concat = subset.join("\t");
+        xxhash_rust::xxh3::xxh3_64(concat.as_bytes())
+    }
+}
+
+// line number and line
+fn from_record(record: &str) -> (u64, String) {
+    let (no, line) = record.split_once('\t').unwrap();
+    (no.parse::<u64>().unwrap(), line.to_string())
+}
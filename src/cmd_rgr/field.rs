@@ -18,6 +18,9 @@ Examples:
 3. Create ranges from a .tsv file:
     rgr field tests/rgr/ctg.tsv --chr 2 --start 3 --end 4 -H
 
+4. Create ranges from a BED file, where starts are 0-based:
+    rgr field tests/rgr/S288c.bed --chr 1 --start 2 --end 3 --strand 6 --bed
+
 "###,
         )
         .arg(
@@ -71,6 +74,12 @@ Examples:
                 .value_parser(value_parser!(usize))
                 .help("Optional field index for end"),
         )
+        .arg(
+            Arg::new("bed")
+                .long("bed")
+                .action(ArgAction::SetTrue)
+                .help("Starts are 0-based and `.` strands are omitted, as in BED files"),
+        )
         .arg(
             Arg::new("append")
                 .long("append")
@@ -78,6 +87,12 @@ Examples:
                 .action(ArgAction::SetTrue)
                 .help("Append a field for the range (default: only write the range)"),
         )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -93,7 +108,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Args
     //----------------------------
-    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap());
+    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap())?;
 
     let is_header = args.get_flag("header");
     let is_sharp = args.get_flag("sharp");
@@ -103,13 +118,15 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     let opt_idx_start = *args.get_one::<usize>("start").unwrap();
     let opt_idx_end = args.get_one::<usize>("end").copied().unwrap_or(0);
 
+    let is_bed = args.get_flag("bed");
     let is_append = args.get_flag("append");
 
     //----------------------------
     // Ops
     //----------------------------
+    let mut skipped = intspan::Skipped::new(args.get_flag("strict"));
     for infile in args.get_many::<String>("infiles").unwrap() {
-        let reader = intspan::reader(infile);
+        let reader = intspan::reader(infile)?;
         'LINE: for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
             let parts: Vec<&str> = line.split('\t').collect();
 
@@ -131,30 +148,22 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
                 continue 'LINE;
             }
 
-            // Build ranges
-            let chr = parts.get(opt_idx_chr - 1).unwrap();
-            let strand = if opt_idx_strand == 0 {
-                ""
-            } else {
-                parts.get(opt_idx_strand - 1).unwrap()
-            };
-            let start = parts
-                .get(opt_idx_start - 1)
-                .unwrap()
-                .parse::<i32>()
-                .unwrap();
-            let end = if opt_idx_end == 0 {
-                start
-            } else {
-                parts.get(opt_idx_end - 1).unwrap().parse::<i32>().unwrap()
-            };
+            // Skip empty lines, and the track and browser lines of BED files
+            if intspan::is_comment(&line, is_bed) {
+                continue 'LINE;
+            }
 
-            let rg = intspan::Range {
-                name: "".to_string(),
-                chr: chr.to_string(),
-                strand: strand.to_string(),
-                start,
-                end,
+            // Build ranges
+            let rg = match build_rg(
+                &parts,
+                (opt_idx_chr, opt_idx_strand, opt_idx_start, opt_idx_end),
+                is_bed,
+            ) {
+                Ok(rg) => rg,
+                Err((idx, e)) => {
+                    skipped.skip(e.at(infile, i + 1, Some(idx)))?;
+                    continue 'LINE;
+                }
             };
 
             //----------------------------
@@ -170,5 +179,51 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         }
     }
 
+    skipped.report();
+
     Ok(())
 }
+
+// A range from fields of chr, strand, start and end, by 1-based indices.
+// Errors come with the index of the missing or malformed field
+fn build_rg(
+    parts: &[&str],
+    (idx_chr, idx_strand, idx_start, idx_end): (usize, usize, usize, usize),
+    is_bed: bool,
+) -> Result<intspan::Range, (usize, intspan::InputError)> {
+    let field = |idx: usize, kind: &str| {
+        idx.checked_sub(1)
+            .and_then(|i| parts.get(i))
+            .copied()
+            .ok_or_else(|| (idx, intspan::InputError::parse(kind, "")))
+    };
+    let number = |idx: usize, kind: &str| {
+        let value = field(idx, kind)?;
+        value
+            .parse::<i64>()
+            .map_err(|_| (idx, intspan::InputError::parse(kind, value)))
+    };
+
+    let chr = field(idx_chr, "chr")?;
+    let strand = if idx_strand == 0 {
+        ""
+    } else {
+        field(idx_strand, "strand")?
+    };
+    // BED uses `.` for unknown strands
+    let strand = if is_bed && strand == "." { "" } else { strand };
+    let start = number(idx_start, "start")? + if is_bed { 1 } else { 0 };
+    let end = if idx_end == 0 {
+        start
+    } else {
+        number(idx_end, "end")?
+    };
+
+    Ok(intspan::Range {
+        name: "".to_string(),
+        chr: chr.to_string(),
+        strand: strand.to_string(),
+        start,
+        end,
+    })
+}
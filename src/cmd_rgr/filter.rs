@@ -132,7 +132,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Args
     //----------------------------
-    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap());
+    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap())?;
 
     let is_header = args.get_flag("header");
     let is_sharp = args.get_flag("sharp");
@@ -157,7 +157,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     // Ops
     //----------------------------
     for infile in args.get_many::<String>("infiles").unwrap() {
-        let reader = intspan::reader(infile);
+        let reader = intspan::reader(infile)?;
         'LINE: for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
             let parts: Vec<&str> = line.split('\t').collect();
 
@@ -304,7 +304,7 @@ fn opt_fields_ff(args: &ArgMatches, id: &str) -> Vec<(usize, usize)> {
                 std::process::exit(1)
             }
 
-            let fields1 = intspan::ints_to_idx(parts.get(0).unwrap());
+            let fields1 = intspan::ints_to_idx(parts.first().unwrap());
             let fields2 = intspan::ints_to_idx(parts.get(1).unwrap());
 
             for i in &fields1 {
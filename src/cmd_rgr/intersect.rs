@@ -0,0 +1,246 @@
+use clap::*;
+use std::io::BufRead;
+
+// Create clap subcommand arguments
+pub fn make_subcommand() -> Command {
+    Command::new("intersect")
+        .about("Join lines of two range files on overlapping ranges")
+        .after_help(
+            r###"
+* For each range in <target>, overlapping lines of <infiles> are appended
+    * One line for each overlapping pair
+    * With `--header`, headers of <target> and the first file of <infiles> are joined
+    * Strands are ignored
+* `--field` applies to both <target> and <infiles>
+* `.bed` and `.bed.gz` files are read as BED; ranges come from the first three columns
+
+* --fraction F
+    * The overlap must cover at least F of both ranges, i.e. a reciprocal overlap
+    * 0 means at least 1 bp
+
+* --mode
+    * inner: output overlapping pairs only
+    * left: also output target lines without overlaps, with a `.` for each column of <infiles>
+    * anti: output target lines without overlaps, unchanged
+
+* --bp appends the number of overlapping bases, calculated by `IntSpan::overlap()`
+
+Example:
+
+    rgr intersect tests/rgr/S288c.rg tests/rgr/genes.rg --bp
+
+    rgr intersect tests/rgr/S288c.rg tests/rgr/genes.rg --mode left -r 0.1
+
+    rgr intersect tests/rgr/S288c.rg tests/rgr/genes.rg --mode anti
+
+"###,
+        )
+        .arg(
+            Arg::new("target")
+                .required(true)
+                .index(1)
+                .num_args(1)
+                .help("Target file"),
+        )
+        .arg(
+            Arg::new("infiles")
+                .required(true)
+                .index(2)
+                .num_args(1..)
+                .help("Files to join. Multiple files can be specified"),
+        )
+        .arg(
+            Arg::new("header")
+                .long("header")
+                .short('H')
+                .action(ArgAction::SetTrue)
+                .help("Treat the first line of each file as a header"),
+        )
+        .arg(
+            Arg::new("sharp")
+                .long("sharp")
+                .short('s')
+                .action(ArgAction::SetTrue)
+                .help("Include lines starting with `#` without changes (default: ignore them)"),
+        )
+        .arg(
+            Arg::new("field")
+                .long("field")
+                .short('f')
+                .num_args(1)
+                .value_parser(value_parser!(usize))
+                .help("Index of the range field. If not set, the first valid range will be used"),
+        )
+        .arg(
+            Arg::new("fraction")
+                .long("fraction")
+                .short('r')
+                .num_args(1)
+                .default_value("0")
+                .value_parser(value_parser!(f64))
+                .help("Minimum reciprocal overlap fraction"),
+        )
+        .arg(
+            Arg::new("mode")
+                .long("mode")
+                .num_args(1)
+                .default_value("inner")
+                .value_parser([
+                    builder::PossibleValue::new("inner"),
+                    builder::PossibleValue::new("left"),
+                    builder::PossibleValue::new("anti"),
+                ])
+                .help("Join mode"),
+        )
+        .arg(
+            Arg::new("bp")
+                .long("bp")
+                .action(ArgAction::SetTrue)
+                .help("Append the number of overlapping bases"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
+        .arg(
+            Arg::new("outfile")
+                .long("outfile")
+                .short('o')
+                .num_args(1)
+                .default_value("stdout")
+                .help("Output filename. [stdout] for screen"),
+        )
+}
+
+// command implementation
+pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
+    //----------------------------
+    // Args
+    //----------------------------
+    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap())?;
+
+    let is_sharp = args.get_flag("sharp");
+    let is_header = args.get_flag("header");
+
+    let opt_idx_range = args.get_one::<usize>("field").copied().unwrap_or(0);
+    // The field of malformed ranges in errors
+    let opt_field = if opt_idx_range == 0 {
+        None
+    } else {
+        Some(opt_idx_range)
+    };
+
+    let opt_fraction = *args.get_one::<f64>("fraction").unwrap();
+    let opt_mode = args.get_one::<String>("mode").unwrap().as_str();
+    let is_bp = args.get_flag("bp");
+
+    //----------------------------
+    // Loading
+    //----------------------------
+    let mut skipped = intspan::Skipped::new(args.get_flag("strict"));
+    let (index, joined_header, joined_cols) = super::load_joined(
+        args.get_many::<String>("infiles").unwrap(),
+        is_header,
+        opt_idx_range,
+        &mut skipped,
+    )?;
+
+    //----------------------------
+    // Operating
+    //----------------------------
+    let target = args.get_one::<String>("target").unwrap();
+    let reader = intspan::reader(target)?;
+    let is_bed = intspan::is_bed(target);
+    'LINE: for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+        // Handle the header line
+        if is_header && i == 0 {
+            if opt_mode == "anti" {
+                writer.write_fmt(format_args!("{}\n", line))?;
+            } else {
+                writer.write_fmt(format_args!(
+                    "{}\t{}{}\n",
+                    line,
+                    joined_header.as_deref().unwrap_or("intersect"),
+                    if is_bp { "\toverlap" } else { "" }
+                ))?;
+            }
+            continue 'LINE;
+        }
+
+        // Skip empty lines, comments, and the track and browser lines of BED files
+        if intspan::is_comment(&line, is_bed) {
+            // Lines starting with '#' are kept with --sharp
+            if is_sharp && line.starts_with('#') {
+                writer.write_fmt(format_args!("{}\n", line))?;
+            }
+            continue 'LINE;
+        }
+
+        let rg = match intspan::extract_rg_bed(&line, opt_idx_range, is_bed) {
+            // Extract the range
+            Some(range) => range,
+            // Skip lines without a valid range
+            None => {
+                skipped.skip(intspan::InputError::parse("range", &line).at(
+                    target,
+                    i + 1,
+                    opt_field,
+                ))?;
+                continue 'LINE;
+            }
+        };
+
+        // (joined line, overlapping bases)
+        let ints = rg.intspan();
+        let mut hits: Vec<(&String, i64)> = vec![];
+        for (range, joined) in index.find(&rg) {
+            let overlap = ints.overlap(&range.intspan());
+            if overlap == 0 {
+                continue;
+            }
+            if (overlap as f64) < opt_fraction * ints.cardinality() as f64
+                || (overlap as f64) < opt_fraction * range.intspan().cardinality() as f64
+            {
+                continue;
+            }
+            hits.push((joined, overlap));
+        }
+
+        //----------------------------
+        // Output
+        //----------------------------
+        if hits.is_empty() {
+            match opt_mode {
+                "left" => {
+                    // One `.` for each column of the joined lines, like `bedtools intersect -loj`
+                    writer.write_fmt(format_args!(
+                        "{}\t{}{}\n",
+                        line,
+                        vec!["."; joined_cols].join("\t"),
+                        if is_bp { "\t0" } else { "" }
+                    ))?;
+                }
+                "anti" => writer.write_fmt(format_args!("{}\n", line))?,
+                _ => {}
+            }
+            continue 'LINE;
+        }
+        if opt_mode == "anti" {
+            continue 'LINE;
+        }
+
+        for (joined, overlap) in hits {
+            if is_bp {
+                writer.write_fmt(format_args!("{}\t{}\t{}\n", line, joined, overlap))?;
+            } else {
+                writer.write_fmt(format_args!("{}\t{}\n", line, joined))?;
+            }
+        }
+    }
+
+    skipped.report();
+
+    Ok(())
+}
@@ -85,7 +85,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
 
     let mut first_file = true; // Track if we are processing the first file
     for infile in infiles {
-        let reader = intspan::reader(infile);
+        let reader = intspan::reader(infile)?;
         let mut header_written = 0;
         let mut lines = reader.lines();
 
@@ -74,8 +74,8 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Loading
     //----------------------------
-    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap());
-    let reader = intspan::reader(args.get_one::<String>("infile").unwrap());
+    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap())?;
+    let reader = intspan::reader(args.get_one::<String>("infile").unwrap())?;
 
     let mut opt_center: intspan::IntSpan = if args.contains_id("center") {
         intspan::fields_to_ints(args.get_one::<String>("center").unwrap())
@@ -123,8 +123,8 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
 
             for (i, &flag) in is_numeric_column.iter().enumerate().take(num_columns) {
                 if flag {
-                    opt_center.remove_n((i + 1) as i32);
-                    opt_right.add_n((i + 1) as i32);
+                    opt_center.remove_n((i + 1) as i64);
+                    opt_right.add_n((i + 1) as i64);
                 }
             }
         }
@@ -155,9 +155,9 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
                     .collect::<Vec<_>>()
                     .iter()
                     .map(|&j| {
-                        if opt_right.contains((j + 1) as i32) {
+                        if opt_right.contains((j + 1) as i64) {
                             "---:".to_string()
-                        } else if opt_center.contains((j + 1) as i32) {
+                        } else if opt_center.contains((j + 1) as i64) {
                             ":---:".to_string()
                         } else {
                             "---".to_string()
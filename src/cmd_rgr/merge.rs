@@ -1,18 +1,22 @@
 use clap::*;
 use intspan::*;
-use petgraph::prelude::NodeIndex;
-use petgraph::*;
-use std::collections::{HashMap, HashSet};
+use petgraph::unionfind::UnionFind;
+use std::collections::{BTreeMap, HashMap};
 use std::io::BufRead;
 
 // Create clap subcommand arguments
 pub fn make_subcommand() -> Command {
     Command::new("merge")
-        .about("Merge overlapped ranges via overlapping graph")
+        .about("Merge overlapped ranges with a sweep line")
         .after_help(
             r###"
 This command merges overlapping ranges from input files based on a specified coverage threshold.
-It builds an overlapping graph for each chromosome and merges ranges that meet the coverage criteria.
+Two ranges are connected if the intersection covers both of them by at least `--coverage`,
+and each group of connected ranges is merged.
+
+* Ranges of each chromosome are sorted by starts and swept once
+    * Only nearby ranges need to be compared, as the coverage limits how far apart starts can be
+    * The complexity is O(n log n) for typical inputs, no need to split them with `pl-2rmp`
 
 Examples:
 
@@ -47,6 +51,21 @@ Examples:
                 .action(ArgAction::SetTrue)
                 .help("Enable verbose mode"),
         )
+        .arg(
+            Arg::new("parallel")
+                .long("parallel")
+                .short('p')
+                .num_args(1)
+                .default_value("1")
+                .value_parser(value_parser!(usize))
+                .help("Number of threads"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -64,124 +83,134 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     let opt_coverage = *args.get_one::<f32>("coverage").unwrap();
     let is_verbose = args.get_flag("verbose");
-
-    // store graph separately by chromosomes
-    // petgraph use NodeIndex to store and identify nodes
-    let mut graph_of_chr: HashMap<String, Graph<String, (), Undirected>> = HashMap::new();
+    let opt_parallel = *args.get_one::<usize>("parallel").unwrap();
 
     // cache ranges
     let mut range_of_part: HashMap<String, Range> = HashMap::new();
-    // cache node indices
-    let mut idx_of_part: HashMap<String, NodeIndex> = HashMap::new();
 
-    // all chromosomes
-    let mut chrs: HashSet<String> = HashSet::new();
+    // chr => parts
+    let mut parts_of_chr: BTreeMap<String, Vec<String>> = BTreeMap::new();
 
     // Load ranges from input files
+    let mut skipped = Skipped::new(args.get_flag("strict"));
     for infile in args.get_many::<String>("infiles").unwrap() {
-        let reader = reader(infile);
-        for line in reader.lines().map_while(Result::ok) {
-            for part in line.split('\t') {
-                let range = Range::from_str(part);
-                if !range.is_valid() {
+        let reader = reader(infile)?;
+        for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+            if is_comment(&line, false) {
+                continue;
+            }
+
+            let links = match parse_links(&line) {
+                Ok(links) => links,
+                Err(e) => {
+                    skipped.skip(e.at(infile, i + 1, None))?;
                     continue;
                 }
+            };
 
+            for (part, range) in links {
                 if range_of_part.contains_key(part) {
                     continue;
                 }
 
-                let chr = range.chr();
-                graph_of_chr
-                    .entry(chr.to_string())
-                    .or_insert_with(Graph::new_undirected);
-                chrs.insert(chr.to_string());
-
-                let idx = graph_of_chr
-                    .get_mut(chr)
-                    .unwrap()
-                    .add_node(part.to_string());
-                idx_of_part.insert(part.to_string(), idx);
-
+                parts_of_chr
+                    .entry(range.chr().to_string())
+                    .or_default()
+                    .push(part.to_string());
                 range_of_part.insert(part.to_string(), range);
             }
         } // end of line
     } // end of file
-    let mut chrs = chrs.into_iter().collect::<Vec<String>>();
-    chrs.sort();
+    skipped.report();
 
     //----------------------------
-    // Checking coverages
+    // Merging
     //----------------------------
-    for chr in &chrs {
+    // Chromosomes are merged in parallel
+    let chr_parts: Vec<(&String, &Vec<String>)> = parts_of_chr.iter().collect();
+    let lines_of_chr = par_map(&chr_parts, opt_parallel, |(chr, parts)| {
+        let mut out_lines: Vec<String> = Vec::new();
+        let mut parts = parts.to_vec();
+
         if is_verbose {
             eprintln!("Chromosome {}", chr);
         }
 
-        let graph = graph_of_chr.get_mut(chr).unwrap();
-        let indices = graph.node_indices().collect::<Vec<NodeIndex>>();
-
-        for i in 0..indices.len() {
-            let node_i = graph.node_weight(indices[i]).unwrap();
-            let intspan_i = range_of_part[node_i].intspan();
-            if is_verbose {
-                eprintln!("    Range {}/{}\t{}", i, indices.len(), node_i);
+        // Sort by starts, so only preceding ranges need to be checked
+        parts.sort_by_cached_key(|e| {
+            let range = &range_of_part[e];
+            (*range.start(), *range.end(), e.clone())
+        });
+        let spans: Vec<(i64, i64)> = parts
+            .iter()
+            .map(|e| (*range_of_part[e].start(), *range_of_part[e].end()))
+            .collect();
+
+        let mut uf: UnionFind<usize> = UnionFind::new(parts.len());
+        if opt_coverage <= 0.0 {
+            // Any overlaps are merged, the range with the largest end is enough
+            let mut max_idx = 0;
+            for i in 1..spans.len() {
+                if spans[i].0 <= spans[max_idx].1 {
+                    uf.union(i, max_idx);
+                }
+                if spans[i].1 > spans[max_idx].1 {
+                    max_idx = i;
+                }
             }
+        } else {
+            for i in 1..spans.len() {
+                let (start_i, end_i) = spans[i];
+                let len_i = end_i - start_i + 1;
+
+                // A preceding range j covers range i well only if
+                //   start_i - start_j <= (1 - c) * len_j and len_j <= len_i / c
+                let window = ((1.0 - opt_coverage as f64) / opt_coverage as f64 * len_i as f64)
+                    .ceil() as i64
+                    + 1;
+
+                for j in (0..i).rev() {
+                    let (start_j, end_j) = spans[j];
+                    if start_j < start_i - window {
+                        break;
+                    }
+                    if end_j < start_i {
+                        continue;
+                    }
 
-            for j in i + 1..indices.len() {
-                let node_j = graph.node_weight(indices[j]).unwrap();
-                let intspan_j = range_of_part[node_j].intspan();
-
-                let intersect = intspan_i.intersect(&intspan_j);
-                if !intersect.is_empty() {
-                    let coverage_i =
-                        intersect.cardinality() as f32 / intspan_i.cardinality() as f32;
-                    let coverage_j =
-                        intersect.cardinality() as f32 / intspan_j.cardinality() as f32;
+                    let intersect = end_i.min(end_j) - start_i + 1;
+                    let coverage_i = intersect as f32 / len_i as f32;
+                    let coverage_j = intersect as f32 / (end_j - start_j + 1) as f32;
 
                     if coverage_i >= opt_coverage && coverage_j >= opt_coverage {
                         if is_verbose {
-                            eprintln!(
-                                "        Merge with Range {}/{}\t{}",
-                                j,
-                                indices.len(),
-                                node_j
-                            );
+                            eprintln!("    Merge {} with {}", parts[i], parts[j]);
                         }
-                        graph.add_edge(indices[i], indices[j], ());
+                        uf.union(i, j);
                     }
                 }
             }
         }
-    }
-
-    //----------------------------
-    // Merging
-    //----------------------------
-    let mut out_lines: Vec<String> = Vec::new();
-    for chr in &chrs {
-        let graph = graph_of_chr.get(chr).unwrap();
 
-        let scc: Vec<Vec<NodeIndex>> = petgraph::algo::tarjan_scc(graph);
-        for cc_indices in &scc {
-            if cc_indices.len() < 2 {
-                continue;
-            }
+        // Connected ranges
+        let mut cc_of: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+        for (i, part) in parts.iter().enumerate() {
+            cc_of.entry(uf.find(i)).or_default().push(part.clone());
+        }
+        let mut ccs: Vec<Vec<String>> = cc_of.into_values().filter(|e| e.len() > 1).collect();
+        for part_list in ccs.iter_mut() {
+            part_list.sort();
+        }
+        ccs.sort();
 
+        for part_list in &ccs {
             if is_verbose {
-                eprintln!("Chromosome {}: Merge {} ranges", chr, cc_indices.len());
+                eprintln!("Chromosome {}: Merge {} ranges", chr, part_list.len());
             }
 
-            // connected ranges
-            let mut part_list = cc_indices
-                .iter()
-                .map(|idx| graph.node_weight(*idx).unwrap().clone())
-                .collect::<Vec<String>>();
-            part_list.sort();
-
             // collect info for merged range
             let mut intspan = IntSpan::new();
-            for part in &part_list {
+            for part in part_list {
                 let range = range_of_part.get(part).unwrap();
                 intspan.merge(&range.intspan());
             }
@@ -189,7 +218,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
             // create merged range
             let merged: String = format!("{}(+):{}", chr, intspan);
 
-            for part in &part_list {
+            for part in part_list {
                 if *part == merged {
                     continue;
                 }
@@ -201,7 +230,9 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
                 out_lines.push(out_line);
             }
         }
-    }
+        out_lines
+    });
+    let out_lines: Vec<String> = lines_of_chr.into_iter().flatten().collect();
 
     //----------------------------
     // Output
@@ -1,9 +1,13 @@
 //! Subcommand modules for the `rgr` binary.
 
+use std::io::BufRead;
+
+pub mod closest;
 pub mod count;
 pub mod dedup;
 pub mod field;
 pub mod filter;
+pub mod intersect;
 pub mod keep;
 pub mod md;
 pub mod merge;
@@ -12,5 +16,63 @@ pub mod prop;
 pub mod replace;
 pub mod runlist;
 pub mod select;
+pub mod seq;
 pub mod sort;
 pub mod span;
+pub mod window;
+
+/// Index lines of `infiles` by their ranges, for `rgr closest`, `rgr intersect` and `rgr window`.
+/// Also returns the header of the first file with `is_header`, and the largest number of columns
+/// of the indexed lines.
+pub fn load_joined<'a>(
+    infiles: impl IntoIterator<Item = &'a String>,
+    is_header: bool,
+    opt_idx_range: usize,
+    skipped: &mut intspan::Skipped,
+) -> anyhow::Result<(intspan::RangeIndex<String>, Option<String>, usize)> {
+    // The field of malformed ranges in errors
+    let opt_field = if opt_idx_range == 0 {
+        None
+    } else {
+        Some(opt_idx_range)
+    };
+
+    // Range => line
+    let mut ranges: Vec<(intspan::Range, String)> = vec![];
+    let mut joined_header: Option<String> = None;
+    let mut joined_cols = 1;
+
+    for infile in infiles {
+        let reader = intspan::reader(infile)?;
+        let is_bed = intspan::is_bed(infile);
+        for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+            if is_header && i == 0 {
+                if joined_header.is_none() {
+                    joined_header = Some(line);
+                }
+                continue;
+            }
+            if intspan::is_comment(&line, is_bed) {
+                continue;
+            }
+
+            match intspan::extract_rg_bed(&line, opt_idx_range, is_bed) {
+                Some(range) => {
+                    joined_cols = joined_cols.max(line.split('\t').count());
+                    ranges.push((range, line));
+                }
+                None => skipped.skip(intspan::InputError::parse("range", &line).at(
+                    infile,
+                    i + 1,
+                    opt_field,
+                ))?,
+            }
+        }
+    }
+
+    Ok((
+        intspan::RangeIndex::from_ranges(ranges),
+        joined_header,
+        joined_cols,
+    ))
+}
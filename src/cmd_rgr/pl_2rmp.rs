@@ -14,8 +14,8 @@ pub fn make_subcommand() -> Command {
         .about("Pipeline - Two Rounds of Merging and Replacing")
         .after_help(
             r###"
-* The computational complexity of merging is O(n^2), splitting it into two
-  rounds will greatly reduce the computation time
+* `rgr merge` now sweeps sorted ranges in O(n log n), so this pipeline is no
+  longer needed for large inputs. It's kept for compatibility
 
 * <infiles> are paths to .rg or .tsv files, .gz is supported
     * infile == stdin means reading from STDIN
@@ -48,6 +48,12 @@ pub fn make_subcommand() -> Command {
                 .value_parser(value_parser!(usize))
                 .help("Each split having this number of lines"),
         )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
         .arg(
             Arg::new("outfile")
                 .short('o')
@@ -74,7 +80,8 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
 
     run_cmd!(info "==> Paths")?;
     run_cmd!(info "    \"rgr\"   = ${rgr}")?;
-    run_cmd!(info "    \"curdir\" = ${curdir}")?;
+    let curdir_str = curdir.display().to_string();
+    run_cmd!(info "    \"curdir\" = ${curdir_str}")?;
     run_cmd!(info "    \"tempdir\" = ${tempdir_str}")?;
 
     //----------------------------
@@ -104,16 +111,25 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     let mut out_ranges = vec![];
     // save inputs, so we can resue stdin latter
     let mut round1 = File::create("r1.lines")?;
+    let mut skipped = Skipped::new(args.get_flag("strict"));
     for infile in abs_infiles.iter() {
-        let reader = reader(infile);
-        for line in reader.lines().map_while(Result::ok) {
-            round1.write_fmt(format_args!("{}\n", &line))?;
+        let reader = reader(infile)?;
+        for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+            if is_comment(&line, false) {
+                round1.write_fmt(format_args!("{}\n", &line))?;
+                continue;
+            }
 
-            for part in line.split('\t') {
-                let range = Range::from_str(part);
-                if !range.is_valid() {
+            let links = match parse_links(&line) {
+                Ok(links) => links,
+                Err(e) => {
+                    skipped.skip(e.at(infile, i + 1, None))?;
                     continue;
                 }
+            };
+            round1.write_fmt(format_args!("{}\n", &line))?;
+
+            for (part, _) in links {
                 out_ranges.push(part.to_string());
 
                 if out_ranges.len() >= line_limit {
@@ -129,6 +145,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         }
     }
     round1.flush()?;
+    skipped.report();
 
     // last part
     if !out_ranges.is_empty() {
@@ -159,7 +176,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         for i in 1..=serial {
             let infile = format!("replaced.{}", i);
             if Path::new(infile.as_str()).is_file() {
-                let reader = reader(&infile);
+                let reader = reader(&infile)?;
                 for line in reader.lines().map_while(Result::ok) {
                     merged_1st.insert(line);
                 }
@@ -172,11 +189,11 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         let count_1st = merged_1st.len();
         run_cmd!(info "   " ${count_1st})?;
 
-        let mut writer_1st_replace = writer("1st.replace.tsv");
+        let mut writer_1st_replace = writer("1st.replace.tsv")?;
         for i in 1..=serial {
             let infile = format!("replace.{}", i);
             if Path::new(infile.as_str()).is_file() {
-                let reader = reader(&infile);
+                let reader = reader(&infile)?;
                 for line in reader.lines().map_while(Result::ok) {
                     let parts: Vec<&str> = line.split('\t').collect();
                     if parts.len() == 2 {
@@ -10,6 +10,7 @@ pub fn make_subcommand() -> Command {
         .after_help(
             r###"
 * Lines without a valid range will not be output
+* `.bed` and `.bed.gz` files are read as BED; ranges come from the first three columns
 * Appended fields
     * `prop`
     * `length`: length of the range (if `--full` is set)
@@ -71,6 +72,12 @@ Example:
                 .action(ArgAction::SetTrue)
                 .help("Prefix the basename of the runlist file if `--header` is set"),
         )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -86,12 +93,18 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Args
     //----------------------------
-    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap());
+    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap())?;
 
     let is_sharp = args.get_flag("sharp");
     let is_header = args.get_flag("header");
 
     let opt_idx_range = args.get_one::<usize>("field").copied().unwrap_or(0);
+    // The field of malformed ranges in errors
+    let opt_field = if opt_idx_range == 0 {
+        None
+    } else {
+        Some(opt_idx_range)
+    };
 
     let is_full = args.get_flag("full");
     let is_prefix = args.get_flag("prefix");
@@ -99,14 +112,16 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Loading
     //----------------------------
-    let json = intspan::read_json(args.get_one::<String>("runlist").unwrap());
-    let set = intspan::json2set(&json);
+    let json = intspan::read_json(args.get_one::<String>("runlist").unwrap())?;
+    let set = intspan::json2set(&json)?;
 
     //----------------------------
     // Ops
     //----------------------------
+    let mut skipped = intspan::Skipped::new(args.get_flag("strict"));
     for infile in args.get_many::<String>("infiles").unwrap() {
-        let reader = intspan::reader(infile);
+        let reader = intspan::reader(infile)?;
+        let is_bed = intspan::is_bed(infile);
         'LINE: for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
             // Handle the header line
             if is_header && i == 0 {
@@ -147,11 +162,23 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
                 continue 'LINE;
             }
 
-            let rg = match intspan::extract_rg(&line, opt_idx_range) {
+            // Skip empty lines, and the track and browser lines of BED files
+            if intspan::is_comment(&line, is_bed) {
+                continue 'LINE;
+            }
+
+            let rg = match intspan::extract_rg_bed(&line, opt_idx_range, is_bed) {
                 // Extract the range
                 Some(range) => range,
                 // Skip lines without a valid range
-                None => continue 'LINE,
+                None => {
+                    skipped.skip(intspan::InputError::parse("range", &line).at(
+                        infile,
+                        i + 1,
+                        opt_field,
+                    ))?;
+                    continue 'LINE;
+                }
             };
 
             // Calculate intersection
@@ -181,5 +208,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         }
     }
 
+    skipped.report();
+
     Ok(())
 }
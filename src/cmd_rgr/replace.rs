@@ -54,14 +54,14 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Args
     //----------------------------
-    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap());
-    let reader = intspan::reader(args.get_one::<String>("infile").unwrap());
+    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap())?;
+    let reader = intspan::reader(args.get_one::<String>("infile").unwrap())?;
 
     //----------------------------
     // Load replacements
     //----------------------------
     let mut replaces: HashMap<String, String> = HashMap::new();
-    for line in intspan::read_lines(args.get_one::<String>("replace").unwrap()) {
+    for line in intspan::read_lines(args.get_one::<String>("replace").unwrap())? {
         let parts: Vec<&str> = line.split('\t').collect();
         if parts.len() == 2 {
             if args.get_flag("reverse") {
@@ -8,6 +8,10 @@ pub fn make_subcommand() -> Command {
         .after_help(
             r###"
 * Lines without a valid range will not be output
+* `.bed` and `.bed.gz` files are read as BED; ranges come from the first three columns
+* With a stranded runlist, ranges are compared with the runlist of the same strand
+    * Ranges without a strand are compared with both strands merged
+    * With `--merge-strands`, all ranges are compared with both strands merged
 
 Example:
 
@@ -17,6 +21,9 @@ Example:
     # # Filter lines that overlap with the runlist in a TSV file with headers
     rgr runlist tests/rgr/intergenic.json tests/rgr/ctg.range.tsv --op overlap -H -f 3
 
+    # Filter lines that overlap with the runlist of the same strand
+    rgr runlist tests/rgr/stranded.json tests/rgr/S288c.rg --op overlap
+
 "###,
         )
         .arg(
@@ -68,6 +75,18 @@ Example:
                 .default_value("overlap")
                 .help("Filter operation: overlap, non-overlap or superset"),
         )
+        .arg(
+            Arg::new("merge_strands")
+                .long("merge-strands")
+                .action(ArgAction::SetTrue)
+                .help("Merge strands of a stranded runlist"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -83,7 +102,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Args
     //----------------------------
-    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap());
+    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap())?;
 
     let opt_op = args.get_one::<String>("op").unwrap().as_str();
 
@@ -91,18 +110,34 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     let is_header = args.get_flag("header");
 
     let opt_idx_range = args.get_one::<usize>("field").copied().unwrap_or(0);
+    // The field of malformed ranges in errors
+    let opt_field = if opt_idx_range == 0 {
+        None
+    } else {
+        Some(opt_idx_range)
+    };
 
     //----------------------------
     // Loading
     //----------------------------
-    let json = intspan::read_json(args.get_one::<String>("runlist").unwrap());
-    let set = intspan::json2set(&json);
+    let json = intspan::read_json(args.get_one::<String>("runlist").unwrap())?;
+    let is_stranded = intspan::is_stranded(&json) && !args.get_flag("merge_strands");
+    // strand => chr => IntSpan, with both strands merged in ""
+    let mut set_of = std::collections::BTreeMap::new();
+    if intspan::is_stranded(&json) {
+        set_of = intspan::json2set_stranded(&json)?;
+        set_of.insert("".to_string(), intspan::merge_strands(&set_of));
+    } else {
+        set_of.insert("".to_string(), intspan::json2set(&json)?);
+    }
 
     //----------------------------
     // Ops
     //----------------------------
+    let mut skipped = intspan::Skipped::new(args.get_flag("strict"));
     for infile in args.get_many::<String>("infiles").unwrap() {
-        let reader = intspan::reader(infile);
+        let reader = intspan::reader(infile)?;
+        let is_bed = intspan::is_bed(infile);
         'LINE: for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
             // Handle the header line
             if is_header && i == 0 {
@@ -118,15 +153,32 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
                 continue 'LINE;
             }
 
-            let rg = match intspan::extract_rg(&line, opt_idx_range) {
+            // Skip empty lines, and the track and browser lines of BED files
+            if intspan::is_comment(&line, is_bed) {
+                continue 'LINE;
+            }
+
+            let rg = match intspan::extract_rg_bed(&line, opt_idx_range, is_bed) {
                 // Extract the range
                 Some(range) => range,
                 // Skip lines without a valid range
-                None => continue 'LINE,
+                None => {
+                    skipped.skip(intspan::InputError::parse("range", &line).at(
+                        infile,
+                        i + 1,
+                        opt_field,
+                    ))?;
+                    continue 'LINE;
+                }
             };
 
             // Prepare the range for comparison
             let chr = rg.chr();
+            let set = if is_stranded && (rg.strand() == "+" || rg.strand() == "-") {
+                set_of.get(rg.strand()).unwrap()
+            } else {
+                set_of.get("").unwrap()
+            };
             let mut intspan = intspan::IntSpan::new();
             intspan.add_pair(*rg.start(), *rg.end());
 
@@ -160,5 +212,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         }
     }
 
+    skipped.report();
+
     Ok(())
 }
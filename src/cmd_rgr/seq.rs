@@ -0,0 +1,161 @@
+use clap::*;
+use std::io::BufRead;
+
+// Create clap subcommand arguments
+pub fn make_subcommand() -> Command {
+    Command::new("seq")
+        .about("Extract sequences of ranges from a FASTA file")
+        .after_help(
+            r###"
+* <fasta> can be plain or bgzipped
+    * The `.fai` index is read if it exists, otherwise it's built in memory
+    * `--fai` saves the built index as `<fasta>.fai`
+* Output is in FASTA format, with the range as the header
+* Ranges on the negative strand, `(-)`, are reverse complemented
+* Lines without a valid range are skipped, as are ranges on unknown sequences
+  or starting beyond the end of the sequence
+* `.bed` and `.bed.gz` files are read as BED; ranges come from the first three columns
+
+Example:
+
+    rgr seq tests/fasr/NC_000932.fa tests/rgr/NC_000932.rg
+
+    rgr seq tests/fasr/NC_000932.fa.gz tests/rgr/NC_000932.tsv -H -f 2
+
+"###,
+        )
+        .arg(
+            Arg::new("fasta")
+                .required(true)
+                .index(1)
+                .num_args(1)
+                .help("FASTA file to extract sequences from"),
+        )
+        .arg(
+            Arg::new("infiles")
+                .required(true)
+                .index(2)
+                .num_args(1..)
+                .help("Input files to process. Multiple files can be specified"),
+        )
+        .arg(
+            Arg::new("header")
+                .long("header")
+                .short('H')
+                .action(ArgAction::SetTrue)
+                .help("Treat the first line of each file as a header"),
+        )
+        .arg(
+            Arg::new("field")
+                .long("field")
+                .short('f')
+                .num_args(1)
+                .value_parser(value_parser!(usize))
+                .help("Index of the range field. If not set, the first valid range will be used"),
+        )
+        .arg(
+            Arg::new("fai")
+                .long("fai")
+                .action(ArgAction::SetTrue)
+                .help("Save the .fai index next to <fasta> if it doesn't exist"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
+        .arg(
+            Arg::new("outfile")
+                .long("outfile")
+                .short('o')
+                .num_args(1)
+                .default_value("stdout")
+                .help("Output filename. [stdout] for screen"),
+        )
+}
+
+// command implementation
+pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
+    //----------------------------
+    // Args
+    //----------------------------
+    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap())?;
+
+    let is_header = args.get_flag("header");
+
+    let opt_idx_range = args.get_one::<usize>("field").copied().unwrap_or(0);
+    // The field of malformed ranges in errors
+    let opt_field = if opt_idx_range == 0 {
+        None
+    } else {
+        Some(opt_idx_range)
+    };
+
+    //----------------------------
+    // Loading
+    //----------------------------
+    let fasta = args.get_one::<String>("fasta").unwrap();
+    let mut fa_reader = intspan::FaidxReader::from_path(fasta)?;
+
+    let fai = format!("{}.fai", fasta);
+    if args.get_flag("fai") && !std::path::Path::new(&fai).is_file() {
+        intspan::write_fai(&fai, fa_reader.records())?;
+    }
+
+    //----------------------------
+    // Ops
+    //----------------------------
+    let mut skipped = intspan::Skipped::new(args.get_flag("strict"));
+    for infile in args.get_many::<String>("infiles").unwrap() {
+        let reader = intspan::reader(infile)?;
+        let is_bed = intspan::is_bed(infile);
+        'LINE: for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+            // Skip the header line, comments and empty lines
+            if (is_header && i == 0) || intspan::is_comment(&line, is_bed) {
+                continue 'LINE;
+            }
+
+            let rg = match intspan::extract_rg_bed(&line, opt_idx_range, is_bed) {
+                // Extract the range
+                Some(range) => range,
+                // Skip lines without a valid range
+                None => {
+                    skipped.skip(intspan::InputError::parse("range", &line).at(
+                        infile,
+                        i + 1,
+                        opt_field,
+                    ))?;
+                    continue 'LINE;
+                }
+            };
+
+            // Skip ranges outside of the sequences
+            let is_inside = match fa_reader.length(rg.chr()) {
+                Some(length) => *rg.start() as u64 <= length,
+                None => false,
+            };
+            if !is_inside {
+                skipped.skip(
+                    intspan::InputError::parse("range of the FASTA file", &rg.to_string()).at(
+                        infile,
+                        i + 1,
+                        opt_field,
+                    ),
+                )?;
+                continue 'LINE;
+            }
+
+            let seq = fa_reader.fetch_range(&rg)?;
+
+            //----------------------------
+            // Output
+            //----------------------------
+            writer.write_fmt(format_args!(">{}\n{}\n", rg, seq))?;
+        }
+    }
+
+    skipped.report();
+
+    Ok(())
+}
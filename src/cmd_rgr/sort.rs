@@ -1,7 +1,8 @@
 use clap::*;
-use itertools::Itertools;
-use std::collections::BTreeMap;
-use std::io::BufRead;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
 
 // Create clap subcommand arguments
 pub fn make_subcommand() -> Command {
@@ -9,10 +10,17 @@ pub fn make_subcommand() -> Command {
         .about("Sort .rg and .tsv files by a range field")
         .after_help(
             r###"
-* If no part of the line is a valid range, the line will be written to to the end of the output
+* If no part of the line is a valid range, the line will be written to the end of the output
+    * Such lines are reported as malformed, and abort the command with `--strict`, except empty lines and comments
+* `.bed` and `.bed.gz` files are read as BED; ranges come from the first three columns
 
 * Using `--group` can improve performance on large datasets by grouping rows before sorting.
     * The group_key can be chr_id, ctg_id, etc.
+* Duplicated lines are written once
+
+* Memory usage is bounded by `--buffer-size`
+    * Sorted chunks are spilled to temp files and merged at the end
+    * Set `TMPDIR` to place temp files in another directory
 
 Example:
 
@@ -57,6 +65,20 @@ Example:
                 .value_parser(value_parser!(usize))
                 .help("Group the rows by this field and then sort within each group"),
         )
+        .arg(
+            Arg::new("buffer")
+                .long("buffer-size")
+                .short('S')
+                .num_args(1)
+                .default_value("1G")
+                .help("Size of the memory buffer, with an optional suffix of K, M or G"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -67,26 +89,44 @@ Example:
         )
 }
 
+// (group, chr, start, strand)
+type SortKey = (String, String, i64, String);
+
 // command implementation
 pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Options
     //----------------------------
-    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap());
+    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap())?;
 
     let is_header = args.get_flag("header");
 
     let opt_idx_range = args.get_one::<usize>("field").copied().unwrap_or(0);
+    // The field of malformed ranges in errors
+    let opt_field = if opt_idx_range == 0 {
+        None
+    } else {
+        Some(opt_idx_range)
+    };
     let opt_idx_group = args.get_one::<usize>("group").copied().unwrap_or(0);
+    let opt_buffer = intspan::parse_size(args.get_one::<String>("buffer").unwrap())?;
 
     //----------------------------
     // Loading
     //----------------------------
-    let mut line_to_rg: BTreeMap<String, intspan::Range> = BTreeMap::new();
-    let mut invalids: Vec<String> = vec![];
+    // Sorted chunks spilled to temp files
+    let mut runs: Vec<File> = vec![];
+    let mut chunk: Vec<(SortKey, String)> = vec![];
+    let mut chunk_size: usize = 0;
+
+    // Lines without valid ranges, in the original order
+    let mut invalids = BufWriter::new(tempfile::tempfile()?);
 
+    // Lines without valid ranges are kept at the end, but still reported
+    let mut skipped = intspan::Skipped::new(args.get_flag("strict"));
     for infile in args.get_many::<String>("infiles").unwrap() {
-        let reader = intspan::reader(infile);
+        let reader = intspan::reader(infile)?;
+        let is_bed = intspan::is_bed(infile);
         'LINE: for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
             // Handle the header line
             if is_header && i == 0 {
@@ -95,63 +135,119 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
             }
 
             // Extract the range
-            if let Some(range) = intspan::extract_rg(&line, opt_idx_range) {
-                // Store the line and its range
-                line_to_rg.insert(line.clone(), range);
+            if let Some(range) = intspan::extract_rg_bed(&line, opt_idx_range, is_bed) {
+                let group = if opt_idx_group == 0 {
+                    ""
+                } else {
+                    line.split('\t').nth(opt_idx_group - 1).unwrap_or("")
+                };
+                let key = (
+                    group.to_string(),
+                    range.chr().to_string(),
+                    *range.start(),
+                    range.strand().to_string(),
+                );
+
+                // Rough size of a record in memory
+                chunk_size += line.len() * 2 + 128;
+                chunk.push((key, line));
+
+                if chunk_size > opt_buffer {
+                    runs.push(spill(&mut chunk)?);
+                    chunk_size = 0;
+                }
             } else {
                 // No valid range found
-                invalids.push(line.clone());
+                if !intspan::is_comment(&line, is_bed) {
+                    skipped.skip(intspan::InputError::parse("range", &line).at(
+                        infile,
+                        i + 1,
+                        opt_field,
+                    ))?;
+                }
+                invalids.write_fmt(format_args!("{}\n", line))?;
             }
         }
     }
+    skipped.report();
 
     //----------------------------
     // Sorting
     //----------------------------
-    let mut sorted: Vec<String> = vec![];
-
-    if opt_idx_group == 0 {
-        // Sort all lines together
-        sorted = line_to_rg.keys().map(|e| e.to_string()).collect();
-
-        sorted.sort_by_cached_key(|k| {
-            let range = line_to_rg.get(k).unwrap();
-            (range.chr().clone(), range.start(), range.strand().clone())
-        });
-    } else {
-        // Group lines by the specified field, then sort within each group
-        let mut lines_of: BTreeMap<String, Vec<String>> = BTreeMap::new();
-
-        for line in line_to_rg.keys() {
-            let parts: Vec<&str> = line.split('\t').collect();
-
-            let group_key = parts.get(opt_idx_group - 1).unwrap();
-            lines_of
-                .entry(group_key.to_string())
-                .or_default()
-                .push(line.clone());
-        }
-
-        for group_key in lines_of.keys().sorted() {
-            let mut lines = lines_of.get(group_key).unwrap().clone();
+    // Sort by groups, then by chr, start and strand, then by lines
+    chunk.sort();
+    chunk.dedup();
+
+    let mut sources: Vec<Box<dyn Iterator<Item = (SortKey, String)>>> = vec![];
+    for run in runs {
+        sources.push(Box::new(
+            BufReader::new(run)
+                .lines()
+                .map_while(Result::ok)
+                .map(|record| from_record(&record)),
+        ));
+    }
+    sources.push(Box::new(chunk.into_iter()));
 
-            lines.sort_by_cached_key(|k| {
-                let range = line_to_rg.get(k).unwrap();
-                (range.chr().clone(), range.start(), range.strand().clone())
-            });
-            sorted.extend(lines);
+    // k-way merge
+    let mut heap: BinaryHeap<Reverse<(SortKey, String, usize)>> = BinaryHeap::new();
+    for (idx, source) in sources.iter_mut().enumerate() {
+        if let Some((key, line)) = source.next() {
+            heap.push(Reverse((key, line, idx)));
         }
     }
 
     //----------------------------
     // Output
     //----------------------------
-    for line in &sorted {
-        writer.write_fmt(format_args!("{}\n", line))?;
-    }
-    for line in &invalids {
+    let mut last: Option<(SortKey, String)> = None;
+    while let Some(Reverse((key, line, idx))) = heap.pop() {
+        if let Some((next_key, next_line)) = sources[idx].next() {
+            heap.push(Reverse((next_key, next_line, idx)));
+        }
+
+        // The same line in different chunks
+        if let Some((last_key, last_line)) = &last {
+            if *last_key == key && *last_line == line {
+                continue;
+            }
+        }
         writer.write_fmt(format_args!("{}\n", line))?;
+        last = Some((key, line));
     }
 
+    let mut invalids = invalids.into_inner()?;
+    invalids.seek(SeekFrom::Start(0))?;
+    std::io::copy(&mut invalids, &mut writer)?;
+
     Ok(())
 }
+
+// Sort a chunk and write it to a temp file
+fn spill(chunk: &mut Vec<(SortKey, String)>) -> anyhow::Result<File> {
+    chunk.sort();
+    chunk.dedup();
+
+    let mut writer = BufWriter::new(tempfile::tempfile()?);
+    for ((group, chr, start, strand), line) in chunk.drain(..) {
+        writer.write_fmt(format_args!(
+            "{}\t{}\t{}\t{}\t{}\n",
+            group, chr, start, strand, line
+        ))?;
+    }
+
+    let mut file = writer.into_inner()?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(file)
+}
+
+fn from_record(record: &str) -> (SortKey, String) {
+    let parts: Vec<&str> = record.splitn(5, '\t').collect();
+    let key = (
+        parts[0].to_string(),
+        parts[1].to_string(),
+        parts[2].parse::<i64>().unwrap(),
+        parts[3].to_string(),
+    );
+    (key, parts[4].to_string())
+}
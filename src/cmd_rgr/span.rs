@@ -8,6 +8,7 @@ pub fn make_subcommand() -> Command {
         .after_help(
             r###"
 This command is similar to `spanr span`, but the <infiles> represent chromosome ranges.
+`.bed` and `.bed.gz` files are read as BED; ranges come from the first three columns.
 
 List of Operations
 
@@ -85,7 +86,7 @@ List of Operations
                 .long("number")
                 .short('n')
                 .num_args(1)
-                .value_parser(value_parser!(i32))
+                .value_parser(value_parser!(i64))
                 .default_value("0")
                 .help("Number of integers to trim, pad, shift, or flank"),
         )
@@ -96,6 +97,12 @@ List of Operations
                 .action(ArgAction::SetTrue)
                 .help("Append a field for the new range (default: only write the new range)"),
         )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -111,24 +118,32 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Args
     //----------------------------
-    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap());
+    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap())?;
 
     let is_header = args.get_flag("header");
     let is_sharp = args.get_flag("sharp");
 
     let opt_idx_range = args.get_one::<usize>("field").copied().unwrap_or(0);
+    // The field of malformed ranges in errors
+    let opt_field = if opt_idx_range == 0 {
+        None
+    } else {
+        Some(opt_idx_range)
+    };
 
     let opt_op = args.get_one::<String>("op").unwrap().as_str();
     let opt_mode = args.get_one::<String>("mode").unwrap().as_str();
-    let opt_number = *args.get_one::<i32>("number").unwrap();
+    let opt_number = *args.get_one::<i64>("number").unwrap();
 
     let is_append = args.get_flag("append");
 
     //----------------------------
     // Ops
     //----------------------------
+    let mut skipped = intspan::Skipped::new(args.get_flag("strict"));
     for infile in args.get_many::<String>("infiles").unwrap() {
-        let reader = intspan::reader(infile);
+        let reader = intspan::reader(infile)?;
+        let is_bed = intspan::is_bed(infile);
         'LINE: for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
             // Handle the header line
             if is_header && i == 0 {
@@ -148,11 +163,23 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
                 continue 'LINE;
             }
 
-            let rg = match intspan::extract_rg(&line, opt_idx_range) {
+            // Skip empty lines, and the track and browser lines of BED files
+            if intspan::is_comment(&line, is_bed) {
+                continue 'LINE;
+            }
+
+            let rg = match intspan::extract_rg_bed(&line, opt_idx_range, is_bed) {
                 // Extract the range
                 Some(range) => range,
                 // Skip lines without a valid range
-                None => continue 'LINE,
+                None => {
+                    skipped.skip(intspan::InputError::parse("range", &line).at(
+                        infile,
+                        i + 1,
+                        opt_field,
+                    ))?;
+                    continue 'LINE;
+                }
             };
 
             let new = match opt_op {
@@ -199,5 +226,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         }
     }
 
+    skipped.report();
+
     Ok(())
 }
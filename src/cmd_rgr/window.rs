@@ -0,0 +1,211 @@
+use clap::*;
+use std::io::BufRead;
+
+// Create clap subcommand arguments
+pub fn make_subcommand() -> Command {
+    Command::new("window")
+        .about("Join ranges within a window of the target ranges")
+        .after_help(
+            r###"
+* For each range in <target>, lines of <infiles> within the window are appended
+    * One line for each pair, lines without pairs are not output
+    * With `--header`, headers of <target> and the first file of <infiles> are joined
+    * The window extends the target range by `--left` and `--right` bp
+    * `--window` sets both sides, `--left` and `--right` override it
+* With `--strand`, left and right mean upstream and downstream of the target ranges
+    * Ranges without a strand are treated as on the positive strand
+* `--field` applies to both <target> and <infiles>
+* `.bed` and `.bed.gz` files are read as BED; ranges come from the first three columns
+
+Example:
+
+    rgr window tests/rgr/S288c.rg tests/rgr/genes.rg -w 50
+
+    rgr window tests/rgr/S288c.rg tests/rgr/genes.rg -l 100 -r 0 --strand
+
+    rgr window tests/rgr/ctg.range.tsv tests/rgr/ctg.range.tsv -H -f 3 -w 1
+
+"###,
+        )
+        .arg(
+            Arg::new("target")
+                .required(true)
+                .index(1)
+                .num_args(1)
+                .help("Target file"),
+        )
+        .arg(
+            Arg::new("infiles")
+                .required(true)
+                .index(2)
+                .num_args(1..)
+                .help("Files to join. Multiple files can be specified"),
+        )
+        .arg(
+            Arg::new("header")
+                .long("header")
+                .short('H')
+                .action(ArgAction::SetTrue)
+                .help("Treat the first line of each file as a header"),
+        )
+        .arg(
+            Arg::new("sharp")
+                .long("sharp")
+                .short('s')
+                .action(ArgAction::SetTrue)
+                .help("Include lines starting with `#` without changes (default: ignore them)"),
+        )
+        .arg(
+            Arg::new("field")
+                .long("field")
+                .short('f')
+                .num_args(1)
+                .value_parser(value_parser!(usize))
+                .help("Index of the range field. If not set, the first valid range will be used"),
+        )
+        .arg(
+            Arg::new("window")
+                .long("window")
+                .short('w')
+                .num_args(1)
+                .default_value("1000")
+                .value_parser(value_parser!(i64))
+                .help("Size of the window on both sides"),
+        )
+        .arg(
+            Arg::new("left")
+                .long("left")
+                .short('l')
+                .num_args(1)
+                .value_parser(value_parser!(i64))
+                .help("Size of the window on the left side"),
+        )
+        .arg(
+            Arg::new("right")
+                .long("right")
+                .short('r')
+                .num_args(1)
+                .value_parser(value_parser!(i64))
+                .help("Size of the window on the right side"),
+        )
+        .arg(
+            Arg::new("strand")
+                .long("strand")
+                .action(ArgAction::SetTrue)
+                .help("Left and right are defined by the strand of target ranges"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
+        .arg(
+            Arg::new("outfile")
+                .long("outfile")
+                .short('o')
+                .num_args(1)
+                .default_value("stdout")
+                .help("Output filename. [stdout] for screen"),
+        )
+}
+
+// command implementation
+pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
+    //----------------------------
+    // Args
+    //----------------------------
+    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap())?;
+
+    let is_sharp = args.get_flag("sharp");
+    let is_header = args.get_flag("header");
+
+    let opt_idx_range = args.get_one::<usize>("field").copied().unwrap_or(0);
+    // The field of malformed ranges in errors
+    let opt_field = if opt_idx_range == 0 {
+        None
+    } else {
+        Some(opt_idx_range)
+    };
+
+    let opt_window = *args.get_one::<i64>("window").unwrap();
+    let opt_left = args.get_one::<i64>("left").copied().unwrap_or(opt_window);
+    let opt_right = args.get_one::<i64>("right").copied().unwrap_or(opt_window);
+    let is_strand = args.get_flag("strand");
+
+    //----------------------------
+    // Loading
+    //----------------------------
+    let mut skipped = intspan::Skipped::new(args.get_flag("strict"));
+    let (index, joined_header, _) = super::load_joined(
+        args.get_many::<String>("infiles").unwrap(),
+        is_header,
+        opt_idx_range,
+        &mut skipped,
+    )?;
+
+    //----------------------------
+    // Operating
+    //----------------------------
+    let target = args.get_one::<String>("target").unwrap();
+    let reader = intspan::reader(target)?;
+    let is_bed = intspan::is_bed(target);
+    'LINE: for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+        // Handle the header line
+        if is_header && i == 0 {
+            writer.write_fmt(format_args!(
+                "{}\t{}\n",
+                line,
+                joined_header.as_deref().unwrap_or("window")
+            ))?;
+            continue 'LINE;
+        }
+
+        // Skip empty lines, comments, and the track and browser lines of BED files
+        if intspan::is_comment(&line, is_bed) {
+            // Lines starting with '#' are kept with --sharp
+            if is_sharp && line.starts_with('#') {
+                writer.write_fmt(format_args!("{}\n", line))?;
+            }
+            continue 'LINE;
+        }
+
+        let rg = match intspan::extract_rg_bed(&line, opt_idx_range, is_bed) {
+            // Extract the range
+            Some(range) => range,
+            // Skip lines without a valid range
+            None => {
+                skipped.skip(intspan::InputError::parse("range", &line).at(
+                    target,
+                    i + 1,
+                    opt_field,
+                ))?;
+                continue 'LINE;
+            }
+        };
+
+        // Extend the range to the window
+        let (left, right) = if is_strand && rg.strand() == "-" {
+            (opt_right, opt_left)
+        } else {
+            (opt_left, opt_right)
+        };
+        let start = (*rg.start() - left).max(1);
+        let end = *rg.end() + right;
+        if start > end {
+            continue 'LINE;
+        }
+        let window = intspan::Range::from(rg.chr(), start, end);
+
+        //----------------------------
+        // Output
+        //----------------------------
+        for (_, joined) in index.find(&window) {
+            writer.write_fmt(format_args!("{}\t{}\n", line, joined))?;
+        }
+    }
+
+    skipped.report();
+
+    Ok(())
+}
@@ -1,7 +1,5 @@
 use clap::*;
 use intspan::*;
-use serde_json::Value;
-use std::collections::BTreeMap;
 
 // Create clap subcommand arguments
 pub fn make_subcommand() -> Command {
@@ -12,6 +10,16 @@ pub fn make_subcommand() -> Command {
 It's expected that the JSON file contains multiple sets of runlists,
 otherwise this command will make no effects
 
+Chromosomes missing from a set other than the first one are left unchanged by it
+
+* union and intersect sweep all sets at once
+* atleast keeps spans present in at least `-k` sets
+* diff and xor are applied from the first set to the last one
+
+Example:
+
+    spanr combine tests/spanr/Atha.json --op atleast -k 2
+
 "###,
         )
         .arg(
@@ -25,8 +33,23 @@ otherwise this command will make no effects
                 .long("op")
                 .num_args(1)
                 .default_value("union")
-                .value_parser(clap::builder::NonEmptyStringValueParser::new())
-                .help("Operations: intersect, union, diff or xor"),
+                .value_parser([
+                    builder::PossibleValue::new("union"),
+                    builder::PossibleValue::new("intersect"),
+                    builder::PossibleValue::new("diff"),
+                    builder::PossibleValue::new("xor"),
+                    builder::PossibleValue::new("atleast"),
+                ])
+                .help("Operations: intersect, union, diff, xor or atleast"),
+        )
+        .arg(
+            Arg::new("k")
+                .long("k")
+                .short('k')
+                .num_args(1)
+                .default_value("1")
+                .value_parser(builder::RangedU64ValueParser::<usize>::new().range(1..))
+                .help("Minimal number of sets for --op atleast"),
         )
         .arg(
             Arg::new("outfile")
@@ -43,47 +66,47 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Loading
     //----------------------------
-    let json: BTreeMap<String, Value> = read_json(args.get_one::<String>("infile").unwrap());
-    let s_of = json2set_m(&json);
-    let chrs = chrs_in_sets(&s_of);
+    let set_of = GenomeSets::read(args.get_one::<String>("infile").unwrap())?;
 
     let op = args.get_one::<String>("op").unwrap().as_str();
+    let opt_k = *args.get_one::<usize>("k").unwrap();
 
     //----------------------------
     // Operating
     //----------------------------
-    let mut res: BTreeMap<String, IntSpan> = BTreeMap::new();
-    fill_up_s(&mut res, &chrs);
-
-    let names: Vec<_> = s_of.keys().cloned().collect();
-    let first = names[0].clone();
-
-    for name in names {
-        let set = s_of.get(name.as_str()).unwrap();
-        for chr in set.keys() {
-            if name == first {
-                let intspan = set.get(chr).unwrap();
-                res.entry(chr.to_string()).and_modify(|e| e.merge(intspan));
-            } else {
-                let mut intspan_op = res.get(chr).unwrap().copy();
-                intspan_op = match op {
-                    "intersect" => intspan_op.intersect(set.get(chr).unwrap()),
-                    "diff" => intspan_op.diff(set.get(chr).unwrap()),
-                    "union" => intspan_op.union(set.get(chr).unwrap()),
-                    "xor" => intspan_op.xor(set.get(chr).unwrap()),
-                    _ => panic!("Invalid IntSpan Op"),
-                };
-                //                eprintln!("Op {}: {}", op, intspan_op.to_string());
-                res.insert(chr.into(), intspan_op);
+    let mut sets = set_of.iter().map(|(_, set)| set);
+    let mut res = match op {
+        "union" => GenomeSet::union_all(sets),
+        "intersect" => {
+            // Chromosomes of the first set, intersected with the sets containing them
+            let mut res = GenomeSet::new();
+            if let Some(first) = sets.next() {
+                for (chr, ints) in first {
+                    let others = set_of.iter().skip(1).filter_map(|(_, set)| set.get(chr));
+                    res.insert(
+                        chr,
+                        IntSpan::intersect_all(std::iter::once(ints).chain(others)),
+                    );
+                }
+            }
+            res
+        }
+        "atleast" => GenomeSet::at_least(sets, opt_k),
+        _ => {
+            // diff or xor with chromosomes missing from a set leaves them unchanged
+            let mut res = sets.next().cloned().unwrap_or_default();
+            for set in sets {
+                res = res.op(op, set)?;
             }
+            res
         }
-    }
+    };
+    res.fill_up(&set_of.chrs());
 
     //----------------------------
     // Output
     //----------------------------
-    let out_json = set2json(&res);
-    write_json(args.get_one::<String>("outfile").unwrap(), &out_json)?;
+    res.write(args.get_one::<String>("outfile").unwrap())?;
 
     Ok(())
 }
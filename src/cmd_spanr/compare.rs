@@ -7,7 +7,18 @@ use std::collections::BTreeMap;
 pub fn make_subcommand() -> Command {
     Command::new("compare")
         .about("Compare one JSON file against others")
-        .after_help("Only the *first* file can contain multiple sets of runlists")
+        .after_help(
+            r###"
+Only the *first* file can contain multiple sets of runlists
+
+Stranded runlists are supported
+    * If the first file is stranded, each strand is compared with the same strand of others
+        * Unstranded files are compared with both strands
+    * Otherwise, strands of other files are merged
+    * With `--merge-strands`, all stranded runlists are merged before comparing
+
+"###,
+        )
         .arg(
             Arg::new("infile")
                 .required(true)
@@ -29,6 +40,21 @@ pub fn make_subcommand() -> Command {
                 .value_parser(clap::builder::NonEmptyStringValueParser::new())
                 .help("Operations: intersect, union, diff or xor"),
         )
+        .arg(
+            Arg::new("merge_strands")
+                .long("merge-strands")
+                .action(ArgAction::SetTrue)
+                .help("Merge strands of stranded runlists"),
+        )
+        .arg(
+            Arg::new("parallel")
+                .long("parallel")
+                .short('p')
+                .num_args(1)
+                .default_value("1")
+                .value_parser(value_parser!(usize))
+                .help("Number of threads"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -44,67 +70,107 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Loading
     //----------------------------
+    let is_merge_strands = args.get_flag("merge_strands");
+
     // first file
-    let json: BTreeMap<String, Value> = read_json(args.get_one::<String>("infile").unwrap());
-    let is_multi: bool = json.values().next().unwrap().is_object();
-    let mut s1_of = json2set_m(&json);
+    let json: BTreeMap<String, Value> = read_json(args.get_one::<String>("infile").unwrap())?;
+    let mut is_stranded = intspan::is_stranded(&json);
+    let mut s1_of = if is_stranded {
+        let set_of = json2set_stranded(&json)?;
+        if is_merge_strands {
+            is_stranded = false;
+            GenomeSets::single(merge_strands(&set_of).into())
+        } else {
+            set_of.into()
+        }
+    } else {
+        GenomeSets::from_json(&json)?
+    };
 
     // second file or more
-    let mut s2s = vec![];
+    // Sets of each strand, or "__single"
+    let mut s2s: Vec<GenomeSets> = vec![];
 
     for infile in args.get_many::<String>("infiles").unwrap() {
-        let json_s = read_json(infile);
-        let s2 = json2set(&json_s);
-        s2s.push(s2);
+        let json_s = read_json(infile)?;
+        let s2_of = if intspan::is_stranded(&json_s) {
+            let set_of = json2set_stranded(&json_s)?;
+            if is_stranded {
+                set_of.into()
+            } else {
+                GenomeSets::single(merge_strands(&set_of).into())
+            }
+        } else {
+            GenomeSets::single(GenomeSet::from_json(&json_s)?)
+        };
+        s2s.push(s2_of);
     }
 
     let op = args.get_one::<String>("op").unwrap().as_str();
+    let opt_parallel = *args.get_one::<usize>("parallel").unwrap();
 
     //----------------------------
     // Operating
     //----------------------------
     // give empty intspan to non-existed chrs
-    let mut chrs = chrs_in_sets(&s1_of);
-    for s2 in &s2s {
-        for chr in s2.keys() {
-            chrs.insert(chr.to_string());
-        }
+    let mut chrs = s1_of.chrs();
+    for s2_of in &s2s {
+        chrs.extend(s2_of.chrs());
     }
-    fill_up_m(&mut s1_of, &chrs);
+    s1_of.fill_up(&chrs);
 
-    for s2 in s2s.iter_mut() {
-        fill_up_s(s2, &chrs);
+    for s2_of in s2s.iter_mut() {
+        s2_of.fill_up(&chrs);
     }
 
-    let mut res_of: BTreeMap<String, BTreeMap<String, IntSpan>> = BTreeMap::new();
-    for (name, s1) in &s1_of {
-        let mut res: BTreeMap<String, IntSpan> = BTreeMap::new();
-        for chr in s1.keys() {
-            let mut intspan_op = s1.get(chr).unwrap().copy();
-            for s2 in s2s.iter() {
-                intspan_op = match op {
-                    "intersect" => intspan_op.intersect(s2.get(chr).unwrap()),
-                    "diff" => intspan_op.diff(s2.get(chr).unwrap()),
-                    "union" => intspan_op.union(s2.get(chr).unwrap()),
-                    "xor" => intspan_op.xor(s2.get(chr).unwrap()),
-                    _ => panic!("Invalid IntSpan Op"),
-                };
-                //                eprintln!("Op {}: {}", op, intspan_op.to_string());
-            }
-            res.insert(chr.into(), intspan_op);
+    // (name, chr) pairs are operated in parallel
+    let f = intspan_op(op)?;
+    let pairs: Vec<(&String, &String)> = s1_of
+        .iter()
+        .flat_map(|(name, s1)| s1.chrs().map(move |chr| (name, chr)))
+        .collect();
+    let intspans = par_map(&pairs, opt_parallel, |(name, chr)| {
+        let s1 = &s1_of[name.as_str()][chr.as_str()];
+        // The same strand, or the unstranded one
+        let others: Vec<&IntSpan> = s2s
+            .iter()
+            .map(|s2_of| {
+                let s2 = s2_of
+                    .get(name)
+                    .or_else(|| s2_of.get(GenomeSets::SINGLE))
+                    .unwrap();
+                &s2[chr.as_str()]
+            })
+            .collect();
+
+        // Sweep all sets at once, except for xor
+        match op {
+            "union" => IntSpan::union_all(std::iter::once(s1).chain(others)),
+            "intersect" => IntSpan::intersect_all(std::iter::once(s1).chain(others)),
+            "diff" => s1.diff(&IntSpan::union_all(others)),
+            _ => others
+                .into_iter()
+                .fold(s1.copy(), |intspan_op, s2| f(&intspan_op, s2)),
         }
-        res_of.insert(name.into(), res);
+    });
+
+    let mut res_of: GenomeSets = s1_of
+        .names()
+        .map(|name| (name.to_string(), GenomeSet::new()))
+        .collect();
+    for ((name, chr), intspan) in pairs.iter().zip(intspans) {
+        res_of.get_mut(name).unwrap().insert(chr, intspan);
     }
 
     //----------------------------
     // Output
     //----------------------------
-    let out_json = if is_multi {
-        set2json_m(&res_of)
+    let outfile = args.get_one::<String>("outfile").unwrap();
+    if is_stranded {
+        write_json(outfile, &set2json_stranded(&res_of.into_map()))?;
     } else {
-        set2json(res_of.get("__single").unwrap())
-    };
-    write_json(args.get_one::<String>("outfile").unwrap(), &out_json)?;
+        res_of.write(outfile)?;
+    }
 
     Ok(())
 }
@@ -3,7 +3,33 @@ use clap::*;
 // Create clap subcommand arguments
 pub fn make_subcommand() -> Command {
     Command::new("convert")
-        .about("Convert runlist file to ranges file")
+        .about("Convert runlist files to ranges, JSON, YAML or binary runlists")
+        .after_help(
+            r###"
+`--to` sets the output format. By default, it's inferred from the extension of `--outfile`,
+`.json`, `.yml`, `.yaml` or `.rlb`, and is `ranges` for others
+
+* ranges: one range per line, or BED lines with `--bed`
+* json: a runlist file
+* yaml: a runlist file in YAML, like the Perl `runlist` tools
+* rlb: a compact binary runlist file, with delta-encoded edges and zlib compression
+
+Binary runlist files, `.rlb`, and YAML files, `.yml` or `.yaml`, are detected by the extension
+and can be used by all `spanr` subcommands in place of JSON files. Outputs of subcommands with
+these extensions are also written in these formats
+
+Example:
+
+    spanr convert tests/spanr/Atha.json -o Atha.rlb
+
+    spanr convert Atha.rlb --to json
+
+    spanr convert tests/spanr/Atha.yml --to json
+
+    spanr stat tests/spanr/Atha.chr.sizes Atha.rlb
+
+"###,
+        )
         .arg(
             Arg::new("infiles")
                 .required(true)
@@ -11,12 +37,36 @@ pub fn make_subcommand() -> Command {
                 .index(1)
                 .help("Set the input files to use"),
         )
+        .arg(
+            Arg::new("to")
+                .long("to")
+                .num_args(1)
+                .value_parser([
+                    builder::PossibleValue::new("ranges"),
+                    builder::PossibleValue::new("json"),
+                    builder::PossibleValue::new("yaml"),
+                    builder::PossibleValue::new("rlb"),
+                ])
+                .help("Output format"),
+        )
+        .arg(
+            Arg::new("no-compress")
+                .long("no-compress")
+                .action(ArgAction::SetTrue)
+                .help("Don't compress binary runlist files"),
+        )
         .arg(
             Arg::new("longest")
                 .long("longest")
                 .action(ArgAction::SetTrue)
                 .help("Only keep the longest range"),
         )
+        .arg(
+            Arg::new("bed")
+                .long("bed")
+                .action(ArgAction::SetTrue)
+                .help("Write BED lines instead of ranges"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -32,19 +82,54 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Args
     //----------------------------
+    let outfile = args.get_one::<String>("outfile").unwrap();
+    let opt_to = match (args.get_one::<String>("to"), format_of(outfile)) {
+        (Some(to), Some(format)) if to != format => {
+            return Err(anyhow::anyhow!(
+                "--to {} doesn't match the extension of {}",
+                to,
+                outfile
+            ));
+        }
+        (Some(to), _) => to.as_str(),
+        (None, format) => format.unwrap_or("ranges"),
+    };
     let is_longest = args.get_flag("longest");
-    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap());
+    let is_bed = args.get_flag("bed");
+    let mut writer = intspan::writer(outfile)?;
+
+    let infiles: Vec<&String> = args.get_many::<String>("infiles").unwrap().collect();
+    if opt_to != "ranges" {
+        if infiles.len() != 1 {
+            return Err(anyhow::anyhow!("--to {} needs exactly one infile", opt_to));
+        }
+        // Runlists are converted as they are, including stranded ones, which GenomeSets can't hold
+        let json = intspan::read_json(infiles[0])?;
+
+        if opt_to == "json" {
+            let mut s = serde_json::to_string_pretty(&json)?;
+            s.push('\n');
+            writer.write_all(s.as_bytes())?;
+        } else if opt_to == "yaml" {
+            let s = serde_yaml::to_string(&json)?;
+            writer.write_all(b"---\n")?;
+            writer.write_all(s.as_bytes())?;
+        } else {
+            let bytes = intspan::encode_rlb(&json, !args.get_flag("no-compress"))?;
+            writer.write_all(&bytes)?;
+        }
+
+        return Ok(());
+    }
 
     //----------------------------
     // Ops
     //----------------------------
-    for infile in args.get_many::<String>("infiles").unwrap() {
-        let json = intspan::read_json(infile);
-        let set_of = intspan::json2set_m(&json);
+    for infile in infiles {
+        let set_of = intspan::GenomeSets::read(infile)?;
 
-        for set in set_of.values() {
-            for chr in set.keys() {
-                let ints = set.get(chr).unwrap();
+        for (_, set) in &set_of {
+            for (chr, ints) in set {
                 let mut intses = ints.intses();
 
                 //----------------------------
@@ -55,11 +140,11 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
                         // Negate the value for descending order
                         intses.sort_by_cached_key(|e| -e.size());
                         let longest = intses.first().unwrap();
-                        writer.write_all(format!("{}:{}\n", chr, longest).as_ref())?;
+                        writer.write_all(format_rg(chr, longest, is_bed).as_ref())?;
                     }
                 } else {
                     for sub in &intses {
-                        writer.write_all(format!("{}:{}\n", chr, sub).as_ref())?;
+                        writer.write_all(format_rg(chr, sub, is_bed).as_ref())?;
                     }
                 }
             }
@@ -68,3 +153,25 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+fn format_rg(chr: &str, ints: &intspan::IntSpan, is_bed: bool) -> String {
+    if is_bed {
+        let range = intspan::Range::from(chr, ints.min(), ints.max());
+        format!("{}\n", range.to_bed())
+    } else {
+        format!("{}:{}\n", chr, ints)
+    }
+}
+
+// Format of runlist files by the extension
+fn format_of(outfile: &str) -> Option<&'static str> {
+    if intspan::is_rlb(outfile) {
+        Some("rlb")
+    } else if intspan::is_yaml(outfile) {
+        Some("yaml")
+    } else if intspan::is_runlist(outfile) {
+        Some("json")
+    } else {
+        None
+    }
+}
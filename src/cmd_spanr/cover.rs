@@ -1,6 +1,5 @@
 use clap::*;
 use intspan::*;
-use std::collections::BTreeMap;
 use std::io::BufRead;
 
 // Create clap subcommand arguments
@@ -15,6 +14,10 @@ Like command `combine`, but <infiles> are chromosome ranges
     I(+):90-150             # Strand will be omitted
     S288c.I(-):190-200      # Species name will be omitted
 
+`.bed` and `.bed.gz` files are read as BED, with 0-based starts
+
+    spanr cover tests/spanr/S288c.bed
+
 "###,
         )
         .arg(
@@ -24,6 +27,12 @@ Like command `combine`, but <infiles> are chromosome ranges
                 .index(1)
                 .help("Set the input files to use"),
         )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -40,31 +49,33 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     // Loading
     //----------------------------
 
-    // seq_name => IntSpan
-    let mut set: BTreeMap<String, IntSpan> = BTreeMap::new();
+    let mut set = GenomeSet::new();
 
+    let mut skipped = Skipped::new(args.get_flag("strict"));
     for infile in args.get_many::<String>("infiles").unwrap() {
-        let reader = reader(infile);
-        for line in reader.lines().map_while(Result::ok) {
-            let range = Range::from_str(&line);
-            if !range.is_valid() {
+        let reader = reader(infile)?;
+        let is_bed = is_bed(infile);
+        for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+            if is_comment(&line, is_bed) {
                 continue;
             }
-            let chr = range.chr();
-            if !set.contains_key(chr) {
-                set.insert(chr.clone(), IntSpan::new());
-            }
 
-            set.entry(chr.to_string())
-                .and_modify(|e| e.add_pair(*range.start(), *range.end()));
+            let range = match parse_rg(&line, is_bed) {
+                Ok(range) => range,
+                Err(e) => {
+                    skipped.skip(e.at(infile, i + 1, None))?;
+                    continue;
+                }
+            };
+            set.add_range(&range);
         }
     }
+    skipped.report();
 
     //----------------------------
     // Output
     //----------------------------
-    let out_json = set2json(&set);
-    write_json(args.get_one::<String>("outfile").unwrap(), &out_json)?;
+    set.write(args.get_one::<String>("outfile").unwrap())?;
 
     Ok(())
 }
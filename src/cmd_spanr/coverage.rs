@@ -1,16 +1,38 @@
 use clap::*;
 use intspan::*;
-use rust_lapper::{Interval, Lapper};
 use std::collections::BTreeMap;
 use std::io::BufRead;
 
-// Interval: represent a range from [start, stop), carrying val
-type Iv = Interval<u32, u32>; // the first type should be Unsigned
-
 // Create clap subcommand arguments
 pub fn make_subcommand() -> Command {
     Command::new("coverage")
         .about("Output minimum or detailed depth of coverage on chromosomes")
+        .after_help(
+            r###"
+<infiles> are chromosome ranges. `.bed` and `.bed.gz` files are read as BED, with 0-based starts
+
+Output formats
+
+* JSON, default: a single runlist of regions with depths >= `--minimum`
+* JSON with `--detailed`: a multi-level runlist keyed by depths
+* bedGraph with `--bedgraph`: `chr`, 0-based `start`, `end` and `depth`
+* bigWig with `--bigwig`
+    * Sizes of chromosomes come from `--sizes`, or the largest ends of ranges
+
+Options for bedGraph and bigWig
+
+* Intervals are split at each boundary of ranges
+* `--merge` merges adjacent intervals with equal depths
+* `--normalize` divides depths by total bases of all ranges, in millions
+
+Example:
+
+    spanr coverage tests/spanr/S288c.rg --bedgraph
+
+    spanr coverage tests/spanr/S288c.rg --bigwig -s tests/spanr/S288c.chr.sizes -o S288c.bw
+
+"###,
+        )
         .arg(
             Arg::new("infiles")
                 .required(true)
@@ -34,6 +56,54 @@ pub fn make_subcommand() -> Command {
                 .action(ArgAction::SetTrue)
                 .help("Output detailed depth"),
         )
+        .arg(
+            Arg::new("bedgraph")
+                .long("bedgraph")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["detailed", "bigwig"])
+                .help("Output depth in bedGraph format"),
+        )
+        .arg(
+            Arg::new("bigwig")
+                .long("bigwig")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("detailed")
+                .help("Output depth in bigWig format"),
+        )
+        .arg(
+            Arg::new("sizes")
+                .long("sizes")
+                .short('s')
+                .num_args(1)
+                .help("chr.sizes for bigWig"),
+        )
+        .arg(
+            Arg::new("merge")
+                .long("merge")
+                .action(ArgAction::SetTrue)
+                .help("Merge adjacent intervals with equal depths"),
+        )
+        .arg(
+            Arg::new("normalize")
+                .long("normalize")
+                .action(ArgAction::SetTrue)
+                .help("Normalize depths by total bases of ranges"),
+        )
+        .arg(
+            Arg::new("parallel")
+                .long("parallel")
+                .short('p')
+                .num_args(1)
+                .default_value("1")
+                .value_parser(value_parser!(usize))
+                .help("Number of threads"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -51,105 +121,168 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     let minimum = *args.get_one::<i32>("minimum").unwrap();
     let is_detailed = args.get_flag("detailed");
+    let is_bedgraph = args.get_flag("bedgraph");
+    let is_bigwig = args.get_flag("bigwig");
+    let is_merge = args.get_flag("merge");
+    let is_normalize = args.get_flag("normalize");
+    let opt_parallel = *args.get_one::<usize>("parallel").unwrap();
 
-    // seq_name => Vector of Intervals
-    let mut iv_of: BTreeMap<String, Vec<Iv>> = BTreeMap::new();
+    // chr => [(start, end)]
+    let mut ranges_of: BTreeMap<String, Vec<(i64, i64)>> = BTreeMap::new();
 
+    let mut skipped = Skipped::new(args.get_flag("strict"));
     for infile in args.get_many::<String>("infiles").unwrap() {
-        let reader = reader(infile);
-        for line in reader.lines().map_while(Result::ok) {
-            if line.starts_with('#') {
+        let reader = reader(infile)?;
+        let is_bed = is_bed(infile);
+        for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+            if is_comment(&line, is_bed) {
                 continue;
             }
-            let range = Range::from_str(&line);
-            if !range.is_valid() {
-                continue;
-            }
-            let chr = range.chr();
-            if !iv_of.contains_key(chr) {
-                let ivs: Vec<Iv> = vec![];
-                iv_of.insert(chr.clone(), ivs);
-            }
 
-            let iv = Iv {
-                start: *range.start() as u32,
-                stop: *range.end() as u32 + 1,
-                val: 0,
+            let range = match parse_rg(&line, is_bed) {
+                Ok(range) => range,
+                Err(e) => {
+                    skipped.skip(e.at(infile, i + 1, None))?;
+                    continue;
+                }
             };
 
-            iv_of.entry(chr.to_string()).and_modify(|e| e.push(iv));
+            ranges_of
+                .entry(range.chr().to_string())
+                .or_default()
+                .push((*range.start(), *range.end()));
         }
     }
+    skipped.report();
+
+    let chrs: Vec<&String> = ranges_of.keys().collect();
+
+    // Exact depths of each chr
+    let depths = par_map(&chrs, opt_parallel, |chr| {
+        IntMap::depth(ranges_of[*chr].iter().copied())
+    });
 
     //----------------------------
     // Output
     //----------------------------
-    if is_detailed {
-        // Multi
-        let mut set_of: BTreeMap<String, BTreeMap<String, IntSpan>> = BTreeMap::new();
+    if is_bedgraph || is_bigwig {
+        // Total bases of all ranges, in millions
+        let total: f64 = ranges_of
+            .values()
+            .flatten()
+            .map(|(start, end)| (end - start + 1) as f64)
+            .sum::<f64>()
+            / 1_000_000.0;
 
-        for chr in iv_of.keys() {
-            let lapper = Lapper::new(iv_of.get(chr).unwrap().to_owned());
-            let ivs = lapper.depth().collect::<Vec<Interval<u32, u32>>>();
+        // chr => (start, end, depth)
+        let pairs: Vec<(&String, &IntMap<usize>)> = chrs.iter().copied().zip(&depths).collect();
+        let intervals_of: BTreeMap<String, Vec<(i64, i64, f32)>> = chrs
+            .iter()
+            .map(|chr| chr.to_string())
+            .zip(par_map(&pairs, opt_parallel, |(chr, depth)| {
+                // Runs are split at each boundary of ranges, unless merged
+                let mut bounds: Vec<i64> = vec![];
+                if !is_merge {
+                    bounds = ranges_of[*chr]
+                        .iter()
+                        .flat_map(|(start, end)| vec![*start, end + 1])
+                        .collect();
+                    bounds.sort_unstable();
+                    bounds.dedup();
+                }
 
-            // depth => IntSpan
-            let mut intspan_of: BTreeMap<String, IntSpan> = BTreeMap::new();
+                let mut intervals: Vec<(i64, i64, usize)> = vec![];
+                for (start, end, depth) in depth.runs() {
+                    if (*depth as i32) < minimum {
+                        continue;
+                    }
 
-            for iv in ivs {
-                let depth = iv.val as i32;
-                if depth < minimum {
-                    continue;
+                    let mut start = start;
+                    let i = bounds.partition_point(|b| *b <= start);
+                    for b in bounds[i..].iter().take_while(|b| **b <= end) {
+                        intervals.push((start, b - 1, *depth));
+                        start = *b;
+                    }
+                    intervals.push((start, end, *depth));
                 }
 
-                let depth = format!("{}", depth);
+                intervals
+                    .into_iter()
+                    .map(|(start, end, depth)| {
+                        let value = if is_normalize {
+                            depth as f64 / total
+                        } else {
+                            depth as f64
+                        };
+                        (start, end, value as f32)
+                    })
+                    .collect::<Vec<_>>()
+            }))
+            .collect();
 
-                if !set_of.contains_key(&depth) {
-                    set_of.insert(depth.clone(), BTreeMap::new());
+        if is_bigwig {
+            let sizes: BTreeMap<String, i64> = match args.get_one::<String>("sizes") {
+                Some(file) => read_sizes(file)?,
+                None => intervals_of
+                    .iter()
+                    .filter_map(|(chr, ivs)| ivs.last().map(|iv| (chr.to_string(), iv.1)))
+                    .collect(),
+            };
+            write_bigwig(
+                args.get_one::<String>("outfile").unwrap(),
+                &sizes,
+                &intervals_of,
+            )?;
+        } else {
+            let mut writer = writer(args.get_one::<String>("outfile").unwrap())?;
+            for (chr, intervals) in &intervals_of {
+                for (start, end, value) in intervals {
+                    if is_normalize {
+                        writer.write_fmt(format_args!(
+                            "{}\t{}\t{}\t{:.4}\n",
+                            chr,
+                            start - 1,
+                            end,
+                            value
+                        ))?;
+                    } else {
+                        writer.write_fmt(format_args!(
+                            "{}\t{}\t{}\t{}\n",
+                            chr,
+                            start - 1,
+                            end,
+                            value
+                        ))?;
+                    }
                 }
+            }
+        }
+    } else if is_detailed {
+        // Multi
+        let mut set_of = GenomeSets::new();
 
-                if !intspan_of.contains_key(&depth) {
-                    intspan_of.insert(depth.clone(), IntSpan::new());
+        // depth => IntSpan of each chr
+        for (chr, depth) in chrs.iter().zip(&depths) {
+            for (depth, intspan) in depth.by_value() {
+                if (depth as i32) < minimum {
+                    continue;
                 }
 
-                intspan_of
-                    .entry(depth)
-                    .and_modify(|e| e.add_pair(iv.start as i32, iv.stop as i32 - 1));
-            }
-
-            for depth in intspan_of.keys() {
-                set_of
-                    .get_mut(depth)
-                    .unwrap()
-                    .insert(chr.clone(), intspan_of.get(depth).unwrap().clone());
+                set_of.entry(&format!("{}", depth)).insert(chr, intspan);
             }
         }
 
-        let out_json = set2json_m(&set_of);
-        write_json(args.get_one::<String>("outfile").unwrap(), &out_json)?;
+        set_of.write(args.get_one::<String>("outfile").unwrap())?;
     } else {
         // Single
-        // chr => IntSpan
-        let mut set: BTreeMap<String, IntSpan> = BTreeMap::new();
-
-        for chr in iv_of.keys() {
-            let lapper = Lapper::new(iv_of.get(chr).unwrap().to_owned());
-            let ivs = lapper.depth().collect::<Vec<Interval<u32, u32>>>();
-
-            let mut intspan = IntSpan::new();
-            for iv in ivs {
-                let depth = iv.val as i32;
-                if depth < minimum {
-                    continue;
-                }
-
-                intspan.add_pair(iv.start as i32, iv.stop as i32 - 1);
-            }
+        let mut set = GenomeSet::new();
 
-            set.insert(chr.to_string(), intspan);
+        for (chr, depth) in chrs.iter().zip(&depths) {
+            let intspan = depth.filter(|depth| (*depth as i32) >= minimum);
+            set.insert(chr, intspan);
         }
 
-        let out_json = set2json(&set);
-        write_json(args.get_one::<String>("outfile").unwrap(), &out_json)?;
+        set.write(args.get_one::<String>("outfile").unwrap())?;
     }
 
     Ok(())
@@ -0,0 +1,162 @@
+use clap::*;
+use intspan::*;
+use std::collections::BTreeSet;
+
+// Create clap subcommand arguments
+pub fn make_subcommand() -> Command {
+    Command::new("features")
+        .about("Derive gene-structure features from gff3/gtf")
+        .after_help(
+            r###"
+Genes and transcripts are loaded from GFF3 or GTF files, like `spanr gff --by transcript`
+
+Output is a multi-level runlist with the following keys:
+
+* gene: spans of transcripts, from the first exon to the last one
+* exon
+* cds
+* intron: holes of exons of each transcript, excluding exons of other transcripts
+* promoter: strand-aware 5' flanks of transcripts, with `--promoter` bases
+* intergenic: the complement of `gene` against chr.sizes
+
+Chromosomes not in chr.sizes are ignored. All features are clipped to the ends of chromosomes
+
+Example:
+
+    spanr features tests/spanr/S288c.chr.sizes tests/spanr/genes.gff3 --promoter 500
+
+"###,
+        )
+        .arg(
+            Arg::new("chr.sizes")
+                .required(true)
+                .index(1)
+                .help("Sets the input file to use"),
+        )
+        .arg(
+            Arg::new("infiles")
+                .required(true)
+                .num_args(1..)
+                .index(2)
+                .help("GFF3 or GTF files"),
+        )
+        .arg(
+            Arg::new("promoter")
+                .long("promoter")
+                .short('p')
+                .num_args(1)
+                .default_value("1000")
+                .value_parser(value_parser!(i64))
+                .help("Length of promoters"),
+        )
+        .arg(
+            Arg::new("attr")
+                .long("attr")
+                .num_args(1)
+                .action(ArgAction::Append)
+                .help("Filter transcripts by attributes, KEY=REGEX"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
+        .arg(
+            Arg::new("outfile")
+                .long("outfile")
+                .short('o')
+                .num_args(1)
+                .default_value("stdout")
+                .help("Output filename. [stdout] for screen"),
+        )
+}
+
+// command implementation
+pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
+    //----------------------------
+    // Args
+    //----------------------------
+    let opt_promoter = *args.get_one::<i64>("promoter").unwrap();
+    if opt_promoter < 1 {
+        return Err(anyhow::anyhow!("--promoter should be positive"));
+    }
+    let filters = super::gff::attr_filters(args)?;
+
+    //----------------------------
+    // Loading
+    //----------------------------
+    let sizes = read_sizes(args.get_one::<String>("chr.sizes").unwrap())?;
+
+    let records = super::gff::read_records(args)?;
+
+    let transcripts: Vec<Transcript> = read_transcripts(&records)
+        .into_iter()
+        .filter(|tx| sizes.contains_key(&tx.seqid) && !tx.exons.is_empty())
+        .filter(|tx| super::gff::is_matched(&filters, &tx.attributes))
+        .collect();
+
+    //----------------------------
+    // Operating
+    //----------------------------
+    let chrs: BTreeSet<String> = sizes.keys().cloned().collect();
+    let mut res_of = GenomeSets::new();
+    for name in ["gene", "exon", "cds", "intron", "promoter", "intergenic"].iter() {
+        let mut set = GenomeSet::new();
+        set.fill_up(&chrs);
+        res_of.insert(name, set);
+    }
+
+    for tx in &transcripts {
+        let chr = &tx.seqid;
+        let size = *sizes.get(chr).unwrap();
+        if size < 1 {
+            continue;
+        }
+        let chr_ints = IntSpan::from_pair(1, size);
+
+        let mut range = Range::from(chr, tx.exons.min(), tx.exons.max());
+        if tx.strand == "+" || tx.strand == "-" {
+            *range.strand_mut() = tx.strand.clone();
+        }
+
+        // Don't go beyond the ends of chromosomes
+        let avail = if tx.strand == "-" {
+            size - range.end()
+        } else {
+            range.start() - 1
+        };
+        let avail = avail.max(0);
+
+        let mut add = |name: &str, ints: &IntSpan| {
+            res_of
+                .get_mut(name)
+                .unwrap()
+                .entry(chr)
+                .merge(&ints.intersect(&chr_ints));
+        };
+        add("gene", &range.intspan());
+        add("exon", &tx.exons);
+        add("cds", &tx.cds);
+        add("intron", &tx.introns());
+        if avail > 0 {
+            let promoter = range.flank_5p(opt_promoter.min(avail));
+            if promoter.is_valid() {
+                add("promoter", &promoter.intspan());
+            }
+        }
+    }
+
+    let intron = res_of["intron"].diff(&res_of["exon"]);
+    res_of.insert("intron", intron);
+
+    let intergenic = res_of["gene"].complement(&sizes);
+    res_of.insert("intergenic", intergenic);
+
+    //----------------------------
+    // Output
+    //----------------------------
+    res_of.write(args.get_one::<String>("outfile").unwrap())?;
+
+    Ok(())
+}
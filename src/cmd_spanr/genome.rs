@@ -1,7 +1,5 @@
 use clap::*;
 use intspan::*;
-use serde_json::Value;
-use std::collections::BTreeMap;
 
 // Create clap subcommand arguments
 pub fn make_subcommand() -> Command {
@@ -28,22 +26,17 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Loading
     //----------------------------
-    let sizes = read_sizes(args.get_one::<String>("infile").unwrap());
+    let sizes = read_sizes(args.get_one::<String>("infile").unwrap())?;
 
     //----------------------------
     // Operating
     //----------------------------
-    let mut json: BTreeMap<String, Value> = BTreeMap::new();
-    for (key, value) in sizes {
-        let mut intspan = IntSpan::new();
-        intspan.add_pair(1, value);
-        json.insert(key, intspan.to_string().into());
-    }
+    let genome = GenomeSet::from_sizes(&sizes);
 
     //----------------------------
     // Output
     //----------------------------
-    write_json(args.get_one::<String>("outfile").unwrap(), &json)?;
+    genome.write(args.get_one::<String>("outfile").unwrap())?;
 
     Ok(())
 }
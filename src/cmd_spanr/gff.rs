@@ -1,12 +1,49 @@
 use clap::*;
+use indexmap::IndexMap;
 use intspan::*;
-use std::collections::BTreeMap;
-use std::io::BufRead;
+use regex::Regex;
+use std::io::{BufRead, Write};
 
 // Create clap subcommand arguments
 pub fn make_subcommand() -> Command {
     Command::new("gff")
         .about("Convert gff3 to covers on chromosomes")
+        .after_help(
+            r###"
+With `--stranded`, covers of each strand are written as stranded runlists
+
+    {"I": {"+": "1-100", "-": "201-300"}}
+
+Features without a strand (`.` or `?`) are skipped in stranded runlists
+
+With `--by gene` or `--by transcript`, GFF3 and GTF files are parsed into genes and transcripts
+
+* GFF3: transcripts are linked to genes by `ID` and `Parent`
+* GTF: transcripts are grouped by `gene_id` and `transcript_id`
+* `--feature` selects the covers of each gene or transcript
+    * exon, cds
+    * intron: holes of exons
+    * utr: exons not in CDS
+    * 5utr, 3utr: UTRs before and after CDS, according to the strand
+* Exons are built from CDS and UTRs, or from the transcript itself, when there are none
+* Output is a multi-level runlist keyed by IDs
+* With `--rg`, ranges are written with the gene ID and the transcript ID as extra columns
+
+`--attr KEY=REGEX` keeps features whose attribute matches the regex, and can be given multiple times.
+With `--by`, attributes of the gene, the transcript and its components are all checked
+
+Example:
+
+    spanr gff tests/spanr/NC_007942.gff --tag CDS
+
+    spanr gff tests/spanr/genes.gff3 --by transcript --feature intron
+
+    spanr gff tests/spanr/genes.gtf --by gene --feature 5utr --rg
+
+    spanr gff tests/spanr/genes.gff3 --by gene --attr biotype=protein_coding
+
+"###,
+        )
         .arg(
             Arg::new("infiles")
                 .required(true)
@@ -18,8 +55,61 @@ pub fn make_subcommand() -> Command {
             Arg::new("tag")
                 .long("tag")
                 .num_args(1)
+                .conflicts_with("by")
                 .help("primary tag (the third field)"),
         )
+        .arg(
+            Arg::new("attr")
+                .long("attr")
+                .num_args(1)
+                .action(ArgAction::Append)
+                .help("Filter by attributes, KEY=REGEX"),
+        )
+        .arg(
+            Arg::new("by")
+                .long("by")
+                .num_args(1)
+                .value_parser([
+                    builder::PossibleValue::new("gene"),
+                    builder::PossibleValue::new("transcript"),
+                ])
+                .help("Write covers of each gene or transcript"),
+        )
+        .arg(
+            Arg::new("feature")
+                .long("feature")
+                .num_args(1)
+                .default_value("exon")
+                .value_parser([
+                    builder::PossibleValue::new("exon"),
+                    builder::PossibleValue::new("intron"),
+                    builder::PossibleValue::new("cds"),
+                    builder::PossibleValue::new("utr"),
+                    builder::PossibleValue::new("5utr"),
+                    builder::PossibleValue::new("3utr"),
+                ])
+                .help("Feature of genes or transcripts"),
+        )
+        .arg(
+            Arg::new("rg")
+                .long("rg")
+                .action(ArgAction::SetTrue)
+                .requires("by")
+                .help("Write ranges with IDs instead of runlists"),
+        )
+        .arg(
+            Arg::new("stranded")
+                .long("stranded")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("by")
+                .help("Keep strands separate"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -33,50 +123,182 @@ pub fn make_subcommand() -> Command {
 // command implementation
 pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
-    // Loading
+    // Args
     //----------------------------
-    let mut res: BTreeMap<String, IntSpan> = BTreeMap::new();
+    let is_stranded = args.get_flag("stranded");
     let tag = if args.contains_id("tag") {
         args.get_one::<String>("tag").unwrap().as_str()
     } else {
         ""
     };
 
-    for infile in args.get_many::<String>("infiles").unwrap() {
-        let reader = reader(infile);
-        for line in reader.lines().map_while(Result::ok) {
-            if line.starts_with('#') {
-                continue;
-            }
+    let filters = attr_filters(args)?;
 
-            let fields: Vec<&str> = line.split('\t').collect();
-            if fields.len() < 8 {
-                continue;
-            }
+    //----------------------------
+    // Loading
+    //----------------------------
+    let records = read_records(args)?;
+
+    if args.contains_id("by") {
+        return by_transcripts(args, &records, &filters);
+    }
 
-            let feature = fields[2];
-            if !tag.is_empty() && feature != tag {
+    //----------------------------
+    // Operating
+    //----------------------------
+    let mut res = GenomeSet::new();
+    // strand => chr => IntSpan
+    let mut res_of = GenomeSets::new();
+
+    for record in &records {
+        if !tag.is_empty() && record.feature != tag {
+            continue;
+        }
+        if !is_matched(&filters, &record.attributes) {
+            continue;
+        }
+
+        if is_stranded {
+            if record.strand != "+" && record.strand != "-" {
                 continue;
             }
+            res_of
+                .entry(&record.strand)
+                .entry(&record.seqid)
+                .add_pair(record.start, record.end);
+            continue;
+        }
 
-            let chr = fields[0];
-            let start = fields[3].parse::<i32>().unwrap();
-            let end = fields[4].parse::<i32>().unwrap();
+        res.entry(&record.seqid).add_pair(record.start, record.end);
+    }
 
-            if !res.contains_key(chr) {
-                let intspan = IntSpan::new();
-                res.insert(chr.to_string(), intspan);
+    //----------------------------
+    // Output
+    //----------------------------
+    let outfile = args.get_one::<String>("outfile").unwrap();
+    if is_stranded {
+        write_json(outfile, &set2json_stranded(&res_of.into_map()))?;
+    } else {
+        res.write(outfile)?;
+    }
+
+    Ok(())
+}
+
+fn by_transcripts(
+    args: &ArgMatches,
+    records: &[GffRecord],
+    filters: &[(String, Regex)],
+) -> anyhow::Result<()> {
+    let is_gene = args.get_one::<String>("by").unwrap() == "gene";
+    let feature = args.get_one::<String>("feature").unwrap();
+
+    //----------------------------
+    // Operating
+    //----------------------------
+    let transcripts: Vec<Transcript> = read_transcripts(records)
+        .into_iter()
+        .filter(|tx| is_matched(filters, &tx.attributes))
+        .collect();
+
+    // Genes are merged from their transcripts
+    let entries: Vec<Transcript> = if is_gene {
+        let mut gene_of: IndexMap<String, Transcript> = IndexMap::new();
+        for tx in transcripts {
+            match gene_of.get_mut(&tx.gene_id) {
+                Some(gene) => gene.merge(&tx),
+                None => {
+                    let gene = Transcript {
+                        id: tx.gene_id.clone(),
+                        ..tx
+                    };
+                    gene_of.insert(gene.id.clone(), gene);
+                }
             }
-            res.entry(chr.to_string())
-                .and_modify(|e| e.add_pair(start, end));
         }
-    }
+        gene_of.into_values().collect()
+    } else {
+        transcripts
+    };
 
     //----------------------------
     // Output
     //----------------------------
-    let out_json = set2json(&res);
-    write_json(args.get_one::<String>("outfile").unwrap(), &out_json)?;
+    if args.get_flag("rg") {
+        let mut writer = writer(args.get_one::<String>("outfile").unwrap())?;
+        for entry in &entries {
+            let ints = entry.feature(feature).unwrap();
+            for (lower, upper) in ints.spans() {
+                let mut range = Range::from(&entry.seqid, lower, upper);
+                if entry.strand == "+" || entry.strand == "-" {
+                    *range.strand_mut() = entry.strand.clone();
+                }
+                if is_gene {
+                    writer.write_fmt(format_args!("{}\t{}\n", range, entry.gene_id))?;
+                } else {
+                    writer
+                        .write_fmt(format_args!("{}\t{}\t{}\n", range, entry.gene_id, entry.id))?;
+                }
+            }
+        }
+    } else {
+        // id => chr => IntSpan
+        let mut set_of = GenomeSets::new();
+        for entry in &entries {
+            let ints = entry.feature(feature).unwrap();
+            if ints.is_empty() {
+                continue;
+            }
+            set_of.entry(&entry.id).entry(&entry.seqid).merge(&ints);
+        }
+        set_of.write(args.get_one::<String>("outfile").unwrap())?;
+    }
 
     Ok(())
 }
+
+// Records of all infiles, with `--strict`
+pub fn read_records(args: &ArgMatches) -> anyhow::Result<Vec<GffRecord>> {
+    let mut skipped = Skipped::new(args.get_flag("strict"));
+
+    let mut records: Vec<GffRecord> = vec![];
+    for infile in args.get_many::<String>("infiles").unwrap() {
+        let reader = reader(infile)?;
+        for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+            // Sequences embedded in GFF3
+            if line.starts_with("##FASTA") {
+                break;
+            }
+            match GffRecord::from_line(&line) {
+                Ok(Some(record)) => records.push(record),
+                Ok(None) => {}
+                Err(e) => skipped.skip(e.at(infile, i + 1, None))?,
+            }
+        }
+    }
+    skipped.report();
+
+    Ok(records)
+}
+
+// (key, regex) of `--attr`
+pub fn attr_filters(args: &ArgMatches) -> anyhow::Result<Vec<(String, Regex)>> {
+    let mut filters: Vec<(String, Regex)> = vec![];
+    if let Some(attrs) = args.get_many::<String>("attr") {
+        for attr in attrs {
+            let (key, pattern) = attr
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("--attr should be KEY=REGEX: {}", attr))?;
+            filters.push((key.to_string(), Regex::new(pattern)?));
+        }
+    }
+
+    Ok(filters)
+}
+
+// All filters should match
+pub fn is_matched(filters: &[(String, Regex)], attributes: &[(String, String)]) -> bool {
+    filters
+        .iter()
+        .all(|(key, re)| attributes.iter().any(|(k, v)| k == key && re.is_match(v)))
+}
@@ -42,7 +42,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     let is_all = args.get_flag("all");
 
     for infile in args.get_many::<String>("infiles").unwrap() {
-        let json = read_json(infile);
+        let json = read_json(infile)?;
 
         let key = if is_all {
             Path::new(infile)
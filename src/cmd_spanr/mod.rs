@@ -5,6 +5,7 @@ pub mod compare;
 pub mod convert;
 pub mod cover;
 pub mod coverage;
+pub mod features;
 pub mod genome;
 pub mod gff;
 pub mod merge;
@@ -13,3 +14,5 @@ pub mod span;
 pub mod split;
 pub mod stat;
 pub mod statop;
+pub mod window;
+pub mod winstat;
@@ -36,10 +36,10 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Loading
     //----------------------------
-    let json: BTreeMap<String, Value> = read_json(args.get_one::<String>("infile").unwrap());
+    let json: BTreeMap<String, Value> = read_json(args.get_one::<String>("infile").unwrap())?;
 
     let mut names: BTreeSet<String> = BTreeSet::new();
-    for line in read_lines(args.get_one::<String>("list").unwrap()) {
+    for line in read_lines(args.get_one::<String>("list").unwrap())? {
         names.insert(line);
     }
 
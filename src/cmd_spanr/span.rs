@@ -1,7 +1,5 @@
 use clap::*;
 use intspan::*;
-use serde_json::Value;
-use std::collections::BTreeMap;
 
 // Create clap subcommand arguments
 pub fn make_subcommand() -> Command {
@@ -47,7 +45,7 @@ List of operations
                 .long("number")
                 .short('n')
                 .num_args(1)
-                .value_parser(value_parser!(i32))
+                .value_parser(value_parser!(i64))
                 .default_value("0"),
         )
         .arg(
@@ -65,44 +63,34 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Args
     //----------------------------
-    let json: BTreeMap<String, Value> = read_json(args.get_one::<String>("infile").unwrap());
-    let is_multi: bool = json.values().next().unwrap().is_object();
-    let set_of = json2set_m(&json);
+    let set_of = GenomeSets::read(args.get_one::<String>("infile").unwrap())?;
 
     let opt_op = args.get_one::<String>("op").unwrap().as_str();
-    let opt_number = *args.get_one::<i32>("number").unwrap();
+    let opt_number = *args.get_one::<i64>("number").unwrap();
 
     //----------------------------
     // Ops
     //----------------------------
-    let mut res_of: BTreeMap<String, BTreeMap<String, IntSpan>> = BTreeMap::new();
-    for (name, set) in &set_of {
-        let mut res: BTreeMap<String, IntSpan> = BTreeMap::new();
-        for chr in set.keys() {
-            let intspan = match opt_op {
-                "cover" => set.get(chr).unwrap().cover(),
-                "holes" => set.get(chr).unwrap().holes(),
-                "trim" => set.get(chr).unwrap().trim(opt_number),
-                "pad" => set.get(chr).unwrap().pad(opt_number),
-                "excise" => set.get(chr).unwrap().excise(opt_number),
-                "fill" => set.get(chr).unwrap().fill(opt_number),
+    let res_of: GenomeSets = set_of
+        .iter()
+        .map(|(name, set)| {
+            let res = set.map(|intspan| match opt_op {
+                "cover" => intspan.cover(),
+                "holes" => intspan.holes(),
+                "trim" => intspan.trim(opt_number),
+                "pad" => intspan.pad(opt_number),
+                "excise" => intspan.excise(opt_number),
+                "fill" => intspan.fill(opt_number),
                 _ => unreachable!("Invalid IntSpan Op"),
-            };
-            //            println!("Op {}: {}", op, op_intspan.to_string());
-            res.insert(chr.into(), intspan);
-        }
-        res_of.insert(name.into(), res);
-    }
+            });
+            (name.to_string(), res)
+        })
+        .collect();
 
     //----------------------------
     // Output
     //----------------------------
-    let out_json = if is_multi {
-        set2json_m(&res_of)
-    } else {
-        set2json(res_of.get("__single").unwrap())
-    };
-    write_json(args.get_one::<String>("outfile").unwrap(), &out_json)?;
+    res_of.write(args.get_one::<String>("outfile").unwrap())?;
 
     Ok(())
 }
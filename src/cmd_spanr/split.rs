@@ -40,7 +40,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Loading
     //----------------------------
-    let json: BTreeMap<String, Value> = read_json(args.get_one::<String>("infile").unwrap());
+    let json: BTreeMap<String, Value> = read_json(args.get_one::<String>("infile").unwrap())?;
 
     let outdir = args.get_one::<String>("outdir").unwrap();
     if outdir != "stdout" {
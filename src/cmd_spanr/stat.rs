@@ -7,6 +7,13 @@ use std::collections::BTreeMap;
 pub fn make_subcommand() -> Command {
     Command::new("stat")
         .about("Coverage on chromosomes for runlists")
+        .after_help(
+            r###"
+Stranded runlists are reported like multi-level ones, with strands as keys
+    * With `--merge-strands`, both strands are merged before reporting
+
+"###,
+        )
         .arg(
             Arg::new("chr.sizes")
                 .required(true)
@@ -25,6 +32,12 @@ pub fn make_subcommand() -> Command {
                 .action(ArgAction::SetTrue)
                 .help("Only write whole genome stats"),
         )
+        .arg(
+            Arg::new("merge_strands")
+                .long("merge-strands")
+                .action(ArgAction::SetTrue)
+                .help("Merge strands of stranded runlists"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -40,12 +53,22 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Loading
     //----------------------------
-    let sizes = read_sizes(args.get_one::<String>("chr.sizes").unwrap());
-
-    let json: BTreeMap<String, Value> = read_json(args.get_one::<String>("infile").unwrap());
-    let is_multi: bool = json.values().next().unwrap().is_object();
-
-    let set_of = json2set_m(&json);
+    let sizes = read_sizes(args.get_one::<String>("chr.sizes").unwrap())?;
+
+    let json: BTreeMap<String, Value> = read_json(args.get_one::<String>("infile").unwrap())?;
+    let is_stranded = is_stranded(&json);
+    let is_merge_strands = args.get_flag("merge_strands");
+
+    let set_of = if is_stranded {
+        let set_of = json2set_stranded(&json)?;
+        if is_merge_strands {
+            GenomeSets::single(merge_strands(&set_of).into())
+        } else {
+            set_of.into()
+        }
+    } else {
+        GenomeSets::from_json(&json)?
+    };
 
     let is_all = args.get_flag("all");
 
@@ -55,10 +78,13 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     let mut lines: Vec<String> = Vec::new(); // Avoid lifetime problems
     let mut header = "key,chr,chrLength,size,coverage".to_string();
 
-    if is_multi {
+    if !set_of.is_single() {
         if is_all {
             header = header.replace("chr,", "");
         }
+        if is_stranded {
+            header = header.replace("key,", "strand,");
+        }
         lines.push(header);
 
         for (name, set) in &set_of {
@@ -72,7 +98,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         }
         lines.push(header);
 
-        let key_lines = csv_lines(set_of.get("__single").unwrap(), &sizes, is_all, None);
+        let key_lines = csv_lines(&set_of[GenomeSets::SINGLE], &sizes, is_all, None);
         lines.push(key_lines);
     }
 
@@ -85,8 +111,8 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
 }
 
 fn csv_lines(
-    set: &BTreeMap<String, IntSpan>,
-    sizes: &BTreeMap<String, i32>,
+    set: &GenomeSet,
+    sizes: &BTreeMap<String, i64>,
     is_all: bool,
     prefix: Option<&str>,
 ) -> String {
@@ -94,9 +120,9 @@ fn csv_lines(
 
     let mut all_length: i64 = 0;
     let mut all_size: i64 = 0;
-    for chr in set.keys() {
+    for (chr, intspan) in set {
         let length = *sizes.get(chr).unwrap();
-        let size = set.get(chr).unwrap().cardinality();
+        let size = intspan.cardinality();
         let line = format!(
             "{},{},{},{:.4}\n",
             chr,
@@ -109,8 +135,8 @@ fn csv_lines(
         };
         lines.push_str(line.as_str());
 
-        all_length += length as i64;
-        all_size += size as i64;
+        all_length += length;
+        all_size += size;
     }
 
     let mut all_line = format!(
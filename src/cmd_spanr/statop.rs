@@ -1,6 +1,5 @@
 use clap::*;
 use intspan::*;
-use serde_json::Value;
 use std::collections::{BTreeMap, BTreeSet};
 use std::ffi::OsStr;
 use std::path::Path;
@@ -48,6 +47,15 @@ pub fn make_subcommand() -> Command {
                 .num_args(1)
                 .help("basename of infile2"),
         )
+        .arg(
+            Arg::new("parallel")
+                .long("parallel")
+                .short('p')
+                .num_args(1)
+                .default_value("1")
+                .value_parser(value_parser!(usize))
+                .help("Number of threads"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -63,14 +71,10 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Loading
     //----------------------------
-    let sizes = read_sizes(args.get_one::<String>("chr.sizes").unwrap());
+    let sizes = read_sizes(args.get_one::<String>("chr.sizes").unwrap())?;
 
-    let json: BTreeMap<String, Value> = read_json(args.get_one::<String>("infile1").unwrap());
-    let is_multi: bool = json.values().next().unwrap().is_object();
-    let mut s1_of = json2set_m(&json);
-
-    let single: BTreeMap<String, Value> = read_json(args.get_one::<String>("infile2").unwrap());
-    let mut s2 = json2set(&single);
+    let mut s1_of = GenomeSets::read(args.get_one::<String>("infile1").unwrap())?;
+    let mut s2 = GenomeSet::read(args.get_one::<String>("infile2").unwrap())?;
 
     let is_all = args.get_flag("all");
     let base = if args.contains_id("base") {
@@ -82,6 +86,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
             .unwrap()
     };
     let op = args.get_one::<String>("op").unwrap().as_str();
+    let opt_parallel = *args.get_one::<usize>("parallel").unwrap();
 
     //----------------------------
     // Operating
@@ -90,24 +95,25 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         .keys()
         .map(|s| s.to_string())
         .collect::<BTreeSet<String>>();
-    fill_up_m(&mut s1_of, &chrs);
-    fill_up_s(&mut s2, &chrs);
-
-    let mut res_of: BTreeMap<String, BTreeMap<String, IntSpan>> = BTreeMap::new();
-    for (name, s1) in &s1_of {
-        let mut set: BTreeMap<String, IntSpan> = BTreeMap::new();
-        for chr in s1.keys() {
-            let intspan = match op {
-                "intersect" => s1.get(chr).unwrap().intersect(s2.get(chr).unwrap()),
-                "diff" => s1.get(chr).unwrap().diff(s2.get(chr).unwrap()),
-                "union" => s1.get(chr).unwrap().union(s2.get(chr).unwrap()),
-                "xor" => s1.get(chr).unwrap().xor(s2.get(chr).unwrap()),
-                _ => panic!("Invalid IntSpan Op"),
-            };
-            //            println!("Op {}: {}", op, op_intspan.to_string());
-            set.insert(chr.into(), intspan);
-        }
-        res_of.insert(name.into(), set);
+    s1_of.fill_up(&chrs);
+    s2.fill_up(&chrs);
+
+    // (name, chr) pairs are operated in parallel
+    let pairs: Vec<(&String, &String)> = s1_of
+        .iter()
+        .flat_map(|(name, s1)| s1.chrs().map(move |chr| (name, chr)))
+        .collect();
+    let f = intspan_op(op)?;
+    let intspans = par_map(&pairs, opt_parallel, |(name, chr)| {
+        f(&s1_of[name.as_str()][chr.as_str()], &s2[chr.as_str()])
+    });
+
+    let mut res_of: GenomeSets = s1_of
+        .names()
+        .map(|name| (name.to_string(), GenomeSet::new()))
+        .collect();
+    for ((name, chr), intspan) in pairs.iter().zip(intspans) {
+        res_of.get_mut(name).unwrap().insert(chr, intspan);
     }
 
     let mut lines: Vec<String> = Vec::new(); // Avoid lifetime problems
@@ -116,21 +122,14 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         base, base
     );
 
-    if is_multi {
+    if !s1_of.is_single() {
         if is_all {
             header = header.replace("chr,", "");
         }
         lines.push(header);
 
-        for name in s1_of.keys() {
-            let key_lines = csv_lines(
-                s1_of.get(name).unwrap(),
-                &sizes,
-                &s2,
-                res_of.get(name).unwrap(),
-                is_all,
-                Some(name),
-            );
+        for name in s1_of.names() {
+            let key_lines = csv_lines(&s1_of[name], &sizes, &s2, &res_of[name], is_all, Some(name));
             lines.push(key_lines);
         }
     } else {
@@ -141,10 +140,10 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         lines.push(header);
 
         let key_lines = csv_lines(
-            s1_of.get("__single").unwrap(),
+            &s1_of[GenomeSets::SINGLE],
             &sizes,
             &s2,
-            res_of.get("__single").unwrap(),
+            &res_of[GenomeSets::SINGLE],
             is_all,
             None,
         );
@@ -160,10 +159,10 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
 }
 
 fn csv_lines(
-    s1: &BTreeMap<String, IntSpan>,
-    sizes: &BTreeMap<String, i32>,
-    s2: &BTreeMap<String, IntSpan>,
-    set_op: &BTreeMap<String, IntSpan>,
+    s1: &GenomeSet,
+    sizes: &BTreeMap<String, i64>,
+    s2: &GenomeSet,
+    set_op: &GenomeSet,
     is_all: bool,
     prefix: Option<&str>,
 ) -> String {
@@ -173,12 +172,12 @@ fn csv_lines(
     let mut all_size: i64 = 0;
     let mut all_s2_length: i64 = 0;
     let mut all_s2_size: i64 = 0;
-    for chr in s1.keys() {
+    for (chr, intspan) in s1 {
         let length = *sizes.get(chr).unwrap();
-        let size = s1.get(chr).unwrap().cardinality();
+        let size = intspan.cardinality();
 
-        let s2_length = s2.get(chr).unwrap().cardinality();
-        let s2_size = set_op.get(chr).unwrap().cardinality();
+        let s2_length = s2[chr.as_str()].cardinality();
+        let s2_size = set_op[chr.as_str()].cardinality();
 
         let c1 = size as f64 / length as f64;
         let c2 = if s2_length == 0 {
@@ -201,10 +200,10 @@ fn csv_lines(
         };
         lines.push_str(line.as_str());
 
-        all_length += length as i64;
-        all_size += size as i64;
-        all_s2_length += s2_length as i64;
-        all_s2_size += s2_size as i64;
+        all_length += length;
+        all_size += size;
+        all_s2_length += s2_length;
+        all_s2_size += s2_size;
     }
 
     let all_c1 = all_size as f64 / all_length as f64;
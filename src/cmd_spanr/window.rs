@@ -0,0 +1,212 @@
+use clap::*;
+use intspan::*;
+
+// Create clap subcommand arguments
+pub fn make_subcommand() -> Command {
+    Command::new("window")
+        .about("Sliding windows on chromosomes")
+        .after_help(
+            r###"
+<infile> is a chr.sizes file or a single runlist file
+    * Files ending in `.json`, `.yml`, `.yaml` or `.rlb` are read as runlists
+
+Windows are written as ranges, one per line
+
+    I:1-1000
+    I:501-1500
+
+* --mode
+    * inside: windows lie inside each span of the runlist
+    * bases: windows are counted in bases of the runlist, gaps between spans are skipped
+        * The range of such a window is from its first base to its last base
+    * For chr.sizes, both modes give the same windows
+
+* The last window of a chromosome or a span may be shorter than `--size`
+    * With `--full`, only full-size windows are written
+
+Example:
+
+    spanr window tests/spanr/S288c.chr.sizes -s 100000 -t 50000
+
+    spanr window tests/spanr/intergenic.json -s 500 --full
+
+    spanr window tests/spanr/intergenic.json -s 1000 --mode bases
+
+"###,
+        )
+        .arg(
+            Arg::new("infile")
+                .required(true)
+                .index(1)
+                .help("Sets the input file to use"),
+        )
+        .arg(
+            Arg::new("size")
+                .long("size")
+                .short('s')
+                .num_args(1)
+                .default_value("1000")
+                .value_parser(value_parser!(i64))
+                .help("Size of windows"),
+        )
+        .arg(
+            Arg::new("step")
+                .long("step")
+                .short('t')
+                .num_args(1)
+                .value_parser(value_parser!(i64))
+                .help("Step of windows. Default is the size of windows"),
+        )
+        .arg(
+            Arg::new("mode")
+                .long("mode")
+                .num_args(1)
+                .default_value("inside")
+                .value_parser([
+                    builder::PossibleValue::new("inside"),
+                    builder::PossibleValue::new("bases"),
+                ])
+                .help("How to place windows on runlists"),
+        )
+        .arg(
+            Arg::new("full")
+                .long("full")
+                .action(ArgAction::SetTrue)
+                .help("Only write full-size windows"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
+        .arg(
+            Arg::new("outfile")
+                .long("outfile")
+                .short('o')
+                .num_args(1)
+                .default_value("stdout")
+                .help("Output filename. [stdout] for screen"),
+        )
+}
+
+// command implementation
+pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
+    //----------------------------
+    // Args
+    //----------------------------
+    let mut writer = writer(args.get_one::<String>("outfile").unwrap())?;
+
+    let opt_size = *args.get_one::<i64>("size").unwrap();
+    let opt_step = args.get_one::<i64>("step").copied().unwrap_or(opt_size);
+    let opt_mode = args.get_one::<String>("mode").unwrap().as_str();
+    let is_full = args.get_flag("full");
+
+    if opt_size < 1 || opt_step < 1 {
+        return Err(anyhow::anyhow!("--size and --step should be positive"));
+    }
+
+    //----------------------------
+    // Loading
+    //----------------------------
+    let infile = args.get_one::<String>("infile").unwrap();
+    let set = if is_runlist(infile) {
+        GenomeSet::read(infile)?
+    } else {
+        let mut skipped = Skipped::new(args.get_flag("strict"));
+        let mut set = GenomeSet::new();
+        for (i, line) in read_lines(infile)?.iter().enumerate() {
+            if is_comment(line, false) {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split('\t').collect();
+            match fields.get(1).map(|size| size.parse::<i64>()) {
+                Some(Ok(size)) if fields.len() == 2 && size >= 1 => {
+                    set.insert(fields[0], IntSpan::from_pair(1, size));
+                }
+                _ => skipped.skip(InputError::parse("chr.sizes line", line).at(
+                    infile,
+                    i + 1,
+                    None,
+                ))?,
+            }
+        }
+        skipped.report();
+
+        set
+    };
+
+    //----------------------------
+    // Operating
+    //----------------------------
+    for (chr, ints) in &set {
+        if ints.is_empty() {
+            continue;
+        }
+
+        let windows = if opt_mode == "bases" {
+            // Positions of windows are indices of bases, then converted back to coordinates
+            let indices = windows(1, ints.cardinality(), opt_size, opt_step, is_full);
+            let spans = ints.spans();
+            let lowers = index_to_pos(&spans, indices.iter().map(|w| w.0));
+            let uppers = index_to_pos(&spans, indices.iter().map(|w| w.1));
+            lowers.into_iter().zip(uppers).collect::<Vec<_>>()
+        } else {
+            ints.spans()
+                .into_iter()
+                .flat_map(|(lower, upper)| windows(lower, upper, opt_size, opt_step, is_full))
+                .collect::<Vec<_>>()
+        };
+
+        //----------------------------
+        // Output
+        //----------------------------
+        for (lower, upper) in windows {
+            writer.write_fmt(format_args!("{}:{}-{}\n", chr, lower, upper))?;
+        }
+    }
+
+    Ok(())
+}
+
+// Windows from lower to upper. Stop at the window reaching upper
+pub fn windows(lower: i64, upper: i64, size: i64, step: i64, is_full: bool) -> Vec<(i64, i64)> {
+    let mut windows = vec![];
+
+    let mut start = lower;
+    while start <= upper {
+        let end = (start + size - 1).min(upper);
+        if !is_full || end - start + 1 == size {
+            windows.push((start, end));
+        }
+        if end == upper {
+            break;
+        }
+        start += step;
+    }
+
+    windows
+}
+
+// Coordinates of ascending 1-based indices of bases, walking the spans only once
+fn index_to_pos(spans: &[(i64, i64)], indices: impl Iterator<Item = i64>) -> Vec<i64> {
+    let mut positions = vec![];
+
+    let mut iter = spans.iter();
+    let mut cur = iter.next();
+    let mut offset = 0; // bases in the spans before `cur`
+    for idx in indices {
+        while let Some(&(lower, upper)) = cur {
+            let len = upper - lower + 1;
+            if idx <= offset + len {
+                positions.push(lower + idx - offset - 1);
+                break;
+            }
+            offset += len;
+            cur = iter.next();
+        }
+    }
+
+    positions
+}
@@ -0,0 +1,218 @@
+use clap::*;
+use intspan::*;
+use std::io::BufRead;
+
+// Create clap subcommand arguments
+pub fn make_subcommand() -> Command {
+    Command::new("winstat")
+        .about("Coverage and density statistics in sliding windows")
+        .after_help(
+            r###"
+Windows are placed on chromosomes of chr.sizes, like `spanr window`
+
+Output is a TSV file with a header line
+
+* Fields of windows: `chr`, `start`, `end` and `length`
+* With `--runlist`
+    * bases: number of bases of the runlist in the window
+    * coverage: bases / length
+* With range files as <infiles>
+    * count: number of ranges overlapping with the window
+    * density: count per kbp
+    * covered: number of bases in the window covered by at least `--minimum` ranges
+
+`.bed` and `.bed.gz` files are read as BED, with 0-based starts
+
+Example:
+
+    spanr winstat tests/spanr/S288c.chr.sizes -r tests/spanr/intergenic.json -s 10000
+
+    spanr winstat tests/spanr/S288c.chr.sizes tests/spanr/S288c.rg -s 100000 -t 50000
+
+"###,
+        )
+        .arg(
+            Arg::new("chr.sizes")
+                .required(true)
+                .index(1)
+                .help("Sets the input file to use"),
+        )
+        .arg(
+            Arg::new("infiles")
+                .num_args(0..)
+                .index(2)
+                .help("Range files to count"),
+        )
+        .arg(
+            Arg::new("runlist")
+                .long("runlist")
+                .short('r')
+                .num_args(1)
+                .help("A single runlist file"),
+        )
+        .arg(
+            Arg::new("size")
+                .long("size")
+                .short('s')
+                .num_args(1)
+                .default_value("1000")
+                .value_parser(value_parser!(i64))
+                .help("Size of windows"),
+        )
+        .arg(
+            Arg::new("step")
+                .long("step")
+                .short('t')
+                .num_args(1)
+                .value_parser(value_parser!(i64))
+                .help("Step of windows. Default is the size of windows"),
+        )
+        .arg(
+            Arg::new("full")
+                .long("full")
+                .action(ArgAction::SetTrue)
+                .help("Only write full-size windows"),
+        )
+        .arg(
+            Arg::new("minimum")
+                .long("minimum")
+                .short('m')
+                .num_args(1)
+                .default_value("1")
+                .value_parser(value_parser!(i32))
+                .help("Set the minimum depth of `covered`"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Abort on malformed lines instead of skipping them"),
+        )
+        .arg(
+            Arg::new("outfile")
+                .long("outfile")
+                .short('o')
+                .num_args(1)
+                .default_value("stdout")
+                .help("Output filename. [stdout] for screen"),
+        )
+}
+
+// command implementation
+pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
+    //----------------------------
+    // Args
+    //----------------------------
+    let mut writer = writer(args.get_one::<String>("outfile").unwrap())?;
+
+    let opt_size = *args.get_one::<i64>("size").unwrap();
+    let opt_step = args.get_one::<i64>("step").copied().unwrap_or(opt_size);
+    let is_full = args.get_flag("full");
+    let minimum = *args.get_one::<i32>("minimum").unwrap();
+
+    if opt_size < 1 || opt_step < 1 {
+        return Err(anyhow::anyhow!("--size and --step should be positive"));
+    }
+    if minimum < 1 {
+        return Err(anyhow::anyhow!("--minimum should be positive"));
+    }
+
+    let infiles: Vec<&String> = match args.get_many::<String>("infiles") {
+        Some(x) => x.collect(),
+        None => vec![],
+    };
+    if args.get_one::<String>("runlist").is_none() && infiles.is_empty() {
+        return Err(anyhow::anyhow!("Need --runlist or range files"));
+    }
+
+    //----------------------------
+    // Loading
+    //----------------------------
+    let sizes = read_sizes(args.get_one::<String>("chr.sizes").unwrap())?;
+
+    let set: Option<GenomeSet> = args
+        .get_one::<String>("runlist")
+        .map(|file| GenomeSet::read(file))
+        .transpose()?;
+
+    let mut ranges: Vec<(Range, ())> = vec![];
+    let mut skipped = Skipped::new(args.get_flag("strict"));
+    for infile in &infiles {
+        let reader = reader(infile)?;
+        let is_bed = is_bed(infile);
+        for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+            if is_comment(&line, is_bed) {
+                continue;
+            }
+
+            let range = match parse_rg(&line, is_bed) {
+                Ok(range) => range,
+                Err(e) => {
+                    skipped.skip(e.at(infile, i + 1, None))?;
+                    continue;
+                }
+            };
+
+            ranges.push((range, ()));
+        }
+    }
+    skipped.report();
+    let index = RangeIndex::from_ranges(ranges);
+
+    //----------------------------
+    // Output
+    //----------------------------
+    let mut header = "chr\tstart\tend\tlength".to_string();
+    if set.is_some() {
+        header.push_str("\tbases\tcoverage");
+    }
+    if !infiles.is_empty() {
+        header.push_str("\tcount\tdensity\tcovered");
+    }
+    writer.write_fmt(format_args!("{}\n", header))?;
+
+    for (chr, size) in &sizes {
+        // Runs of the runlist and depths of ranges, swept once per chromosome
+        let bases_chr = match &set {
+            Some(set) => IntMap::depth(set.get(chr).map(|ints| ints.spans()).unwrap_or_default()),
+            None => IntMap::new(),
+        };
+        let depth_chr = IntMap::depth(
+            index
+                .find(&Range::from(chr, 1, *size))
+                .into_iter()
+                .map(|(range, _)| (*range.start(), *range.end())),
+        );
+        // Bases in the window with a depth of at least `min`
+        let bases_in = |map: &IntMap<usize>, start: i64, end: i64, min: usize| -> i64 {
+            map.runs_in(start, end)
+                .filter(|(_, _, depth)| **depth >= min)
+                .map(|(lower, upper, _)| upper - lower + 1)
+                .sum()
+        };
+
+        for (start, end) in super::window::windows(1, *size, opt_size, opt_step, is_full) {
+            let length = end - start + 1;
+
+            let mut line = format!("{}\t{}\t{}\t{}", chr, start, end, length);
+            if set.is_some() {
+                let bases = bases_in(&bases_chr, start, end, 1);
+                line.push_str(&format!("\t{}\t{:.4}", bases, bases as f64 / length as f64));
+            }
+            if !infiles.is_empty() {
+                let count = index.count(&Range::from(chr, start, end));
+                let covered = bases_in(&depth_chr, start, end, minimum as usize);
+                line.push_str(&format!(
+                    "\t{}\t{:.4}\t{}",
+                    count,
+                    count as f64 * 1000.0 / length as f64,
+                    covered
+                ));
+            }
+
+            writer.write_fmt(format_args!("{}\n", line))?;
+        }
+    }
+
+    Ok(())
+}
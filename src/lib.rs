@@ -4,10 +4,18 @@ extern crate lazy_static;
 mod libs;
 mod utils;
 
+pub use crate::libs::bigwig::*;
 pub use crate::libs::coverage::*;
+pub use crate::libs::error::*;
+pub use crate::libs::fasta::*;
+pub use crate::libs::genome::*;
+pub use crate::libs::gff::*;
+pub use crate::libs::intmap::*;
 pub use crate::libs::intspan::*;
 pub use crate::libs::linalg::*;
 pub use crate::libs::matrix::*;
 pub use crate::libs::range::*;
+pub use crate::libs::range_index::*;
+pub use crate::libs::rlb::*;
 
 pub use crate::utils::*;
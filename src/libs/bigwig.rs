@@ -0,0 +1,740 @@
+//! A bigWig writer, following the BBI file format of the UCSC Genome Browser.
+//!
+//! Only bedGraph sections are written. Data blocks are zlib compressed, and zoom levels are
+//! generated so that genome browsers can show the whole chromosome quickly.
+
+use anyhow::{anyhow, bail};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+const BIGWIG_MAGIC: u32 = 0x888F_FC26;
+const CHROM_TREE_MAGIC: u32 = 0x78CA_8C91;
+const CIR_TREE_MAGIC: u32 = 0x2468_ACE0;
+
+const BBI_VERSION: u16 = 4;
+// Items in a data block, and children of a node in the R-tree
+const ITEMS_PER_SLOT: usize = 1024;
+const BLOCK_SIZE: usize = 256;
+const MAX_ZOOM_LEVELS: usize = 10;
+const ZOOM_INCREMENT: i64 = 4;
+
+const HEADER_SIZE: usize = 64;
+const ZOOM_HEADER_SIZE: usize = 24;
+
+// bedGraph section type
+const SECTION_BEDGRAPH: u8 = 1;
+
+/// A compressed block, with the region it covers
+#[derive(Debug, Clone)]
+struct Block {
+    chrom_id: u32,
+    start: u32,
+    end: u32,
+    offset: u64,
+    size: u64,
+}
+
+/// A zoom record, summarizing values in a bin
+#[derive(Debug, Clone)]
+struct ZoomRecord {
+    chrom_id: u32,
+    start: u32,
+    end: u32,
+    valid_count: u32,
+    min: f32,
+    max: f32,
+    sum: f32,
+    sum_squares: f32,
+}
+
+/// Summary of all values
+#[derive(Debug, Clone, Default)]
+struct Summary {
+    bases_covered: u64,
+    min: f64,
+    max: f64,
+    sum: f64,
+    sum_squares: f64,
+}
+
+// Little-endian helpers
+trait PutLe {
+    fn put_u8(&mut self, v: u8);
+    fn put_u16(&mut self, v: u16);
+    fn put_u32(&mut self, v: u32);
+    fn put_u64(&mut self, v: u64);
+    fn put_f32(&mut self, v: f32);
+    fn put_f64(&mut self, v: f64);
+    fn set_u64(&mut self, pos: usize, v: u64);
+}
+
+impl PutLe for Vec<u8> {
+    fn put_u8(&mut self, v: u8) {
+        self.push(v);
+    }
+    fn put_u16(&mut self, v: u16) {
+        self.extend_from_slice(&v.to_le_bytes());
+    }
+    fn put_u32(&mut self, v: u32) {
+        self.extend_from_slice(&v.to_le_bytes());
+    }
+    fn put_u64(&mut self, v: u64) {
+        self.extend_from_slice(&v.to_le_bytes());
+    }
+    fn put_f32(&mut self, v: f32) {
+        self.extend_from_slice(&v.to_le_bytes());
+    }
+    fn put_f64(&mut self, v: f64) {
+        self.extend_from_slice(&v.to_le_bytes());
+    }
+    fn set_u64(&mut self, pos: usize, v: u64) {
+        self[pos..pos + 8].copy_from_slice(&v.to_le_bytes());
+    }
+}
+
+/// Build a bigWig file in memory.
+///
+/// `intervals_of` is chr => `(start, end, value)`, with 1-based and inclusive positions.
+/// Intervals of a chromosome should be sorted and not overlapping.
+/// Chromosomes without intervals are not written.
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// let sizes: BTreeMap<String, i64> = vec![("I".to_string(), 1000)].into_iter().collect();
+/// let mut intervals_of: BTreeMap<String, Vec<(i64, i64, f32)>> = BTreeMap::new();
+/// intervals_of.insert("I".to_string(), vec![(1, 100, 1.0), (101, 150, 2.0)]);
+///
+/// let bytes = intspan::bigwig_bytes(&sizes, &intervals_of).unwrap();
+/// assert_eq!(&bytes[0..4], &0x888F_FC26u32.to_le_bytes());
+///
+/// intervals_of.insert("II".to_string(), vec![(1, 100, 1.0)]);
+/// assert!(intspan::bigwig_bytes(&sizes, &intervals_of).is_err());
+/// ```
+pub fn bigwig_bytes(
+    sizes: &BTreeMap<String, i64>,
+    intervals_of: &BTreeMap<String, Vec<(i64, i64, f32)>>,
+) -> anyhow::Result<Vec<u8>> {
+    //----------------------------
+    // Check and convert intervals
+    //----------------------------
+    // chromosomes with intervals, ordered by names
+    let mut chroms: Vec<(String, u32)> = vec![];
+    // 0-based, half-open intervals of each chromosome
+    let mut data: Vec<Vec<(u32, u32, f32)>> = vec![];
+    for (chr, intervals) in intervals_of {
+        if intervals.is_empty() {
+            continue;
+        }
+        let size = *sizes
+            .get(chr)
+            .ok_or_else(|| anyhow!("Chromosome `{}` not found in chr.sizes", chr))?;
+        if size > u32::MAX as i64 {
+            bail!("Chromosome `{}` is too long for bigWig", chr);
+        }
+
+        let mut ivs: Vec<(u32, u32, f32)> = vec![];
+        let mut last_end = 0;
+        for (start, end, value) in intervals {
+            if *start < 1 || start > end || *end > size {
+                bail!("Invalid interval {}:{}-{}", chr, start, end);
+            }
+            if *start <= last_end {
+                bail!("Intervals of `{}` are not sorted or overlapping", chr);
+            }
+            last_end = *end;
+            ivs.push((*start as u32 - 1, *end as u32, *value));
+        }
+
+        chroms.push((chr.to_string(), size as u32));
+        data.push(ivs);
+    }
+    if chroms.len() > u16::MAX as usize {
+        bail!("Too many chromosomes for bigWig");
+    }
+
+    let summary = summarize(&data);
+    let zooms = zoom_levels(&chroms, &data);
+
+    //----------------------------
+    // Header, zoom headers and the total summary
+    //----------------------------
+    let mut buf: Vec<u8> = vec![0; HEADER_SIZE + ZOOM_HEADER_SIZE * zooms.len()];
+
+    let summary_offset = buf.len();
+    buf.put_u64(summary.bases_covered);
+    buf.put_f64(summary.min);
+    buf.put_f64(summary.max);
+    buf.put_f64(summary.sum);
+    buf.put_f64(summary.sum_squares);
+
+    //----------------------------
+    // Chromosome B+ tree
+    //----------------------------
+    let chrom_tree_offset = buf.len();
+    let key_size = chroms.iter().map(|(chr, _)| chr.len()).max().unwrap_or(1);
+    buf.put_u32(CHROM_TREE_MAGIC);
+    buf.put_u32(chroms.len().max(1) as u32); // blockSize, all chromosomes in one leaf
+    buf.put_u32(key_size as u32);
+    buf.put_u32(8); // valSize
+    buf.put_u64(chroms.len() as u64);
+    buf.put_u64(0);
+
+    buf.put_u8(1); // isLeaf
+    buf.put_u8(0);
+    buf.put_u16(chroms.len() as u16);
+    for (i, (chr, size)) in chroms.iter().enumerate() {
+        let mut key = chr.as_bytes().to_vec();
+        key.resize(key_size, 0);
+        buf.extend_from_slice(&key);
+        buf.put_u32(i as u32);
+        buf.put_u32(*size);
+    }
+
+    //----------------------------
+    // Data and the R-tree index
+    //----------------------------
+    let mut max_block_size = 0;
+
+    let data_offset = buf.len();
+    buf.put_u64(0); // number of blocks, filled later
+    let mut blocks: Vec<Block> = vec![];
+    for (chrom_id, ivs) in data.iter().enumerate() {
+        for chunk in ivs.chunks(ITEMS_PER_SLOT) {
+            let mut raw: Vec<u8> = vec![];
+            raw.put_u32(chrom_id as u32);
+            raw.put_u32(chunk[0].0);
+            raw.put_u32(chunk[chunk.len() - 1].1);
+            raw.put_u32(0); // itemStep
+            raw.put_u32(0); // itemSpan
+            raw.put_u8(SECTION_BEDGRAPH);
+            raw.put_u8(0);
+            raw.put_u16(chunk.len() as u16);
+            for (start, end, value) in chunk {
+                raw.put_u32(*start);
+                raw.put_u32(*end);
+                raw.put_f32(*value);
+            }
+
+            max_block_size = max_block_size.max(raw.len());
+            let block = write_block(
+                &mut buf,
+                &raw,
+                chrom_id as u32,
+                chunk[0].0,
+                chunk[chunk.len() - 1].1,
+            )?;
+            blocks.push(block);
+        }
+    }
+    buf.set_u64(data_offset, blocks.len() as u64);
+
+    let index_offset = buf.len();
+    write_cir_tree(&mut buf, &blocks, index_offset as u64);
+
+    //----------------------------
+    // Zoom levels
+    //----------------------------
+    let mut zoom_headers: Vec<(u32, u64, u64)> = vec![];
+    for (reduction, records) in &zooms {
+        let zoom_data_offset = buf.len();
+        buf.put_u32(records.len() as u32);
+
+        let mut blocks: Vec<Block> = vec![];
+        // Records of a block should be on the same chromosome
+        let mut chunks: Vec<&[ZoomRecord]> = vec![];
+        let mut begin = 0;
+        for i in 1..=records.len() {
+            if i == records.len()
+                || records[i].chrom_id != records[begin].chrom_id
+                || i - begin == ITEMS_PER_SLOT
+            {
+                chunks.push(&records[begin..i]);
+                begin = i;
+            }
+        }
+        for chunk in chunks {
+            let mut raw: Vec<u8> = vec![];
+            for r in chunk {
+                raw.put_u32(r.chrom_id);
+                raw.put_u32(r.start);
+                raw.put_u32(r.end);
+                raw.put_u32(r.valid_count);
+                raw.put_f32(r.min);
+                raw.put_f32(r.max);
+                raw.put_f32(r.sum);
+                raw.put_f32(r.sum_squares);
+            }
+
+            max_block_size = max_block_size.max(raw.len());
+            let block = write_block(
+                &mut buf,
+                &raw,
+                chunk[0].chrom_id,
+                chunk[0].start,
+                chunk[chunk.len() - 1].end,
+            )?;
+            blocks.push(block);
+        }
+
+        let zoom_index_offset = buf.len();
+        write_cir_tree(&mut buf, &blocks, zoom_index_offset as u64);
+
+        zoom_headers.push((
+            *reduction as u32,
+            zoom_data_offset as u64,
+            zoom_index_offset as u64,
+        ));
+    }
+
+    //----------------------------
+    // Fill the header
+    //----------------------------
+    let mut header: Vec<u8> = vec![];
+    header.put_u32(BIGWIG_MAGIC);
+    header.put_u16(BBI_VERSION);
+    header.put_u16(zooms.len() as u16);
+    header.put_u64(chrom_tree_offset as u64);
+    header.put_u64(data_offset as u64);
+    header.put_u64(index_offset as u64);
+    header.put_u16(0); // fieldCount
+    header.put_u16(0); // definedFieldCount
+    header.put_u64(0); // autoSqlOffset
+    header.put_u64(summary_offset as u64);
+    header.put_u32(max_block_size as u32); // uncompressBufSize
+    header.put_u64(0); // extensionOffset
+    for (reduction, zoom_data_offset, zoom_index_offset) in zoom_headers {
+        header.put_u32(reduction);
+        header.put_u32(0);
+        header.put_u64(zoom_data_offset);
+        header.put_u64(zoom_index_offset);
+    }
+    buf[..header.len()].copy_from_slice(&header);
+
+    Ok(buf)
+}
+
+/// Write a bigWig file. `output` can be `stdout`.
+///
+/// See [`bigwig_bytes`] for the layout of `intervals_of`.
+pub fn write_bigwig(
+    output: &str,
+    sizes: &BTreeMap<String, i64>,
+    intervals_of: &BTreeMap<String, Vec<(i64, i64, f32)>>,
+) -> anyhow::Result<()> {
+    let bytes = bigwig_bytes(sizes, intervals_of)?;
+
+    let mut writer = crate::writer(output)?;
+    writer.write_all(&bytes)?;
+
+    Ok(())
+}
+
+// Compress `raw` and append it to `buf`
+fn write_block(
+    buf: &mut Vec<u8>,
+    raw: &[u8],
+    chrom_id: u32,
+    start: u32,
+    end: u32,
+) -> anyhow::Result<Block> {
+    let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+    encoder.write_all(raw)?;
+    let compressed = encoder.finish()?;
+
+    let block = Block {
+        chrom_id,
+        start,
+        end,
+        offset: buf.len() as u64,
+        size: compressed.len() as u64,
+    };
+    buf.extend_from_slice(&compressed);
+
+    Ok(block)
+}
+
+/// Bounds of a node in the R-tree: (startChromIx, startBase, endChromIx, endBase)
+type Bounds = (u32, u32, u32, u32);
+
+// Write the R-tree index of blocks. Blocks are sorted by positions.
+fn write_cir_tree(buf: &mut Vec<u8>, blocks: &[Block], end_file_offset: u64) {
+    // Nodes of each level, from leaves to the root. A node is its bounds and the range of children
+    let mut levels: Vec<Vec<(Bounds, std::ops::Range<usize>)>> = vec![];
+
+    let leaves = (0..blocks.len())
+        .step_by(BLOCK_SIZE)
+        .map(|begin| {
+            let end = (begin + BLOCK_SIZE).min(blocks.len());
+            let bounds = (
+                blocks[begin].chrom_id,
+                blocks[begin].start,
+                blocks[end - 1].chrom_id,
+                blocks[end - 1].end,
+            );
+            (bounds, begin..end)
+        })
+        .collect::<Vec<_>>();
+    levels.push(leaves);
+
+    while levels.last().unwrap().len() > 1 {
+        let below = levels.last().unwrap();
+        let nodes = (0..below.len())
+            .step_by(BLOCK_SIZE)
+            .map(|begin| {
+                let end = (begin + BLOCK_SIZE).min(below.len());
+                let bounds = (
+                    below[begin].0 .0,
+                    below[begin].0 .1,
+                    below[end - 1].0 .2,
+                    below[end - 1].0 .3,
+                );
+                (bounds, begin..end)
+            })
+            .collect::<Vec<_>>();
+        levels.push(nodes);
+    }
+
+    let root = if blocks.is_empty() {
+        (0, 0, 0, 0)
+    } else {
+        levels.last().unwrap()[0].0
+    };
+    buf.put_u32(CIR_TREE_MAGIC);
+    buf.put_u32(BLOCK_SIZE as u32);
+    buf.put_u64(blocks.len() as u64);
+    buf.put_u32(root.0);
+    buf.put_u32(root.1);
+    buf.put_u32(root.2);
+    buf.put_u32(root.3);
+    buf.put_u64(end_file_offset);
+    buf.put_u32(ITEMS_PER_SLOT as u32);
+    buf.put_u32(0);
+
+    if blocks.is_empty() {
+        // An empty leaf as the root
+        buf.put_u8(1);
+        buf.put_u8(0);
+        buf.put_u16(0);
+        return;
+    }
+
+    // Nodes are written from the root to leaves. Offsets of each level are computed first
+    let node_size = |level: usize, n: usize| -> usize {
+        if level == 0 {
+            4 + n * 32
+        } else {
+            4 + n * 24
+        }
+    };
+    let mut level_offsets: Vec<usize> = vec![0; levels.len()];
+    let mut offset = buf.len();
+    for level in (0..levels.len()).rev() {
+        level_offsets[level] = offset;
+        offset += levels[level]
+            .iter()
+            .map(|(_, children)| node_size(level, children.len()))
+            .sum::<usize>();
+    }
+
+    for level in (0..levels.len()).rev() {
+        for (_, children) in &levels[level] {
+            buf.put_u8(if level == 0 { 1 } else { 0 });
+            buf.put_u8(0);
+            buf.put_u16(children.len() as u16);
+
+            if level == 0 {
+                for block in &blocks[children.clone()] {
+                    buf.put_u32(block.chrom_id);
+                    buf.put_u32(block.start);
+                    buf.put_u32(block.chrom_id);
+                    buf.put_u32(block.end);
+                    buf.put_u64(block.offset);
+                    buf.put_u64(block.size);
+                }
+            } else {
+                let below = &levels[level - 1];
+                for i in children.clone() {
+                    let (bounds, _) = &below[i];
+                    let child_offset = level_offsets[level - 1]
+                        + below[..i]
+                            .iter()
+                            .map(|(_, c)| node_size(level - 1, c.len()))
+                            .sum::<usize>();
+                    buf.put_u32(bounds.0);
+                    buf.put_u32(bounds.1);
+                    buf.put_u32(bounds.2);
+                    buf.put_u32(bounds.3);
+                    buf.put_u64(child_offset as u64);
+                }
+            }
+        }
+    }
+}
+
+fn summarize(data: &[Vec<(u32, u32, f32)>]) -> Summary {
+    let mut summary = Summary {
+        min: f64::INFINITY,
+        max: f64::NEG_INFINITY,
+        ..Default::default()
+    };
+
+    for (start, end, value) in data.iter().flatten() {
+        let len = (end - start) as f64;
+        let value = *value as f64;
+        summary.bases_covered += (end - start) as u64;
+        summary.min = summary.min.min(value);
+        summary.max = summary.max.max(value);
+        summary.sum += value * len;
+        summary.sum_squares += value * value * len;
+    }
+
+    if summary.bases_covered == 0 {
+        summary.min = 0.0;
+        summary.max = 0.0;
+    }
+
+    summary
+}
+
+// Zoom levels as (reduction, records). A level is kept only if it halves the number of records
+fn zoom_levels(
+    chroms: &[(String, u32)],
+    data: &[Vec<(u32, u32, f32)>],
+) -> Vec<(i64, Vec<ZoomRecord>)> {
+    let item_count: usize = data.iter().map(|ivs| ivs.len()).sum();
+    if item_count == 0 {
+        return vec![];
+    }
+    let bases: i64 = data
+        .iter()
+        .flatten()
+        .map(|(start, end, _)| (end - start) as i64)
+        .sum();
+    let max_size = chroms.iter().map(|(_, size)| *size as i64).max().unwrap();
+
+    let mut zooms = vec![];
+    let mut last_count = item_count;
+    let mut reduction = (bases / item_count as i64 * 10).max(10);
+    while zooms.len() < MAX_ZOOM_LEVELS && reduction <= u32::MAX as i64 {
+        let records = zoom_records(chroms, data, reduction);
+        if records.len() * 2 <= last_count {
+            last_count = records.len();
+            zooms.push((reduction, records));
+        }
+        if reduction >= max_size {
+            break;
+        }
+        reduction *= ZOOM_INCREMENT;
+    }
+
+    zooms
+}
+
+fn zoom_records(
+    chroms: &[(String, u32)],
+    data: &[Vec<(u32, u32, f32)>],
+    reduction: i64,
+) -> Vec<ZoomRecord> {
+    let mut records: Vec<ZoomRecord> = vec![];
+
+    for (chrom_id, ivs) in data.iter().enumerate() {
+        let size = chroms[chrom_id].1 as i64;
+        let mut current: Option<ZoomRecord> = None;
+
+        for (start, end, value) in ivs {
+            let mut pos = *start as i64;
+            while pos < *end as i64 {
+                let bin_start = pos / reduction * reduction;
+                let bin_end = (bin_start + reduction).min(size);
+                let part_end = bin_end.min(*end as i64);
+                let len = (part_end - pos) as f32;
+
+                if current.as_ref().map(|r| r.start as i64) != Some(bin_start) {
+                    if let Some(r) = current.take() {
+                        records.push(r);
+                    }
+                    current = Some(ZoomRecord {
+                        chrom_id: chrom_id as u32,
+                        start: bin_start as u32,
+                        end: bin_end as u32,
+                        valid_count: 0,
+                        min: *value,
+                        max: *value,
+                        sum: 0.0,
+                        sum_squares: 0.0,
+                    });
+                }
+                let r = current.as_mut().unwrap();
+                r.valid_count += len as u32;
+                r.min = r.min.min(*value);
+                r.max = r.max.max(*value);
+                r.sum += value * len;
+                r.sum_squares += value * value * len;
+
+                pos = part_end;
+            }
+        }
+
+        if let Some(r) = current.take() {
+            records.push(r);
+        }
+    }
+
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::ZlibDecoder;
+    use std::convert::TryInto;
+    use std::io::Read;
+
+    fn u16_at(buf: &[u8], pos: usize) -> u16 {
+        u16::from_le_bytes(buf[pos..pos + 2].try_into().unwrap())
+    }
+    fn u32_at(buf: &[u8], pos: usize) -> u32 {
+        u32::from_le_bytes(buf[pos..pos + 4].try_into().unwrap())
+    }
+    fn u64_at(buf: &[u8], pos: usize) -> u64 {
+        u64::from_le_bytes(buf[pos..pos + 8].try_into().unwrap())
+    }
+    fn f32_at(buf: &[u8], pos: usize) -> f32 {
+        f32::from_le_bytes(buf[pos..pos + 4].try_into().unwrap())
+    }
+
+    // Decompressed blocks referred by leaves of the R-tree at `index_offset`
+    fn read_blocks(buf: &[u8], index_offset: usize) -> Vec<Vec<u8>> {
+        assert_eq!(u32_at(buf, index_offset), CIR_TREE_MAGIC);
+
+        let mut blocks = vec![];
+        let mut stack = vec![index_offset + 48];
+        while let Some(node) = stack.pop() {
+            let is_leaf = buf[node] == 1;
+            let count = u16_at(buf, node + 2) as usize;
+            let mut children = vec![];
+            for i in 0..count {
+                if is_leaf {
+                    let item = node + 4 + i * 32;
+                    let offset = u64_at(buf, item + 16) as usize;
+                    let size = u64_at(buf, item + 24) as usize;
+                    let mut raw = vec![];
+                    ZlibDecoder::new(&buf[offset..offset + size])
+                        .read_to_end(&mut raw)
+                        .unwrap();
+                    blocks.push(raw);
+                } else {
+                    let item = node + 4 + i * 24;
+                    children.push(u64_at(buf, item + 16) as usize);
+                }
+            }
+            // Depth-first, keep the order of children
+            stack.extend(children.into_iter().rev());
+        }
+
+        blocks
+    }
+
+    fn round_trip(
+        sizes: &BTreeMap<String, i64>,
+        intervals_of: &BTreeMap<String, Vec<(i64, i64, f32)>>,
+    ) {
+        let buf = bigwig_bytes(sizes, intervals_of).unwrap();
+
+        assert_eq!(u32_at(&buf, 0), BIGWIG_MAGIC);
+        assert_eq!(u16_at(&buf, 4), BBI_VERSION);
+        let zoom_levels = u16_at(&buf, 6) as usize;
+        let chrom_tree_offset = u64_at(&buf, 8) as usize;
+        let index_offset = u64_at(&buf, 24) as usize;
+        let uncompress_buf_size = u32_at(&buf, 52) as usize;
+
+        // chromosomes
+        assert_eq!(u32_at(&buf, chrom_tree_offset), CHROM_TREE_MAGIC);
+        let key_size = u32_at(&buf, chrom_tree_offset + 8) as usize;
+        let count = u16_at(&buf, chrom_tree_offset + 32 + 2) as usize;
+        let mut chroms = vec![];
+        for i in 0..count {
+            let item = chrom_tree_offset + 36 + i * (key_size + 8);
+            let key = String::from_utf8(buf[item..item + key_size].to_vec()).unwrap();
+            chroms.push(key.trim_end_matches('\0').to_string());
+            assert_eq!(u32_at(&buf, item + key_size) as usize, i);
+        }
+
+        // intervals
+        let mut read_of: BTreeMap<String, Vec<(i64, i64, f32)>> = BTreeMap::new();
+        for raw in read_blocks(&buf, index_offset) {
+            assert!(raw.len() <= uncompress_buf_size);
+            let chr = &chroms[u32_at(&raw, 0) as usize];
+            assert_eq!(raw[20], SECTION_BEDGRAPH);
+            for i in 0..u16_at(&raw, 22) as usize {
+                let item = 24 + i * 12;
+                read_of.entry(chr.to_string()).or_default().push((
+                    u32_at(&raw, item) as i64 + 1,
+                    u32_at(&raw, item + 4) as i64,
+                    f32_at(&raw, item + 8),
+                ));
+            }
+        }
+        let expected: BTreeMap<String, Vec<(i64, i64, f32)>> = intervals_of
+            .iter()
+            .filter(|(_, v)| !v.is_empty())
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        assert_eq!(read_of, expected);
+
+        // zoom levels cover the same bases
+        let bases = u64_at(&buf, HEADER_SIZE + ZOOM_HEADER_SIZE * zoom_levels);
+        for level in 0..zoom_levels {
+            let header = HEADER_SIZE + ZOOM_HEADER_SIZE * level;
+            let zoom_index_offset = u64_at(&buf, header + 16) as usize;
+            let mut valid_count = 0;
+            for raw in read_blocks(&buf, zoom_index_offset) {
+                assert!(raw.len() <= uncompress_buf_size);
+                for i in 0..raw.len() / 32 {
+                    valid_count += u32_at(&raw, i * 32 + 12) as u64;
+                }
+            }
+            assert_eq!(valid_count, bases);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let sizes: BTreeMap<String, i64> = vec![
+            ("I".to_string(), 230218),
+            ("II".to_string(), 813184),
+            ("Mito".to_string(), 85779),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut intervals_of: BTreeMap<String, Vec<(i64, i64, f32)>> = BTreeMap::new();
+        intervals_of.insert("I".to_string(), vec![(1, 100, 1.0), (101, 150, 2.5)]);
+        intervals_of.insert("Mito".to_string(), vec![]);
+        round_trip(&sizes, &intervals_of);
+
+        // Multiple blocks and levels of the R-tree
+        let ivs: Vec<(i64, i64, f32)> = (0..300_000)
+            .map(|i| (i * 2 + 1, i * 2 + 1, (i % 7) as f32))
+            .collect();
+        intervals_of.insert("II".to_string(), ivs);
+        round_trip(&sizes, &intervals_of);
+
+        // No intervals
+        round_trip(&sizes, &BTreeMap::new());
+    }
+
+    #[test]
+    fn test_invalid() {
+        let sizes: BTreeMap<String, i64> = vec![("I".to_string(), 1000)].into_iter().collect();
+
+        let mut intervals_of: BTreeMap<String, Vec<(i64, i64, f32)>> = BTreeMap::new();
+        intervals_of.insert("I".to_string(), vec![(1, 100, 1.0), (100, 150, 2.0)]);
+        assert!(bigwig_bytes(&sizes, &intervals_of).is_err());
+
+        intervals_of.insert("I".to_string(), vec![(1, 1001, 1.0)]);
+        assert!(bigwig_bytes(&sizes, &intervals_of).is_err());
+    }
+}
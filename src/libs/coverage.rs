@@ -1,12 +1,56 @@
 use crate::IntSpan;
 use std::collections::BTreeMap;
 
-#[derive(Default, Clone)]
+/// Serialized as the max coverage and IntSpans of each tier
+///
+/// ```
+/// # use intspan::Coverage;
+/// let mut cover = Coverage::new_len(2, 500);
+/// cover.bump(1, 100);
+/// cover.bump(90, 150);
+///
+/// let s = serde_json::to_string(&cover).unwrap();
+/// assert_eq!(
+///     s,
+///     r#"{"max":2,"tiers":{"-1":"1-500","0":"151-500","1":"1-150","2":"90-100"}}"#
+/// );
+/// let cover: Coverage = serde_json::from_str(&s).unwrap();
+/// assert_eq!(cover.tiers()[&2].to_string(), "90-100");
+///
+/// // Tiers must run from -1 to max
+/// assert!(serde_json::from_str::<Coverage>(r#"{"max":2,"tiers":{}}"#).is_err());
+/// assert!(serde_json::from_str::<Coverage>(r#"{"max":-1,"tiers":{"-1":"1-500"}}"#).is_err());
+/// ```
+#[derive(Default, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "CoverageFields")]
 pub struct Coverage {
     max: i32,
     tiers: BTreeMap<i32, IntSpan>,
 }
 
+// The unvalidated form, as bump() and max_tier() expect every tier to exist
+#[derive(serde::Deserialize)]
+struct CoverageFields {
+    max: i32,
+    tiers: BTreeMap<i32, IntSpan>,
+}
+
+impl std::convert::TryFrom<CoverageFields> for Coverage {
+    type Error = String;
+
+    fn try_from(fields: CoverageFields) -> Result<Self, Self::Error> {
+        let CoverageFields { max, tiers } = fields;
+        if max < 0 {
+            return Err(format!("max should be non-negative, got {}", max));
+        }
+        if !tiers.keys().copied().eq(-1..=max) {
+            return Err(format!("tiers should be exactly -1 to {}", max));
+        }
+
+        Ok(Self { max, tiers })
+    }
+}
+
 impl Coverage {
     pub fn max(&self) -> &i32 {
         &self.max
@@ -15,11 +59,19 @@ impl Coverage {
         &self.tiers
     }
 
+    /// Tiers of uncovered regions run to `POS_INF`.
+    /// Before 64-bit coordinates, they ran to 1 Gbp, i.e. `Coverage::new_len(max, 1_000_000_000)`
+    ///
+    /// ```
+    /// # use intspan::Coverage;
+    /// let cover = Coverage::new(1);
+    /// assert_eq!(cover.tiers()[&0].max(), cover.tiers()[&0].get_pos_inf());
+    /// ```
     pub fn new(max: i32) -> Self {
-        Self::new_len(max, 1_000_000_000)
+        Self::new_len(max, IntSpan::new().get_pos_inf())
     }
 
-    pub fn new_len(max: i32, len: i32) -> Self {
+    pub fn new_len(max: i32, len: i64) -> Self {
         let mut tiers: BTreeMap<i32, IntSpan> = BTreeMap::new();
         tiers.insert(-1, IntSpan::from_pair(1, len));
         tiers.insert(0, IntSpan::from_pair(1, len));
@@ -31,7 +83,7 @@ impl Coverage {
         Self { max, tiers }
     }
 
-    fn begin_end(begin: i32, end: i32) -> (i32, i32) {
+    fn begin_end(begin: i64, end: i64) -> (i64, i64) {
         let mut tup = (begin.min(end), begin.max(end));
 
         if tup.0 == 0 {
@@ -47,7 +99,9 @@ impl Coverage {
     /// cover.bump(1, 100);
     /// cover.bump(90, 150);
     /// assert_eq!(cover.tiers().get(&1).unwrap().to_string(), "1-150");
-    /// # assert_eq!(cover.tiers().get(&0).unwrap().to_string(), "151-1000000000");
+    /// # assert!(cover.tiers().get(&0).unwrap().is_pos_inf());
+    /// cover.bump(3_000_000_001, 3_000_000_100);
+    /// assert_eq!(cover.tiers().get(&1).unwrap().to_string(), "1-150,3000000001-3000000100");
     ///
     /// let mut cover = Coverage::new_len(1, 500);
     /// cover.bump(1, 100);
@@ -56,7 +110,7 @@ impl Coverage {
     /// # assert_eq!(cover.tiers().get(&0).unwrap().to_string(), "151-500");
     /// # assert_eq!(cover.tiers().get(&-1).unwrap().to_string(), "1-500");
     /// ```
-    pub fn bump(&mut self, begin: i32, end: i32) {
+    pub fn bump(&mut self, begin: i64, end: i64) {
         let tup = Self::begin_end(begin, end);
         let mut intspan = IntSpan::from_pair(tup.0, tup.1);
 
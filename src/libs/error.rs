@@ -0,0 +1,184 @@
+//! Errors of reading inputs and parsing runlists, and counting of malformed lines.
+
+use std::fmt;
+
+/// Errors of files, values and lines
+///
+/// ```
+/// # use intspan::InputError;
+/// let err = InputError::parse("range", "I:100-abc");
+/// assert_eq!(err.to_string(), "invalid range: `I:100-abc`");
+///
+/// let err = err.at("ranges.tsv", 3, Some(2));
+/// assert_eq!(
+///     err.to_string(),
+///     "ranges.tsv: line 3, field 2: invalid range: `I:100-abc`"
+/// );
+/// ```
+#[derive(Debug)]
+pub enum InputError {
+    /// Can't open a file for reading or writing
+    File {
+        path: String,
+        source: std::io::Error,
+    },
+    /// A malformed value
+    Parse { kind: String, value: String },
+    /// An error at a line of a file, and an optional 1-based field
+    Line {
+        file: String,
+        line: usize,
+        field: Option<usize>,
+        source: Box<InputError>,
+    },
+}
+
+impl InputError {
+    pub fn file(path: &str, source: std::io::Error) -> Self {
+        InputError::File {
+            path: path.to_string(),
+            source,
+        }
+    }
+
+    pub fn parse(kind: &str, value: &str) -> Self {
+        InputError::Parse {
+            kind: kind.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    /// Locate the error at a 1-based line number
+    pub fn at(self, file: &str, line: usize, field: Option<usize>) -> Self {
+        InputError::Line {
+            file: file.to_string(),
+            line,
+            field,
+            source: Box::new(self),
+        }
+    }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::File { path, source } => write!(f, "could not open {}: {}", path, source),
+            InputError::Parse { kind, value } => write!(f, "invalid {}: `{}`", kind, value),
+            InputError::Line {
+                file,
+                line,
+                field,
+                source,
+            } => match field {
+                Some(field) => write!(f, "{}: line {}, field {}: {}", file, line, field, source),
+                None => write!(f, "{}: line {}: {}", file, line, source),
+            },
+        }
+    }
+}
+
+// Display already includes the underlying errors
+impl std::error::Error for InputError {}
+
+/// Malformed lines are counted, or abort the command with `--strict`
+///
+/// ```
+/// # use intspan::{InputError, Skipped};
+/// let mut skipped = Skipped::new(false);
+/// assert!(skipped.skip(InputError::parse("range", "foo")).is_ok());
+/// assert_eq!(skipped.count(), 1);
+///
+/// let mut skipped = Skipped::new(true);
+/// assert!(skipped.skip(InputError::parse("range", "foo")).is_err());
+/// ```
+#[derive(Debug, Default)]
+pub struct Skipped {
+    is_strict: bool,
+    count: usize,
+}
+
+impl Skipped {
+    pub fn new(is_strict: bool) -> Self {
+        Self {
+            is_strict,
+            count: 0,
+        }
+    }
+
+    pub fn skip(&mut self, err: InputError) -> Result<(), InputError> {
+        if self.is_strict {
+            return Err(err);
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Write the number of skipped lines to stderr
+    pub fn report(&self) {
+        if self.count > 0 {
+            eprintln!(
+                "Skipped {} malformed line(s). Use --strict to abort on them",
+                self.count
+            );
+        }
+    }
+}
+
+/// Reasons of malformed runlists
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunlistErrorKind {
+    /// A character other than digits, `-` and `,`
+    InvalidChar(char),
+    /// A number is expected, e.g. `1-` or `1,,2`
+    MissingNumber,
+    /// The lower bound is larger than the upper one
+    Reversed(i64, i64),
+    /// Numbers beyond `NEG_INF` or `POS_INF`
+    Overflow,
+}
+
+/// A malformed runlist, with the byte position of the error
+///
+/// ```
+/// # use intspan::{RunlistError, RunlistErrorKind};
+/// let err: RunlistError = "1-3,5-x".parse::<intspan::IntSpan>().unwrap_err();
+/// assert_eq!(err.pos, 6);
+/// assert_eq!(err.kind, RunlistErrorKind::InvalidChar('x'));
+/// assert_eq!(err.to_string(), "Number format error: x at 6 of 1-3,5-x");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunlistError {
+    pub runlist: String,
+    pub pos: usize,
+    pub kind: RunlistErrorKind,
+}
+
+impl RunlistError {
+    pub fn new(runlist: &str, pos: usize, kind: RunlistErrorKind) -> Self {
+        Self {
+            runlist: runlist.to_string(),
+            pos,
+            kind,
+        }
+    }
+}
+
+impl fmt::Display for RunlistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            RunlistErrorKind::InvalidChar(ch) => write!(f, "Number format error: {}", ch)?,
+            RunlistErrorKind::MissingNumber => write!(f, "Missing number")?,
+            RunlistErrorKind::Reversed(lower, upper) => {
+                write!(f, "Bad order: {},{}", lower, upper)?
+            }
+            RunlistErrorKind::Overflow => write!(f, "Overflow past NEG_INF or POS_INF")?,
+        }
+        write!(f, " at {} of {}", self.pos, self.runlist)
+    }
+}
+
+impl std::error::Error for RunlistError {}
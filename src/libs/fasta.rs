@@ -0,0 +1,477 @@
+use crate::Range;
+use anyhow::{anyhow, bail};
+use flate2::read::{DeflateDecoder, MultiGzDecoder};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+
+/// A line of the `.fai` index, as produced by `samtools faidx`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaiRecord {
+    pub name: String,
+    pub length: u64,
+    pub offset: u64,
+    pub line_bases: u64,
+    pub line_width: u64,
+}
+
+impl FaiRecord {
+    /// Byte offset of the 0-based position `pos` in the uncompressed file
+    fn pos_offset(&self, pos: u64) -> u64 {
+        self.offset + pos / self.line_bases * self.line_width + pos % self.line_bases
+    }
+}
+
+/// Build the `.fai` index of a plain or bgzipped FASTA file
+///
+/// ```
+/// let records = intspan::build_fai("tests/fasr/NC_000932.fa").unwrap();
+/// assert_eq!(records.len(), 1);
+/// assert_eq!(records[0].name, "NC_000932");
+/// assert_eq!(records[0].length, 154478);
+/// assert_eq!(records[0].offset, 11);
+/// assert_eq!(records[0].line_bases, 50);
+/// assert_eq!(records[0].line_width, 51);
+///
+/// let records_gz = intspan::build_fai("tests/fasr/NC_000932.fa.gz").unwrap();
+/// assert_eq!(records, records_gz);
+/// ```
+pub fn build_fai(path: &str) -> anyhow::Result<Vec<FaiRecord>> {
+    let file = File::open(path).map_err(|e| anyhow!("Can't open `{}`: {}", path, e))?;
+    let mut reader: Box<dyn BufRead> = if is_gzipped(path)? {
+        Box::new(BufReader::new(MultiGzDecoder::new(file)))
+    } else {
+        Box::new(BufReader::new(file))
+    };
+
+    let mut records: Vec<FaiRecord> = vec![];
+    let mut offset: u64 = 0;
+    // A line shorter than the others is only allowed at the end of a sequence
+    let mut is_short = false;
+    let mut buf: Vec<u8> = vec![];
+
+    loop {
+        buf.clear();
+        let width = reader.read_until(b'\n', &mut buf)? as u64;
+        if width == 0 {
+            break;
+        }
+        offset += width;
+
+        let line = trim_eol(&buf);
+        if line.first() == Some(&b'>') {
+            let name = String::from_utf8_lossy(&line[1..])
+                .split_whitespace()
+                .next()
+                .unwrap_or("")
+                .to_string();
+            records.push(FaiRecord {
+                name,
+                length: 0,
+                offset,
+                line_bases: 0,
+                line_width: 0,
+            });
+            is_short = false;
+            continue;
+        }
+
+        let bases = line.len() as u64;
+        let record = match records.last_mut() {
+            Some(x) => x,
+            None if bases == 0 => continue,
+            None => bail!("`{}` doesn't start with a FASTA header", path),
+        };
+        if bases == 0 {
+            is_short = true;
+            continue;
+        }
+
+        if record.line_bases == 0 {
+            record.line_bases = bases;
+            record.line_width = width;
+        } else if is_short || bases > record.line_bases {
+            bail!("Different line lengths in sequence `{}`", record.name);
+        } else if bases < record.line_bases || width != record.line_width {
+            is_short = true;
+        }
+        record.length += bases;
+    }
+
+    Ok(records)
+}
+
+/// Read a `.fai` file
+pub fn read_fai(path: &str) -> anyhow::Result<Vec<FaiRecord>> {
+    let file = File::open(path).map_err(|e| anyhow!("Can't open `{}`: {}", path, e))?;
+
+    let mut records: Vec<FaiRecord> = vec![];
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 5 {
+            bail!("Malformed index line {} in `{}`", i + 1, path);
+        }
+        let ints = fields[1..5]
+            .iter()
+            .map(|e| e.parse::<u64>())
+            .collect::<Result<Vec<u64>, _>>()
+            .map_err(|_| anyhow!("Malformed index line {} in `{}`", i + 1, path))?;
+
+        records.push(FaiRecord {
+            name: fields[0].to_string(),
+            length: ints[0],
+            offset: ints[1],
+            line_bases: ints[2],
+            line_width: ints[3],
+        });
+    }
+
+    Ok(records)
+}
+
+/// Write a `.fai` file
+pub fn write_fai(path: &str, records: &[FaiRecord]) -> anyhow::Result<()> {
+    let mut writer = std::io::BufWriter::new(File::create(path)?);
+    for rec in records {
+        writer.write_fmt(format_args!(
+            "{}\t{}\t{}\t{}\t{}\n",
+            rec.name, rec.length, rec.offset, rec.line_bases, rec.line_width
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// Reverse complement of DNA sequences, IUPAC codes and the case are kept
+///
+/// ```
+/// assert_eq!(intspan::rev_comp("ATGGGCGAAC"), "GTTCGCCCAT");
+/// assert_eq!(intspan::rev_comp("acgtNRY-"), "-RYNacgt");
+/// ```
+pub fn rev_comp(seq: &str) -> String {
+    seq.chars()
+        .rev()
+        .map(|c| match c {
+            'A' => 'T',
+            'C' => 'G',
+            'G' => 'C',
+            'T' | 'U' => 'A',
+            'R' => 'Y',
+            'Y' => 'R',
+            'K' => 'M',
+            'M' => 'K',
+            'B' => 'V',
+            'V' => 'B',
+            'D' => 'H',
+            'H' => 'D',
+            'a' => 't',
+            'c' => 'g',
+            'g' => 'c',
+            't' | 'u' => 'a',
+            'r' => 'y',
+            'y' => 'r',
+            'k' => 'm',
+            'm' => 'k',
+            'b' => 'v',
+            'v' => 'b',
+            'd' => 'h',
+            'h' => 'd',
+            _ => c,
+        })
+        .collect()
+}
+
+enum Source {
+    Plain(File),
+    // (compressed offset, uncompressed offset) of each BGZF block
+    Bgzf(File, Vec<(u64, u64)>),
+}
+
+/// Random access to plain or bgzipped FASTA files via the `.fai` index.
+///
+/// The index is read from `<file>.fai`.
+/// If it doesn't exist, the index is built in memory and no file is written; see [`write_fai`].
+///
+/// ```
+/// # use intspan::{FaidxReader, Range};
+/// let mut reader = FaidxReader::from_path("tests/fasr/NC_000932.fa").unwrap();
+/// assert_eq!(reader.fetch("NC_000932", 1, 10).unwrap(), "ATGGGCGAAC");
+/// assert_eq!(reader.fetch("NC_000932", 49, 52).unwrap(), "CTGC");
+///
+/// let range = Range::from_str_lenient("NC_000932(-):1-10");
+/// assert_eq!(reader.fetch_range(&range).unwrap(), "GTTCGCCCAT");
+///
+/// let mut reader = FaidxReader::from_path("tests/fasr/NC_000932.fa.gz").unwrap();
+/// assert_eq!(reader.fetch("NC_000932", 49, 52).unwrap(), "CTGC");
+/// assert_eq!(reader.fetch("NC_000932", 154471, 154478).unwrap(), "CGGGCATC");
+///
+/// let res = reader.fetch("FAKE", 1, 10);
+/// assert_eq!(format!("{}", res.unwrap_err()), "Sequence not found: `FAKE`");
+/// ```
+pub struct FaidxReader {
+    source: Source,
+    records: Vec<FaiRecord>,
+    idx_of: HashMap<String, usize>,
+}
+
+impl FaidxReader {
+    pub fn from_path(path: &str) -> anyhow::Result<Self> {
+        let fai = format!("{}.fai", path);
+        let records = if std::path::Path::new(&fai).is_file() {
+            read_fai(&fai)?
+        } else {
+            build_fai(path)?
+        };
+
+        let idx_of = records
+            .iter()
+            .enumerate()
+            .map(|(i, rec)| (rec.name.clone(), i))
+            .collect();
+
+        let file = File::open(path).map_err(|e| anyhow!("Can't open `{}`: {}", path, e))?;
+        let source = if is_gzipped(path)? {
+            let blocks = scan_bgzf(path)?;
+            Source::Bgzf(file, blocks)
+        } else {
+            Source::Plain(file)
+        };
+
+        Ok(Self {
+            source,
+            records,
+            idx_of,
+        })
+    }
+
+    pub fn records(&self) -> &[FaiRecord] {
+        &self.records
+    }
+
+    /// The length of the sequence `chr`, or `None` if it isn't in the file
+    ///
+    /// ```
+    /// # use intspan::FaidxReader;
+    /// let reader = FaidxReader::from_path("tests/fasr/NC_000932.fa").unwrap();
+    /// assert_eq!(reader.length("NC_000932"), Some(154478));
+    /// assert_eq!(reader.length("FAKE"), None);
+    /// ```
+    pub fn length(&self, chr: &str) -> Option<u64> {
+        self.idx_of.get(chr).map(|i| self.records[*i].length)
+    }
+
+    /// Fetch the sequence of `chr` from `start` to `end`, both 1-based and inclusive.
+    /// `end` is clipped to the length of the sequence.
+    pub fn fetch(&mut self, chr: &str, start: i64, end: i64) -> anyhow::Result<String> {
+        let record = match self.idx_of.get(chr) {
+            Some(i) => self.records[*i].clone(),
+            None => bail!("Sequence not found: `{}`", chr),
+        };
+
+        let end = end.min(record.length as i64);
+        if start < 1 || start > end {
+            bail!("Invalid range: `{}:{}-{}`", chr, start, end);
+        }
+
+        let begin = record.pos_offset(start as u64 - 1);
+        let finish = record.pos_offset(end as u64 - 1) + 1;
+        let bytes = self.read_at(begin, finish - begin)?;
+
+        let seq: Vec<u8> = bytes
+            .into_iter()
+            .filter(|e| *e != b'\n' && *e != b'\r')
+            .collect();
+        Ok(String::from_utf8(seq)?)
+    }
+
+    /// Fetch the sequence of a Range. Ranges on the negative strand are reverse complemented.
+    /// The whole sequence is returned if the Range has no positions.
+    pub fn fetch_range(&mut self, range: &Range) -> anyhow::Result<String> {
+        let (start, end) = if range.is_valid() {
+            (*range.start(), *range.end())
+        } else {
+            (1, i64::MAX)
+        };
+
+        let seq = self.fetch(range.chr(), start, end)?;
+        if range.strand() == "-" {
+            Ok(rev_comp(&seq))
+        } else {
+            Ok(seq)
+        }
+    }
+
+    fn read_at(&mut self, offset: u64, len: u64) -> anyhow::Result<Vec<u8>> {
+        let mut buf: Vec<u8> = vec![];
+
+        match &mut self.source {
+            Source::Plain(file) => {
+                file.seek(SeekFrom::Start(offset))?;
+                file.take(len).read_to_end(&mut buf)?;
+            }
+            Source::Bgzf(file, blocks) => {
+                let mut i = blocks.partition_point(|e| e.1 <= offset).saturating_sub(1);
+                let mut skip = (offset - blocks[i].1) as usize;
+                while (buf.len() as u64) < len && i < blocks.len() {
+                    let block = read_bgzf_block(file, blocks[i].0)?;
+                    buf.extend_from_slice(&block[skip.min(block.len())..]);
+                    skip = 0;
+                    i += 1;
+                }
+                buf.truncate(len as usize);
+            }
+        }
+
+        Ok(buf)
+    }
+}
+
+fn trim_eol(line: &[u8]) -> &[u8] {
+    let mut end = line.len();
+    while end > 0 && (line[end - 1] == b'\n' || line[end - 1] == b'\r') {
+        end -= 1;
+    }
+    &line[..end]
+}
+
+// Gzipped files should be bgzipped to be seekable
+fn is_gzipped(path: &str) -> anyhow::Result<bool> {
+    let mut file = File::open(path).map_err(|e| anyhow!("Can't open `{}`: {}", path, e))?;
+    let mut magic = [0u8; 4];
+    let n = file.read(&mut magic)?;
+    if n < 4 || magic[0] != 0x1f || magic[1] != 0x8b {
+        return Ok(false);
+    }
+
+    file.seek(SeekFrom::Start(0))?;
+    if bgzf_block_size(&mut file)?.is_none() {
+        bail!("`{}` is gzipped but not bgzipped", path);
+    }
+
+    Ok(true)
+}
+
+// Returns the total size of the BGZF block at the current position, None at EOF
+fn bgzf_block_size(file: &mut File) -> anyhow::Result<Option<u64>> {
+    let mut header = [0u8; 12];
+    match file.read_exact(&mut header) {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    // FEXTRA should be set
+    if header[0] != 0x1f || header[1] != 0x8b || header[3] & 4 == 0 {
+        return Ok(None);
+    }
+
+    let xlen = u16::from_le_bytes([header[10], header[11]]) as usize;
+    let mut extra = vec![0u8; xlen];
+    file.read_exact(&mut extra)?;
+
+    // Look for the `BC` subfield
+    let mut i = 0;
+    while i + 4 <= xlen {
+        let slen = u16::from_le_bytes([extra[i + 2], extra[i + 3]]) as usize;
+        if extra[i] == b'B' && extra[i + 1] == b'C' && slen == 2 && i + 6 <= xlen {
+            let bsize = u16::from_le_bytes([extra[i + 4], extra[i + 5]]) as u64;
+            return Ok(Some(bsize + 1));
+        }
+        i += 4 + slen;
+    }
+
+    Ok(None)
+}
+
+fn scan_bgzf(path: &str) -> anyhow::Result<Vec<(u64, u64)>> {
+    let mut file = File::open(path)?;
+    let mut blocks: Vec<(u64, u64)> = vec![];
+    let mut coffset: u64 = 0;
+    let mut uoffset: u64 = 0;
+
+    loop {
+        file.seek(SeekFrom::Start(coffset))?;
+        let size = match bgzf_block_size(&mut file)? {
+            Some(x) => x,
+            None => break,
+        };
+
+        // ISIZE is the last 4 bytes of a block
+        file.seek(SeekFrom::Start(coffset + size - 4))?;
+        let mut isize = [0u8; 4];
+        file.read_exact(&mut isize)?;
+
+        blocks.push((coffset, uoffset));
+        coffset += size;
+        uoffset += u32::from_le_bytes(isize) as u64;
+    }
+
+    if blocks.is_empty() {
+        bail!("`{}` is gzipped but not bgzipped", path);
+    }
+
+    Ok(blocks)
+}
+
+fn read_bgzf_block(file: &mut File, coffset: u64) -> anyhow::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(coffset))?;
+    let size = bgzf_block_size(file)?.ok_or_else(|| anyhow!("Broken BGZF block"))?;
+    let header_len = file.stream_position()? - coffset;
+
+    let mut cdata = vec![0u8; (size - header_len - 8) as usize];
+    file.read_exact(&mut cdata)?;
+
+    let mut block: Vec<u8> = vec![];
+    DeflateDecoder::new(&cdata[..]).read_to_end(&mut block)?;
+
+    Ok(block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_fai() {
+        let records = read_fai("tests/fasr/NC_000932.fa.fai").unwrap();
+        let built = build_fai("tests/fasr/NC_000932.fa").unwrap();
+        assert_eq!(records, built);
+    }
+
+    #[test]
+    fn test_fetch_bgzf() {
+        let mut plain = FaidxReader::from_path("tests/fasr/NC_000932.fa").unwrap();
+        let mut bgzf = FaidxReader::from_path("tests/fasr/NC_000932.fa.gz").unwrap();
+
+        // Ranges spanning the boundaries of BGZF blocks
+        for (start, end) in [(1, 100), (19500, 20500), (39000, 61000), (154400, 200000)] {
+            assert_eq!(
+                plain.fetch("NC_000932", start, end).unwrap(),
+                bgzf.fetch("NC_000932", start, end).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_build_fai_lines() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let filename = tempdir.path().join("test.fa").to_string_lossy().to_string();
+
+        std::fs::write(&filename, ">a desc\r\nACGT\r\nAC\r\n>b\nACG\nACG\nA").unwrap();
+        let records = build_fai(&filename).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].name, "a");
+        assert_eq!(records[0].length, 6);
+        assert_eq!(records[0].line_width, 6);
+        assert_eq!(records[1].offset, 22);
+        assert_eq!(records[1].length, 7);
+
+        let mut reader = FaidxReader::from_path(&filename).unwrap();
+        assert_eq!(reader.fetch("a", 3, 100).unwrap(), "GTAC");
+        assert_eq!(reader.fetch("b", 2, 7).unwrap(), "CGACGA");
+        assert!(reader.fetch("b", 8, 9).is_err());
+
+        std::fs::write(&filename, ">a\nACGT\nAC\nACGT\n").unwrap();
+        assert!(build_fai(&filename).is_err());
+    }
+}
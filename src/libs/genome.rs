@@ -0,0 +1,618 @@
+use crate::{
+    is_rlb, parse_rg, read_json, read_lines, read_rlb, write_json, write_lines, IntSpan, Range,
+};
+use serde_json::Value;
+use std::collections::{btree_map, BTreeMap, BTreeSet};
+use std::iter::FromIterator;
+
+/// The binary operation of IntSpans by its name, `intersect`, `diff`, `union` or `xor`
+///
+/// ```
+/// # use intspan::IntSpan;
+/// let f = intspan::intspan_op("diff").unwrap();
+/// assert_eq!(f(&IntSpan::from("1-10"), &IntSpan::from("5-20")).to_string(), "1-4");
+/// assert!(intspan::intspan_op("foo").is_err());
+/// ```
+pub fn intspan_op(op: &str) -> anyhow::Result<fn(&IntSpan, &IntSpan) -> IntSpan> {
+    match op {
+        "intersect" => Ok(IntSpan::intersect),
+        "diff" => Ok(IntSpan::diff),
+        "union" => Ok(IntSpan::union),
+        "xor" => Ok(IntSpan::xor),
+        _ => Err(anyhow::anyhow!("Invalid IntSpan Op: {}", op)),
+    }
+}
+
+/// IntSpans of a genome, chr => IntSpan.
+///
+/// Chromosomes missing from one side of a binary operation are treated as empty sets,
+/// and results contain chromosomes of both sides.
+///
+/// ```
+/// # use intspan::{GenomeSet, Range};
+/// let set: GenomeSet = vec![
+///     Range::from_str_lenient("I:1-100"),
+///     Range::from_str_lenient("I(+):90-150"),
+///     Range::from_str_lenient("II:21294-22075"),
+/// ]
+/// .into_iter()
+/// .collect();
+///
+/// assert_eq!(set.len(), 2);
+/// assert_eq!(set["I"].to_string(), "1-150");
+/// assert_eq!(set.cardinality(), 150 + 782);
+///
+/// let other = GenomeSet::from_json(
+///     &serde_json::from_str(r#"{"I": "101-200", "III": "1-10"}"#).unwrap(),
+/// )
+/// .unwrap();
+/// assert_eq!(set.intersect(&other)["I"].to_string(), "101-150");
+/// assert_eq!(set.union(&other).chrs().count(), 3);
+/// assert_eq!(set.diff(&other)["I"].to_string(), "1-100");
+/// assert!(set.diff(&other)["III"].is_empty());
+///
+/// let rgs: Vec<String> = set.ranges().map(|rg| rg.to_string()).collect();
+/// assert_eq!(rgs, vec!["I:1-150", "II:21294-22075"]);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct GenomeSet {
+    sets: BTreeMap<String, IntSpan>,
+}
+
+impl GenomeSet {
+    pub fn new() -> Self {
+        Self {
+            sets: BTreeMap::new(),
+        }
+    }
+
+    /// The whole genome, `1-size` of each chromosome
+    ///
+    /// ```
+    /// # use intspan::GenomeSet;
+    /// let sizes = intspan::read_sizes("tests/spanr/S288c.chr.sizes").unwrap();
+    /// let genome = GenomeSet::from_sizes(&sizes);
+    /// assert_eq!(genome["II"].to_string(), "1-813184");
+    /// ```
+    pub fn from_sizes(sizes: &BTreeMap<String, i64>) -> Self {
+        sizes
+            .iter()
+            .map(|(chr, size)| (chr.to_string(), IntSpan::from_pair(1, *size)))
+            .collect()
+    }
+
+    /// From a single-level runlist JSON, see [`json2set`](crate::json2set)
+    pub fn from_json(json: &BTreeMap<String, Value>) -> anyhow::Result<Self> {
+        Ok(Self {
+            sets: crate::json2set(json)?,
+        })
+    }
+
+    pub fn to_json(&self) -> BTreeMap<String, Value> {
+        crate::set2json(&self.sets)
+    }
+
+    /// Read a single-level runlist file, `.json`, `.yml` or `.rlb`
+    pub fn read(input: &str) -> anyhow::Result<Self> {
+        if is_rlb(input) {
+            let sets = read_rlb(input)?;
+            if !sets.is_single() {
+                let name = sets.names().next().unwrap();
+                return Err(anyhow::anyhow!(
+                    "{}: {}: runlists should be strings",
+                    input,
+                    name
+                ));
+            }
+            return Ok(Self::from(
+                sets.into_map().remove(GenomeSets::SINGLE).unwrap(),
+            ));
+        }
+
+        Self::from_json(&read_json(input)?)
+    }
+
+    pub fn write(&self, output: &str) -> anyhow::Result<()> {
+        write_json(output, &self.to_json())
+    }
+
+    /// Read ranges of a BED file
+    ///
+    /// ```
+    /// # use intspan::GenomeSet;
+    /// let set = GenomeSet::read_bed("tests/spanr/S288c.bed").unwrap();
+    /// assert_eq!(set["I"].to_string(), "1-150,190-200");
+    /// ```
+    pub fn read_bed(input: &str) -> anyhow::Result<Self> {
+        let mut set = Self::new();
+        for (i, line) in read_lines(input)?.iter().enumerate() {
+            if crate::is_comment(line, true) {
+                continue;
+            }
+            let range = parse_rg(line, true).map_err(|e| e.at(input, i + 1, None))?;
+            set.add_range(&range);
+        }
+
+        Ok(set)
+    }
+
+    /// Write spans as BED lines, without strands
+    pub fn write_bed(&self, output: &str) -> anyhow::Result<()> {
+        let lines: Vec<String> = self.ranges().map(|range| range.to_bed()).collect();
+        write_lines(output, &lines)
+    }
+
+    pub fn get(&self, chr: &str) -> Option<&IntSpan> {
+        self.sets.get(chr)
+    }
+
+    /// The IntSpan of a chromosome, inserted as an empty one if missing
+    pub fn entry(&mut self, chr: &str) -> &mut IntSpan {
+        self.sets.entry(chr.to_string()).or_default()
+    }
+
+    pub fn insert(&mut self, chr: &str, ints: IntSpan) {
+        self.sets.insert(chr.to_string(), ints);
+    }
+
+    pub fn add_range(&mut self, range: &Range) {
+        self.entry(range.chr())
+            .add_pair(*range.start(), *range.end());
+    }
+
+    /// Give empty IntSpans to missing chromosomes
+    pub fn fill_up(&mut self, chrs: &BTreeSet<String>) {
+        for chr in chrs {
+            self.entry(chr);
+        }
+    }
+
+    pub fn chrs(&self) -> btree_map::Keys<'_, String, IntSpan> {
+        self.sets.keys()
+    }
+
+    pub fn iter(&self) -> btree_map::Iter<'_, String, IntSpan> {
+        self.sets.iter()
+    }
+
+    /// Number of chromosomes
+    pub fn len(&self) -> usize {
+        self.sets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sets.is_empty()
+    }
+
+    /// Total number of elements of all chromosomes
+    pub fn cardinality(&self) -> i64 {
+        self.sets.values().map(|ints| ints.cardinality()).sum()
+    }
+
+    /// Spans of all chromosomes as Ranges
+    pub fn ranges(&self) -> impl Iterator<Item = Range> + '_ {
+        self.sets.iter().flat_map(|(chr, ints)| {
+            ints.iter_spans()
+                .map(move |(lower, upper)| Range::from(chr, lower, upper))
+        })
+    }
+
+    pub fn as_map(&self) -> &BTreeMap<String, IntSpan> {
+        &self.sets
+    }
+
+    pub fn into_map(self) -> BTreeMap<String, IntSpan> {
+        self.sets
+    }
+
+    /// Apply `f` to the IntSpan of each chromosome
+    ///
+    /// ```
+    /// # use intspan::GenomeSet;
+    /// let set = GenomeSet::from_json(&serde_json::from_str(r#"{"I": "1-10,21-30"}"#).unwrap()).unwrap();
+    /// assert_eq!(set.map(|ints| ints.holes())["I"].to_string(), "11-20");
+    /// ```
+    pub fn map<F>(&self, f: F) -> Self
+    where
+        F: Fn(&IntSpan) -> IntSpan,
+    {
+        self.sets
+            .iter()
+            .map(|(chr, ints)| (chr.to_string(), f(ints)))
+            .collect()
+    }
+
+    /// Apply `f` to IntSpans of the same chromosome, on chromosomes of both sets
+    pub fn zip_with<F>(&self, other: &Self, f: F) -> Self
+    where
+        F: Fn(&IntSpan, &IntSpan) -> IntSpan,
+    {
+        let empty = IntSpan::new();
+        let chrs: BTreeSet<&String> = self.chrs().chain(other.chrs()).collect();
+
+        chrs.into_iter()
+            .map(|chr| {
+                let a = self.get(chr).unwrap_or(&empty);
+                let b = other.get(chr).unwrap_or(&empty);
+                (chr.to_string(), f(a, b))
+            })
+            .collect()
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a.union(b))
+    }
+
+    pub fn intersect(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a.intersect(b))
+    }
+
+    pub fn diff(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a.diff(b))
+    }
+
+    pub fn xor(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a.xor(b))
+    }
+
+    /// By the name of an operation, `intersect`, `diff`, `union` or `xor`
+    ///
+    /// ```
+    /// # use intspan::GenomeSet;
+    /// let a = GenomeSet::from_json(&serde_json::from_str(r#"{"I": "1-10"}"#).unwrap()).unwrap();
+    /// let b = GenomeSet::from_json(&serde_json::from_str(r#"{"I": "5-20"}"#).unwrap()).unwrap();
+    /// assert_eq!(a.op("xor", &b).unwrap()["I"].to_string(), "1-4,11-20");
+    /// assert!(a.op("foo", &b).is_err());
+    /// ```
+    pub fn op(&self, op: &str, other: &Self) -> anyhow::Result<Self> {
+        Ok(self.zip_with(other, intspan_op(op)?))
+    }
+
+    /// Spans in any of the sets, on chromosomes of all sets
+    pub fn union_all<'a, I>(sets: I) -> Self
+    where
+        I: IntoIterator<Item = &'a GenomeSet>,
+    {
+        Self::at_least(sets, 1)
+    }
+
+    /// Spans in all of the sets, on chromosomes of all sets
+    pub fn intersect_all<'a, I>(sets: I) -> Self
+    where
+        I: IntoIterator<Item = &'a GenomeSet>,
+    {
+        let sets: Vec<&GenomeSet> = sets.into_iter().collect();
+        if sets.is_empty() {
+            return Self::new();
+        }
+        let n = sets.len();
+        Self::at_least(sets, n)
+    }
+
+    /// Spans in at least `k` of the sets, on chromosomes of all sets.
+    /// See [`IntSpan::at_least`]
+    ///
+    /// ```
+    /// # use intspan::GenomeSet;
+    /// let sets: Vec<GenomeSet> = vec![
+    ///     r#"{"I": "1-100", "II": "1-10"}"#,
+    ///     r#"{"I": "51-150"}"#,
+    ///     r#"{"I": "91-200", "II": "5-20"}"#,
+    /// ]
+    /// .into_iter()
+    /// .map(|s| GenomeSet::from_json(&serde_json::from_str(s).unwrap()).unwrap())
+    /// .collect();
+    ///
+    /// let res = GenomeSet::at_least(&sets, 2);
+    /// assert_eq!(res["I"].to_string(), "51-150");
+    /// assert_eq!(res["II"].to_string(), "5-10");
+    ///
+    /// let res = GenomeSet::intersect_all(&sets);
+    /// assert_eq!(res["I"].to_string(), "91-100");
+    /// assert!(res["II"].is_empty());
+    /// ```
+    pub fn at_least<'a, I>(sets: I, k: usize) -> Self
+    where
+        I: IntoIterator<Item = &'a GenomeSet>,
+    {
+        let sets: Vec<&GenomeSet> = sets.into_iter().collect();
+        let chrs: BTreeSet<&String> = sets.iter().flat_map(|set| set.chrs()).collect();
+
+        chrs.into_iter()
+            .map(|chr| {
+                let ints = IntSpan::at_least(sets.iter().filter_map(|set| set.get(chr)), k);
+                (chr.to_string(), ints)
+            })
+            .collect()
+    }
+
+    /// The complement against chr.sizes, on chromosomes of `sizes` only
+    ///
+    /// ```
+    /// # use intspan::GenomeSet;
+    /// let sizes = intspan::read_sizes("tests/spanr/S288c.chr.sizes").unwrap();
+    /// let set = GenomeSet::from_json(
+    ///     &serde_json::from_str(r#"{"I": "1-100", "foo": "1-10"}"#).unwrap(),
+    /// )
+    /// .unwrap();
+    /// let comp = set.complement(&sizes);
+    /// assert_eq!(comp.len(), 16);
+    /// assert_eq!(comp["I"].to_string(), "101-230218");
+    /// assert!(comp.get("foo").is_none());
+    /// ```
+    pub fn complement(&self, sizes: &BTreeMap<String, i64>) -> Self {
+        sizes
+            .iter()
+            .map(|(chr, size)| {
+                let mut ints = IntSpan::from_pair(1, *size);
+                if let Some(set) = self.get(chr) {
+                    ints.subtract(set);
+                }
+                (chr.to_string(), ints)
+            })
+            .collect()
+    }
+}
+
+impl From<BTreeMap<String, IntSpan>> for GenomeSet {
+    fn from(sets: BTreeMap<String, IntSpan>) -> Self {
+        Self { sets }
+    }
+}
+
+impl FromIterator<(String, IntSpan)> for GenomeSet {
+    fn from_iter<I: IntoIterator<Item = (String, IntSpan)>>(iter: I) -> Self {
+        Self {
+            sets: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl FromIterator<Range> for GenomeSet {
+    fn from_iter<I: IntoIterator<Item = Range>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for range in iter {
+            set.add_range(&range);
+        }
+        set
+    }
+}
+
+impl<'a> IntoIterator for &'a GenomeSet {
+    type Item = (&'a String, &'a IntSpan);
+    type IntoIter = btree_map::Iter<'a, String, IntSpan>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl std::ops::Index<&str> for GenomeSet {
+    type Output = IntSpan;
+
+    fn index(&self, chr: &str) -> &IntSpan {
+        self.sets
+            .get(chr)
+            .unwrap_or_else(|| panic!("Chromosome not found: {}", chr))
+    }
+}
+
+/// Named GenomeSets, name => chr => IntSpan.
+///
+/// Single-level runlists are stored with the name `__single`.
+///
+/// ```
+/// # use intspan::GenomeSets;
+/// let sets = GenomeSets::read("tests/spanr/Atha.json").unwrap();
+/// assert!(!sets.is_single());
+/// assert_eq!(sets.names().count(), 5);
+/// assert_eq!(sets.chrs().len(), 2);
+///
+/// let sets = GenomeSets::read("tests/spanr/brca2.json").unwrap();
+/// assert!(sets.is_single());
+/// assert_eq!(sets.to_json(), intspan::read_json("tests/spanr/brca2.json").unwrap());
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct GenomeSets {
+    set_of: BTreeMap<String, GenomeSet>,
+}
+
+impl GenomeSets {
+    pub const SINGLE: &'static str = "__single";
+
+    pub fn new() -> Self {
+        Self {
+            set_of: BTreeMap::new(),
+        }
+    }
+
+    /// A single GenomeSet
+    pub fn single(set: GenomeSet) -> Self {
+        let mut sets = Self::new();
+        sets.insert(Self::SINGLE, set);
+        sets
+    }
+
+    /// From a single or multi-level runlist JSON
+    pub fn from_json(json: &BTreeMap<String, Value>) -> anyhow::Result<Self> {
+        if json.is_empty() {
+            return Ok(Self::single(GenomeSet::new()));
+        }
+
+        let s_of = crate::json2set_m(json)?;
+        Ok(Self {
+            set_of: s_of
+                .into_iter()
+                .map(|(name, set)| (name, GenomeSet::from(set)))
+                .collect(),
+        })
+    }
+
+    /// Single-level runlists for a single GenomeSet, otherwise multi-level ones
+    pub fn to_json(&self) -> BTreeMap<String, Value> {
+        if self.is_single() {
+            return self.set_of[Self::SINGLE].to_json();
+        }
+
+        self.set_of
+            .iter()
+            .map(|(name, set)| {
+                (
+                    name.to_string(),
+                    serde_json::to_value(set.to_json()).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    /// Read a single or multi-level runlist file, `.json`, `.yml` or `.rlb`
+    pub fn read(input: &str) -> anyhow::Result<Self> {
+        if is_rlb(input) {
+            return read_rlb(input);
+        }
+
+        Self::from_json(&read_json(input)?)
+    }
+
+    pub fn write(&self, output: &str) -> anyhow::Result<()> {
+        write_json(output, &self.to_json())
+    }
+
+    pub fn is_single(&self) -> bool {
+        self.set_of.len() == 1 && self.set_of.contains_key(Self::SINGLE)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&GenomeSet> {
+        self.set_of.get(name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut GenomeSet> {
+        self.set_of.get_mut(name)
+    }
+
+    /// The GenomeSet of a name, inserted as an empty one if missing
+    pub fn entry(&mut self, name: &str) -> &mut GenomeSet {
+        self.set_of.entry(name.to_string()).or_default()
+    }
+
+    pub fn insert(&mut self, name: &str, set: GenomeSet) {
+        self.set_of.insert(name.to_string(), set);
+    }
+
+    pub fn names(&self) -> btree_map::Keys<'_, String, GenomeSet> {
+        self.set_of.keys()
+    }
+
+    pub fn iter(&self) -> btree_map::Iter<'_, String, GenomeSet> {
+        self.set_of.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.set_of.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.set_of.is_empty()
+    }
+
+    /// name => chr => IntSpan, e.g. for [`set2json_stranded`](crate::set2json_stranded)
+    pub fn into_map(self) -> BTreeMap<String, BTreeMap<String, IntSpan>> {
+        self.set_of
+            .into_iter()
+            .map(|(name, set)| (name, set.into_map()))
+            .collect()
+    }
+
+    /// Chromosomes of all GenomeSets
+    pub fn chrs(&self) -> BTreeSet<String> {
+        self.set_of
+            .values()
+            .flat_map(|set| set.chrs().cloned())
+            .collect()
+    }
+
+    /// Give empty IntSpans to missing chromosomes of each GenomeSet
+    pub fn fill_up(&mut self, chrs: &BTreeSet<String>) {
+        for set in self.set_of.values_mut() {
+            set.fill_up(chrs);
+        }
+    }
+}
+
+impl From<BTreeMap<String, BTreeMap<String, IntSpan>>> for GenomeSets {
+    fn from(s_of: BTreeMap<String, BTreeMap<String, IntSpan>>) -> Self {
+        Self {
+            set_of: s_of
+                .into_iter()
+                .map(|(name, set)| (name, GenomeSet::from(set)))
+                .collect(),
+        }
+    }
+}
+
+impl FromIterator<(String, GenomeSet)> for GenomeSets {
+    fn from_iter<I: IntoIterator<Item = (String, GenomeSet)>>(iter: I) -> Self {
+        Self {
+            set_of: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a GenomeSets {
+    type Item = (&'a String, &'a GenomeSet);
+    type IntoIter = btree_map::Iter<'a, String, GenomeSet>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl std::ops::Index<&str> for GenomeSets {
+    type Output = GenomeSet;
+
+    fn index(&self, name: &str) -> &GenomeSet {
+        self.set_of
+            .get(name)
+            .unwrap_or_else(|| panic!("Name not found: {}", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_and_serde() {
+        let sets = GenomeSets::from_json(&BTreeMap::new()).unwrap();
+        assert!(sets.is_single());
+        assert!(sets[GenomeSets::SINGLE].is_empty());
+
+        let sets = GenomeSets::read("tests/spanr/Atha.json").unwrap();
+        let s = serde_json::to_string(&sets).unwrap();
+        let back: GenomeSets = serde_json::from_str(&s).unwrap();
+        assert_eq!(back, sets);
+        assert_eq!(back.to_json(), read_json("tests/spanr/Atha.json").unwrap());
+    }
+
+    #[test]
+    fn missing_chrs() {
+        let a: GenomeSet = vec![
+            Range::from_str_lenient("I:1-100"),
+            Range::from_str_lenient("II:1-100"),
+        ]
+        .into_iter()
+        .collect();
+        let b: GenomeSet = vec![Range::from_str_lenient("I:51-150")]
+            .into_iter()
+            .collect();
+
+        assert_eq!(a.intersect(&b).cardinality(), 50);
+        assert_eq!(a.union(&b).cardinality(), 250);
+        assert_eq!(a.diff(&b).cardinality(), 150);
+        assert_eq!(b.diff(&a).cardinality(), 50);
+        assert_eq!(a.xor(&b).cardinality(), 200);
+        assert_eq!(a.intersect(&b).len(), 2);
+    }
+}
@@ -0,0 +1,503 @@
+use crate::{InputError, IntSpan, Range};
+use indexmap::IndexMap;
+use std::collections::HashMap;
+
+/// A line of GFF3 or GTF
+///
+/// The format of attributes is detected by the first attribute:
+/// `key=value` for GFF3 and `key "value"` for GTF.
+///
+/// ```
+/// # use intspan::GffRecord;
+/// let line = "I\tSGD\tCDS\t1101\t1200\t.\t+\t0\tID=cds1;Parent=t1,t2;Note=a%3Bb";
+/// let record = GffRecord::from_line(line).unwrap().unwrap();
+/// assert_eq!(record.seqid, "I");
+/// assert_eq!(record.feature, "CDS");
+/// assert_eq!((record.start, record.end), (1101, 1200));
+/// assert_eq!(record.id().unwrap(), "cds1");
+/// assert_eq!(record.parents(), vec!["t1", "t2"]);
+/// assert_eq!(record.attr("Note").unwrap(), "a;b");
+/// assert_eq!(record.range().to_string(), "I(+):1101-1200");
+///
+/// let line = "I\tSGD\tgene\t1001\t1200\t.\t+\t.\tID=g1;Name=caf%C3%A9;Note=5%xé";
+/// let record = GffRecord::from_line(line).unwrap().unwrap();
+/// assert_eq!(record.attr("Name").unwrap(), "café");
+/// assert_eq!(record.attr("Note").unwrap(), "5%xé");
+///
+/// let line = "I\tSGD\texon\t1001\t1200\t.\t-\t.\tgene_id \"g1\"; transcript_id \"t1\";";
+/// let record = GffRecord::from_line(line).unwrap().unwrap();
+/// assert!(record.is_gtf);
+/// assert_eq!(record.attr("gene_id").unwrap(), "g1");
+/// assert!(record.id().is_none());
+/// assert_eq!(record.parents(), vec!["t1"]);
+///
+/// assert!(GffRecord::from_line("##gff-version 3").unwrap().is_none());
+/// let err = GffRecord::from_line("I\tSGD\texon\tstart\t1200\t.\t-\t.\t").unwrap_err();
+/// assert_eq!(err.to_string(), "invalid start: `start`");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct GffRecord {
+    pub seqid: String,
+    pub source: String,
+    pub feature: String,
+    pub start: i64,
+    pub end: i64,
+    pub score: String,
+    pub strand: String,
+    pub phase: String,
+    pub attributes: Vec<(String, String)>,
+    pub is_gtf: bool,
+}
+
+impl GffRecord {
+    /// Parse a line. Comments, directives and empty lines return `Ok(None)`.
+    pub fn from_line(line: &str) -> Result<Option<Self>, InputError> {
+        if line.starts_with('#') || line.trim().is_empty() {
+            return Ok(None);
+        }
+
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 8 {
+            return Err(InputError::parse("GFF line", line));
+        }
+        let start = fields[3]
+            .parse::<i64>()
+            .map_err(|_| InputError::parse("start", fields[3]))?;
+        let end = fields[4]
+            .parse::<i64>()
+            .map_err(|_| InputError::parse("end", fields[4]))?;
+        if start < 1 || start > end {
+            return Err(InputError::parse(
+                "coordinates",
+                &format!("{}-{}", fields[3], fields[4]),
+            ));
+        }
+
+        let column = fields.get(8).copied().unwrap_or("").trim();
+        let first = column.split(';').next().unwrap_or("");
+        let is_gtf = !first.is_empty() && !first.contains('=');
+        let attributes = if is_gtf {
+            parse_gtf_attributes(column)
+        } else {
+            parse_gff_attributes(column)
+        };
+
+        Ok(Some(Self {
+            seqid: fields[0].to_string(),
+            source: fields[1].to_string(),
+            feature: fields[2].to_string(),
+            start,
+            end,
+            score: fields[5].to_string(),
+            strand: fields[6].to_string(),
+            phase: fields[7].to_string(),
+            attributes,
+            is_gtf,
+        }))
+    }
+
+    /// The first value of an attribute
+    pub fn attr(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// `ID` of GFF3. For GTF, `transcript_id` of transcripts and `gene_id` of genes
+    pub fn id(&self) -> Option<&str> {
+        if self.is_gtf {
+            match self.feature.as_str() {
+                "gene" => self.attr("gene_id"),
+                "transcript" => self.attr("transcript_id"),
+                _ => None,
+            }
+        } else {
+            self.attr("ID")
+        }
+    }
+
+    /// `Parent` of GFF3. For GTF, `gene_id` of transcripts and `transcript_id` of others
+    pub fn parents(&self) -> Vec<&str> {
+        if self.is_gtf {
+            let parent = match self.feature.as_str() {
+                "gene" => None,
+                "transcript" => self.attr("gene_id"),
+                _ => self.attr("transcript_id").or_else(|| self.attr("gene_id")),
+            };
+            parent.into_iter().collect()
+        } else {
+            match self.attr("Parent") {
+                Some(v) => v.split(',').collect(),
+                None => vec![],
+            }
+        }
+    }
+
+    /// The strand of `Range` is kept only if it's `+` or `-`
+    pub fn range(&self) -> Range {
+        let mut range = Range::from(&self.seqid, self.start, self.end);
+        if self.strand == "+" || self.strand == "-" {
+            *range.strand_mut() = self.strand.clone();
+        }
+        range
+    }
+}
+
+// key=value;key=value1,value2
+fn parse_gff_attributes(column: &str) -> Vec<(String, String)> {
+    column
+        .split(';')
+        .filter_map(|pair| {
+            let pair = pair.trim();
+            let (key, value) = pair.split_once('=')?;
+            Some((percent_decode(key), percent_decode(value)))
+        })
+        .collect()
+}
+
+// key "value"; key "value";
+fn parse_gtf_attributes(column: &str) -> Vec<(String, String)> {
+    column
+        .split(';')
+        .filter_map(|pair| {
+            let pair = pair.trim();
+            let (key, value) = pair.split_once(' ')?;
+            Some((key.to_string(), value.trim().trim_matches('"').to_string()))
+        })
+        .collect()
+}
+
+fn percent_decode(s: &str) -> String {
+    if !s.contains('%') {
+        return s.to_string();
+    }
+
+    let bytes = s.as_bytes();
+    let mut decoded: Vec<u8> = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && i + 2 < bytes.len()
+            && bytes[i + 1].is_ascii_hexdigit()
+            && bytes[i + 2].is_ascii_hexdigit()
+        {
+            decoded.push(hex_value(bytes[i + 1]) << 4 | hex_value(bytes[i + 2]));
+            i += 3;
+            continue;
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+fn hex_value(b: u8) -> u8 {
+    match b {
+        b'0'..=b'9' => b - b'0',
+        b'a'..=b'f' => b - b'a' + 10,
+        _ => b - b'A' + 10,
+    }
+}
+
+/// A transcript with its exons and CDS, built from GFF3 or GTF records
+///
+/// Features are derived from exons and CDS:
+///
+/// * introns: holes of exons
+/// * utr: exons not in CDS
+/// * 5utr and 3utr: UTRs before and after CDS, according to the strand
+#[derive(Debug, Clone, Default)]
+pub struct Transcript {
+    pub id: String,
+    pub gene_id: String,
+    pub seqid: String,
+    pub strand: String,
+    pub exons: IntSpan,
+    pub cds: IntSpan,
+    /// Attributes of the gene, the transcript and its components
+    pub attributes: Vec<(String, String)>,
+}
+
+impl Transcript {
+    pub fn introns(&self) -> IntSpan {
+        self.exons.holes()
+    }
+
+    pub fn utr(&self) -> IntSpan {
+        if self.cds.is_empty() {
+            IntSpan::new()
+        } else {
+            self.exons.diff(&self.cds)
+        }
+    }
+
+    pub fn utr5(&self) -> IntSpan {
+        let (lower, upper) = self.utr_sides();
+        if self.strand == "-" {
+            upper
+        } else {
+            lower
+        }
+    }
+
+    pub fn utr3(&self) -> IntSpan {
+        let (lower, upper) = self.utr_sides();
+        if self.strand == "-" {
+            lower
+        } else {
+            upper
+        }
+    }
+
+    // UTRs on the left and the right of CDS
+    fn utr_sides(&self) -> (IntSpan, IntSpan) {
+        let mut lower = IntSpan::new();
+        let mut upper = IntSpan::new();
+        for (l, u) in self.utr().spans() {
+            if u < self.cds.min() {
+                lower.add_pair(l, u);
+            } else {
+                upper.add_pair(l, u);
+            }
+        }
+        (lower, upper)
+    }
+
+    /// Features by names: exon, intron, cds, utr, 5utr and 3utr
+    ///
+    /// ```
+    /// # use intspan::{IntSpan, Transcript};
+    /// let tx = Transcript {
+    ///     strand: "-".to_string(),
+    ///     exons: IntSpan::from("5001-5500,5701-6000"),
+    ///     cds: IntSpan::from("5301-5500,5701-5900"),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(tx.feature("intron").unwrap().to_string(), "5501-5700");
+    /// assert_eq!(tx.feature("utr").unwrap().to_string(), "5001-5300,5901-6000");
+    /// assert_eq!(tx.feature("5utr").unwrap().to_string(), "5901-6000");
+    /// assert_eq!(tx.feature("3utr").unwrap().to_string(), "5001-5300");
+    /// assert!(tx.feature("gene").is_none());
+    /// ```
+    pub fn feature(&self, name: &str) -> Option<IntSpan> {
+        match name {
+            "exon" => Some(self.exons.copy()),
+            "intron" => Some(self.introns()),
+            "cds" => Some(self.cds.copy()),
+            "utr" => Some(self.utr()),
+            "5utr" => Some(self.utr5()),
+            "3utr" => Some(self.utr3()),
+            _ => None,
+        }
+    }
+
+    /// Merge another transcript into this one, e.g. to build a gene from its transcripts
+    pub fn merge(&mut self, other: &Self) {
+        self.exons.merge(&other.exons);
+        self.cds.merge(&other.cds);
+        for attr in &other.attributes {
+            if !self.attributes.contains(attr) {
+                self.attributes.push(attr.clone());
+            }
+        }
+    }
+}
+
+// exon-like features of transcripts
+fn component_kind(feature: &str) -> Option<&'static str> {
+    match feature.to_ascii_lowercase().as_str() {
+        "exon" => Some("exon"),
+        "cds" => Some("cds"),
+        "utr" | "5utr" | "3utr" | "five_prime_utr" | "three_prime_utr" => Some("utr"),
+        _ => None,
+    }
+}
+
+/// A record, or records sharing the same ID
+#[derive(Debug, Default)]
+struct Node {
+    feature: String,
+    seqid: String,
+    strand: String,
+    parents: Vec<String>,
+    spans: IntSpan,
+    attributes: Vec<(String, String)>,
+}
+
+#[derive(Debug, Default)]
+struct Parts {
+    gene_id: Option<String>,
+    seqid: String,
+    strand: String,
+    exons: IntSpan,
+    cds: IntSpan,
+    utrs: IntSpan,
+    attributes: Vec<(String, String)>,
+}
+
+/// Build transcripts from GFF3 or GTF records, in the order of appearance
+///
+/// * GFF3: transcripts are parents of exons, CDS and UTRs, or children of genes without them
+///     * Genes without children are transcripts of themselves
+/// * GTF: transcripts are grouped by `transcript_id` and `gene_id`
+/// * If there are no exons, CDS and UTRs are exons; otherwise the transcript itself is
+///
+/// ```
+/// # use intspan::{GffRecord, read_transcripts};
+/// let lines = vec![
+///     "I\t.\tgene\t1001\t2000\t.\t+\t.\tID=g1",
+///     "I\t.\tmRNA\t1001\t2000\t.\t+\t.\tID=t1;Parent=g1",
+///     "I\t.\texon\t1001\t1200\t.\t+\t.\tParent=t1",
+///     "I\t.\texon\t1801\t2000\t.\t+\t.\tParent=t1",
+///     "I\t.\tCDS\t1101\t1200\t.\t+\t0\tParent=t1",
+///     "I\t.\tCDS\t1801\t1900\t.\t+\t2\tParent=t1",
+///     "I\t.\tgene\t7001\t7100\t.\t-\t.\tID=g2",
+///     "I\t.\ttRNA\t7001\t7100\t.\t-\t.\tID=r2;Parent=g2",
+/// ];
+/// let records: Vec<GffRecord> = lines
+///     .iter()
+///     .filter_map(|l| GffRecord::from_line(l).unwrap())
+///     .collect();
+/// let txs = read_transcripts(&records);
+///
+/// assert_eq!(txs.len(), 2);
+/// assert_eq!(txs[0].id, "t1");
+/// assert_eq!(txs[0].gene_id, "g1");
+/// assert_eq!(txs[0].introns().to_string(), "1201-1800");
+/// assert_eq!(txs[0].utr5().to_string(), "1001-1100");
+/// assert_eq!(txs[1].id, "r2");
+/// assert_eq!(txs[1].exons.to_string(), "7001-7100");
+/// ```
+pub fn read_transcripts(records: &[GffRecord]) -> Vec<Transcript> {
+    // ID => Node
+    let mut node_of: IndexMap<String, Node> = IndexMap::new();
+    // transcript ID => Parts
+    let mut parts_of: IndexMap<String, Parts> = IndexMap::new();
+
+    for record in records {
+        if let Some(id) = record.id() {
+            let node = node_of.entry(id.to_string()).or_insert_with(|| Node {
+                feature: record.feature.clone(),
+                seqid: record.seqid.clone(),
+                strand: record.strand.clone(),
+                parents: record.parents().iter().map(|s| s.to_string()).collect(),
+                ..Default::default()
+            });
+            node.spans.add_pair(record.start, record.end);
+            for attr in &record.attributes {
+                if !node.attributes.contains(attr) {
+                    node.attributes.push(attr.clone());
+                }
+            }
+        }
+
+        let kind = match component_kind(&record.feature) {
+            Some(x) => x,
+            None => continue,
+        };
+        for parent in record.parents() {
+            let parts = parts_of.entry(parent.to_string()).or_default();
+            if parts.gene_id.is_none() && record.is_gtf {
+                parts.gene_id = record.attr("gene_id").map(|s| s.to_string());
+            }
+            parts.seqid = record.seqid.clone();
+            parts.strand = record.strand.clone();
+            match kind {
+                "exon" => parts.exons.add_pair(record.start, record.end),
+                "cds" => parts.cds.add_pair(record.start, record.end),
+                _ => parts.utrs.add_pair(record.start, record.end),
+            }
+            for attr in &record.attributes {
+                if !parts.attributes.contains(attr) {
+                    parts.attributes.push(attr.clone());
+                }
+            }
+        }
+    }
+
+    // IDs with children
+    let mut has_children: HashMap<&str, bool> = HashMap::new();
+    for node in node_of.values() {
+        for parent in &node.parents {
+            has_children.insert(parent.as_str(), true);
+        }
+    }
+    let is_top = |id: &str| match node_of.get(id) {
+        Some(node) => node.parents.is_empty(),
+        None => true,
+    };
+
+    // Transcripts without exon-like features, e.g. tRNAs in GFF3 or genes without children
+    for (id, node) in &node_of {
+        if parts_of.contains_key(id) || component_kind(&node.feature).is_some() {
+            continue;
+        }
+        let is_transcript = if node.parents.is_empty() {
+            !has_children.contains_key(id.as_str()) && node.feature.contains("gene")
+        } else {
+            node.parents.iter().all(|p| is_top(p)) && !has_children.contains_key(id.as_str())
+        };
+        if is_transcript {
+            parts_of.insert(
+                id.to_string(),
+                Parts {
+                    seqid: node.seqid.clone(),
+                    strand: node.strand.clone(),
+                    exons: node.spans.copy(),
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    let mut transcripts: Vec<Transcript> = vec![];
+    for (id, parts) in parts_of {
+        let node = node_of.get(&id);
+
+        let gene_id = match (&parts.gene_id, node) {
+            (Some(gene_id), _) => gene_id.to_string(),
+            (None, Some(node)) if !node.parents.is_empty() => node.parents[0].to_string(),
+            _ => id.to_string(),
+        };
+
+        let mut exons = parts.exons.copy();
+        if exons.is_empty() {
+            exons = parts.cds.union(&parts.utrs);
+        }
+        if exons.is_empty() {
+            if let Some(node) = node {
+                exons = node.spans.copy();
+            }
+        }
+
+        let mut attributes: Vec<(String, String)> = vec![];
+        for attrs in vec![
+            node_of.get(&gene_id).map(|n| &n.attributes),
+            node.map(|n| &n.attributes),
+            Some(&parts.attributes),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            for attr in attrs {
+                if !attributes.contains(attr) {
+                    attributes.push(attr.clone());
+                }
+            }
+        }
+
+        transcripts.push(Transcript {
+            id,
+            gene_id,
+            seqid: parts.seqid,
+            strand: parts.strand,
+            exons,
+            cds: parts.cds,
+            attributes,
+        });
+    }
+
+    transcripts
+}
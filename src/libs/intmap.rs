@@ -0,0 +1,528 @@
+//! Piecewise-constant values over integers.
+
+use crate::IntSpan;
+use std::collections::BTreeMap;
+use std::ops::Add;
+
+/// `IntMap` stores values over runs of integers, like `1-100 => 1, 101-150 => 3`.
+///
+/// Runs are inclusive, non-overlapping and sorted. Adjacent runs with equal values are joined,
+/// so each map has a single representation. Positions outside of runs have no values.
+///
+/// ```
+/// # use intspan::IntMap;
+/// let mut depth: IntMap<u32> = IntMap::new();
+/// depth.add(1, 100, 1);
+/// depth.add(90, 150, 1);
+/// depth.add(190, 200, 1);
+///
+/// assert_eq!(
+///     depth.runs().collect::<Vec<_>>(),
+///     vec![(1, 89, &1), (90, 100, &2), (101, 150, &1), (190, 200, &1)]
+/// );
+/// assert_eq!(depth.get(95), Some(&2));
+/// assert_eq!(depth.get(160), None);
+/// assert_eq!(depth.at_least(&2).to_string(), "90-100");
+/// assert_eq!(depth.span().to_string(), "1-150,190-200");
+/// ```
+///
+/// Serialized as an array of `[start, end, value]`
+///
+/// ```
+/// # use intspan::IntMap;
+/// let mut scores: IntMap<f32> = IntMap::new();
+/// scores.insert(1, 10, 0.5);
+/// scores.max(5, 20, 0.8);
+///
+/// let s = serde_json::to_string(&scores).unwrap();
+/// assert_eq!(s, "[[1,4,0.5],[5,20,0.8]]");
+/// let back: IntMap<f32> = serde_json::from_str(&s).unwrap();
+/// assert_eq!(back, scores);
+///
+/// assert!(serde_json::from_str::<IntMap<f32>>("[[5,20,0.8],[1,10,0.5]]").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntMap<V> {
+    // start => (end, value)
+    runs: BTreeMap<i64, (i64, V)>,
+}
+
+impl<V> Default for IntMap<V> {
+    fn default() -> Self {
+        Self {
+            runs: BTreeMap::new(),
+        }
+    }
+}
+
+/// INTERFACE: Creation and contents
+///
+/// ----
+/// ----
+impl<V: Clone + PartialEq> IntMap<V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of runs
+    pub fn len(&self) -> usize {
+        self.runs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.runs.clear();
+    }
+
+    /// The value at a position
+    pub fn get(&self, pos: i64) -> Option<&V> {
+        self.runs
+            .range(..=pos)
+            .next_back()
+            .filter(|(_, (end, _))| pos <= *end)
+            .map(|(_, (_, value))| value)
+    }
+
+    /// Runs as (start, end, value), sorted by starts
+    pub fn runs(&self) -> impl Iterator<Item = (i64, i64, &V)> + '_ {
+        self.runs
+            .iter()
+            .map(|(start, (end, value))| (*start, *end, value))
+    }
+
+    /// Runs overlapping `lower-upper`, clipped to it
+    ///
+    /// ```
+    /// # use intspan::IntMap;
+    /// let mut map: IntMap<i32> = IntMap::new();
+    /// map.insert(1, 100, 1);
+    /// map.insert(201, 300, 2);
+    /// assert_eq!(
+    ///     map.runs_in(51, 250).collect::<Vec<_>>(),
+    ///     vec![(51, 100, &1), (201, 250, &2)]
+    /// );
+    /// ```
+    pub fn runs_in(&self, lower: i64, upper: i64) -> impl Iterator<Item = (i64, i64, &V)> + '_ {
+        let first = self
+            .runs
+            .range(..=lower)
+            .next_back()
+            .map_or(lower, |(start, _)| *start);
+        self.runs
+            .range(first..=upper.max(first))
+            .filter(move |(_, (end, _))| *end >= lower)
+            .map(move |(start, (end, value))| ((*start).max(lower), (*end).min(upper), value))
+            .filter(|(start, end, _)| start <= end)
+    }
+
+    /// Positions with values
+    pub fn span(&self) -> IntSpan {
+        self.filter(|_| true)
+    }
+
+    /// Positions whose values satisfy `f`
+    pub fn filter<F>(&self, f: F) -> IntSpan
+    where
+        F: Fn(&V) -> bool,
+    {
+        let mut ints = IntSpan::new();
+        for (start, end, value) in self.runs() {
+            if f(value) {
+                ints.add_pair(start, end);
+            }
+        }
+        ints
+    }
+
+    /// Positions grouped by values
+    ///
+    /// ```
+    /// # use intspan::IntMap;
+    /// let mut map: IntMap<u32> = IntMap::new();
+    /// map.add(1, 100, 1);
+    /// map.add(90, 150, 1);
+    /// let sets = map.by_value();
+    /// assert_eq!(sets[&1].to_string(), "1-89,101-150");
+    /// assert_eq!(sets[&2].to_string(), "90-100");
+    /// ```
+    pub fn by_value(&self) -> BTreeMap<V, IntSpan>
+    where
+        V: Ord,
+    {
+        let mut sets: BTreeMap<V, IntSpan> = BTreeMap::new();
+        for (start, end, value) in self.runs() {
+            sets.entry(value.clone()).or_default().add_pair(start, end);
+        }
+        sets
+    }
+}
+
+/// INTERFACE: Thresholds
+///
+/// ----
+/// ----
+impl<V: Clone + PartialOrd> IntMap<V> {
+    /// Positions with values >= `min`
+    pub fn at_least(&self, min: &V) -> IntSpan {
+        self.filter(|value| value >= min)
+    }
+
+    /// Positions with values <= `max`
+    pub fn at_most(&self, max: &V) -> IntSpan {
+        self.filter(|value| value <= max)
+    }
+}
+
+/// INTERFACE: Member operations (mutate original map)
+///
+/// Positions without values take `value` in `add()`, `max()` and `min()`
+///
+/// ```
+/// # use intspan::IntMap;
+/// let mut map: IntMap<i32> = IntMap::new();
+/// map.insert(1, 100, 5);
+/// map.min(51, 150, 3);
+/// map.max(91, 200, 4);
+/// map.remove(181, 190);
+/// assert_eq!(
+///     map.runs().collect::<Vec<_>>(),
+///     vec![(1, 50, &5), (51, 90, &3), (91, 180, &4), (191, 200, &4)]
+/// );
+/// ```
+///
+/// ----
+/// ----
+impl<V: Clone + PartialEq> IntMap<V> {
+    /// Set values of `lower-upper`
+    pub fn insert(&mut self, lower: i64, upper: i64, value: V) {
+        self.update(lower, upper, |_| Some(value.clone()));
+    }
+
+    /// Remove values of `lower-upper`
+    pub fn remove(&mut self, lower: i64, upper: i64) {
+        self.update(lower, upper, |_| None);
+    }
+
+    /// Replace values of each run and gap in `lower-upper` by `f`.
+    /// Gaps are passed as `None`, and runs with `None` returned are removed.
+    ///
+    /// Does nothing when `lower > upper`.
+    pub fn update<F>(&mut self, lower: i64, upper: i64, mut f: F)
+    where
+        F: FnMut(Option<&V>) -> Option<V>,
+    {
+        if lower > upper {
+            return;
+        }
+
+        self.split_at(lower);
+        if upper < i64::MAX {
+            self.split_at(upper + 1);
+        }
+
+        let olds: Vec<(i64, (i64, V))> = self
+            .runs
+            .range(lower..=upper)
+            .map(|(start, run)| (*start, run.clone()))
+            .collect();
+
+        let mut news: Vec<(i64, i64, V)> = vec![];
+        // The first position not visited yet, `None` past `i64::MAX`
+        let mut cur = Some(lower);
+        for (start, (end, value)) in &olds {
+            if let Some(gap) = cur.filter(|gap| gap < start) {
+                if let Some(v) = f(None) {
+                    news.push((gap, start - 1, v));
+                }
+            }
+            if let Some(v) = f(Some(value)) {
+                news.push((*start, *end, v));
+            }
+            cur = end.checked_add(1);
+        }
+        if let Some(gap) = cur.filter(|gap| *gap <= upper) {
+            if let Some(v) = f(None) {
+                news.push((gap, upper, v));
+            }
+        }
+
+        for (start, _) in olds {
+            self.runs.remove(&start);
+        }
+        for (start, end, value) in news {
+            self.runs.insert(start, (end, value));
+        }
+
+        self.join(lower, upper);
+    }
+
+    // Make `pos` the start of a run, if it's inside one
+    fn split_at(&mut self, pos: i64) {
+        let found = self
+            .runs
+            .range(..pos)
+            .next_back()
+            .filter(|(_, (end, _))| *end >= pos)
+            .map(|(start, (end, value))| (*start, *end, value.clone()));
+
+        if let Some((start, end, value)) = found {
+            self.runs.insert(start, (pos - 1, value.clone()));
+            self.runs.insert(pos, (end, value));
+        }
+    }
+
+    // Join adjacent runs with equal values around `lower-upper`
+    fn join(&mut self, lower: i64, upper: i64) {
+        let first = self
+            .runs
+            .range(..lower)
+            .next_back()
+            .map_or(lower, |(start, _)| *start);
+        let keys: Vec<i64> = self
+            .runs
+            .range(first..=upper.saturating_add(1))
+            .map(|(start, _)| *start)
+            .collect();
+
+        let mut prev: Option<i64> = None;
+        for start in keys {
+            if let Some(p) = prev {
+                let (p_end, p_value) = &self.runs[&p];
+                let (end, value) = &self.runs[&start];
+                if p_end.saturating_add(1) == start && p_value == value {
+                    let end = *end;
+                    self.runs.remove(&start);
+                    self.runs.get_mut(&p).unwrap().0 = end;
+                    continue;
+                }
+            }
+            prev = Some(start);
+        }
+    }
+}
+
+impl<V: Clone + PartialEq + Add<Output = V>> IntMap<V> {
+    /// Add `value` to values of `lower-upper`
+    pub fn add(&mut self, lower: i64, upper: i64, value: V) {
+        self.update(lower, upper, |old| match old {
+            Some(v) => Some(v.clone() + value.clone()),
+            None => Some(value.clone()),
+        });
+    }
+}
+
+impl<V: Clone + PartialOrd> IntMap<V> {
+    /// Keep the larger one of the old value and `value` in `lower-upper`
+    pub fn max(&mut self, lower: i64, upper: i64, value: V) {
+        self.update(lower, upper, |old| match old {
+            Some(v) if *v >= value => Some(v.clone()),
+            _ => Some(value.clone()),
+        });
+    }
+
+    /// Keep the smaller one of the old value and `value` in `lower-upper`
+    pub fn min(&mut self, lower: i64, upper: i64, value: V) {
+        self.update(lower, upper, |old| match old {
+            Some(v) if *v <= value => Some(v.clone()),
+            _ => Some(value.clone()),
+        });
+    }
+}
+
+impl IntMap<usize> {
+    /// Depths of coverage of ranges, as (start, end).
+    ///
+    /// Edges of ranges are sorted and swept once, which is faster than `add()` for deep coverage.
+    /// Reversed ranges are ignored.
+    ///
+    /// ```
+    /// # use intspan::IntMap;
+    /// let depth = IntMap::depth(vec![(1, 100), (90, 150), (151, 200), (301, 400)]);
+    /// assert_eq!(
+    ///     depth.runs().collect::<Vec<_>>(),
+    ///     vec![(1, 89, &1), (90, 100, &2), (101, 200, &1), (301, 400, &1)]
+    /// );
+    /// ```
+    pub fn depth<I>(ranges: I) -> Self
+    where
+        I: IntoIterator<Item = (i64, i64)>,
+    {
+        // (position, +1 for starts or -1 for ends)
+        let mut edges: Vec<(i64, i64)> = vec![];
+        for (start, end) in ranges {
+            if start > end {
+                continue;
+            }
+            edges.push((start, 1));
+            if let Some(next) = end.checked_add(1) {
+                edges.push((next, -1));
+            }
+        }
+        edges.sort_unstable();
+
+        let mut map = Self::new();
+        let mut depth: i64 = 0;
+        let mut i = 0;
+        while i < edges.len() {
+            let pos = edges[i].0;
+            while i < edges.len() && edges[i].0 == pos {
+                depth += edges[i].1;
+                i += 1;
+            }
+
+            // Runs are pushed in order, and joined with the previous one
+            let end = edges.get(i).map_or(i64::MAX, |(next, _)| next - 1);
+            if depth > 0 {
+                match map.runs.iter_mut().next_back() {
+                    Some((_, (last_end, last)))
+                        if last_end.checked_add(1) == Some(pos) && *last == depth as usize =>
+                    {
+                        *last_end = end;
+                    }
+                    _ => {
+                        map.runs.insert(pos, (end, depth as usize));
+                    }
+                }
+            }
+        }
+
+        map
+    }
+}
+
+/// INTERFACE: Serde
+///
+/// ----
+/// ----
+impl<V: serde::Serialize> serde::Serialize for IntMap<V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            self.runs
+                .iter()
+                .map(|(start, (end, value))| (start, end, value)),
+        )
+    }
+}
+
+impl<'de, V> serde::Deserialize<'de> for IntMap<V>
+where
+    V: serde::Deserialize<'de> + Clone + PartialEq,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let runs: Vec<(i64, i64, V)> = serde::Deserialize::deserialize(deserializer)?;
+
+        let mut map = Self::new();
+        let mut prev: Option<i64> = None;
+        for (start, end, value) in runs {
+            if start > end || prev.is_some_and(|p| start <= p) {
+                return Err(serde::de::Error::custom(format!(
+                    "Runs should be sorted and disjoint: {}-{}",
+                    start, end
+                )));
+            }
+            map.insert(start, end, value);
+            prev = Some(end);
+        }
+
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Values of each position in 0..=40
+    fn naive(map: &IntMap<i32>) -> Vec<Option<i32>> {
+        (0..=40).map(|n| map.get(n).copied()).collect()
+    }
+
+    #[test]
+    fn against_naive() {
+        let ops: Vec<(&str, i64, i64, i32)> = vec![
+            ("add", 1, 10, 1),
+            ("add", 5, 15, 2),
+            ("max", 8, 20, 2),
+            ("min", 0, 6, 2),
+            ("insert", 12, 13, 7),
+            ("add", 11, 11, 1),
+            ("remove", 3, 4, 0),
+            ("add", 25, 30, 1),
+            ("add", 31, 35, 1),
+            ("min", 28, 40, 0),
+        ];
+
+        let mut map: IntMap<i32> = IntMap::new();
+        let mut expected: Vec<Option<i32>> = vec![None; 41];
+        for (op, lower, upper, value) in ops {
+            match op {
+                "add" => map.add(lower, upper, value),
+                "max" => map.max(lower, upper, value),
+                "min" => map.min(lower, upper, value),
+                "insert" => map.insert(lower, upper, value),
+                "remove" => map.remove(lower, upper),
+                _ => unreachable!(),
+            }
+            for n in lower..=upper {
+                let old = expected[n as usize];
+                expected[n as usize] = match op {
+                    "add" => Some(old.unwrap_or(0) + value),
+                    "max" => Some(old.map_or(value, |v| v.max(value))),
+                    "min" => Some(old.map_or(value, |v| v.min(value))),
+                    "insert" => Some(value),
+                    _ => None,
+                };
+            }
+            assert_eq!(naive(&map), expected, "{} {}-{}", op, lower, upper);
+
+            // Joined runs
+            let runs: Vec<_> = map.runs().collect();
+            for w in runs.windows(2) {
+                assert!(w[0].1 < w[1].0);
+                assert!(w[0].1 + 1 < w[1].0 || w[0].2 != w[1].2);
+            }
+        }
+    }
+
+    #[test]
+    fn depth_against_add() {
+        let ranges: Vec<(i64, i64)> = (0..200)
+            .map(|i| {
+                let start = (i * 7919) % 1000;
+                (start, start + (i * 104729) % 300)
+            })
+            .collect();
+
+        let mut expected: IntMap<usize> = IntMap::new();
+        for (start, end) in &ranges {
+            expected.add(*start, *end, 1);
+        }
+        assert_eq!(IntMap::depth(ranges), expected);
+
+        let depth = IntMap::depth(vec![(i64::MAX - 1, i64::MAX), (5, 1)]);
+        assert_eq!(
+            depth.runs().collect::<Vec<_>>(),
+            vec![(i64::MAX - 1, i64::MAX, &1)]
+        );
+    }
+
+    #[test]
+    fn bounds() {
+        let mut map: IntMap<i32> = IntMap::new();
+        map.add(i64::MIN, i64::MAX, 1);
+        map.add(i64::MAX, i64::MAX, 1);
+        map.add(i64::MIN, 0, 1);
+        assert_eq!(map.get(i64::MAX), Some(&2));
+        assert_eq!(map.get(1), Some(&1));
+        assert_eq!(map.get(i64::MIN), Some(&2));
+        assert_eq!(map.len(), 3);
+
+        // Nothing for reversed ranges
+        map.insert(10, 5, 100);
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.runs_in(10, 5).count(), 0);
+    }
+}
@@ -1,9 +1,13 @@
-use anyhow::anyhow;
 use std::borrow::Cow;
+use std::cmp::Reverse;
 use std::cmp::{min, Ordering};
-use std::collections::VecDeque;
+use std::collections::{BinaryHeap, VecDeque};
+use std::convert::TryFrom;
+use std::iter::FromIterator;
 use std::vec::Vec;
 
+use crate::{RunlistError, RunlistErrorKind};
+
 /// `IntSpan` handles of sets containing integer spans.
 ///
 /// # SYNOPSIS
@@ -54,8 +58,8 @@ use std::vec::Vec;
 /// 3-7, 9, 11, 12) would be represented internally as (1, 2, 3, 8, 11, 13).
 ///
 /// Sets may be infinite - assuming you're prepared to accept that infinity is actually no more than a
-/// fairly large integer. Specifically the constants `neg_inf` and `pos_inf` are defined to be (-2^31+1)
-/// and (2^31-2) respectively. To create an infinite set invert an empty one:
+/// fairly large integer. Specifically the constants `neg_inf` and `pos_inf` are defined to be (-2^63+1)
+/// and (2^63-2) respectively. To create an infinite set invert an empty one:
 ///
 /// ```
 /// # use intspan::IntSpan;
@@ -91,14 +95,14 @@ use std::vec::Vec;
 /// This Rust crate is ported from the Java class `jintspan` and the Perl module `AlignDB::IntSpan`,
 /// which contains many codes from `Set::IntSpan`, `Set::IntSpan::Fast` and `Set::IntSpan::Island`.
 ///
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
 pub struct IntSpan {
-    edges: VecDeque<i32>,
+    edges: VecDeque<i64>,
 }
 
 lazy_static! {
-    static ref POS_INF: i32 = 2_147_483_647 - 1; // INT_MAX - 1, Real Largest int is POS_INF - 1
-    static ref NEG_INF: i32 = -2_147_483_648 + 1;
+    static ref POS_INF: i64 = i64::MAX - 1; // LONG_MAX - 1, Real Largest int is POS_INF - 1
+    static ref NEG_INF: i64 = i64::MIN + 1;
     static ref EMPTY_STRING: String = "-".to_string();
 }
 
@@ -114,6 +118,11 @@ impl IntSpan {
         }
     }
 
+    /// Constructed from a runlist
+    ///
+    /// # Panics
+    ///
+    /// Panics on malformed runlists. Use [`TryFrom`](std::convert::TryFrom) or [`str::parse`] to get an error instead.
     pub fn from(runlist: &str) -> Self {
         let mut new = Self::new();
         new.add_runlist(runlist);
@@ -122,11 +131,10 @@ impl IntSpan {
     }
 
     pub fn valid(runlist: &str) -> bool {
-        let new = Self::new();
-        new.runlist_to_ranges(runlist).is_ok()
+        runlist.parse::<Self>().is_ok()
     }
 
-    pub fn from_pair(lower: i32, upper: i32) -> Self {
+    pub fn from_pair(lower: i64, upper: i64) -> Self {
         let mut new = Self::new();
         new.add_pair(lower, upper);
 
@@ -137,7 +145,7 @@ impl IntSpan {
     ///
     /// Typically used to construct infinite sets
     #[inline]
-    pub fn get_pos_inf(&self) -> i32 {
+    pub fn get_pos_inf(&self) -> i64 {
         *POS_INF - 1
     }
 
@@ -145,7 +153,7 @@ impl IntSpan {
     ///
     /// Typically used to construct infinite sets
     #[inline]
-    pub fn get_neg_inf(&self) -> i32 {
+    pub fn get_neg_inf(&self) -> i64 {
         *NEG_INF
     }
 
@@ -165,21 +173,18 @@ impl IntSpan {
         self.edge_size() / 2
     }
 
-    pub fn to_vec(&self) -> Vec<i32> {
-        self.spans()
-            .into_iter()
-            .flat_map(|(lower, upper)| (lower..=upper).collect::<Vec<_>>())
-            .collect()
+    pub fn to_vec(&self) -> Vec<i64> {
+        self.iter().collect()
     }
 
     #[inline]
-    pub fn contains(&self, n: i32) -> bool {
+    pub fn contains(&self, n: i64) -> bool {
         let pos = self.find_pos(n + 1, 0);
         (pos & 1) == 1
     }
 
     #[inline]
-    pub fn min(&self) -> i32 {
+    pub fn min(&self) -> i64 {
         if self.is_empty() {
             panic!("Can't get extrema for empty IntSpan");
         }
@@ -188,7 +193,7 @@ impl IntSpan {
     }
 
     #[inline]
-    pub fn max(&self) -> i32 {
+    pub fn max(&self) -> i64 {
         if self.is_empty() {
             panic!("Can't get extrema for empty IntSpan");
         }
@@ -218,15 +223,20 @@ mod create {
             ("1-3,4", "1-4", vec![1, 2, 3, 4]),
             ("1-3,4,5-7", "1-7", vec![1, 2, 3, 4, 5, 6, 7]),
             ("1,2,3,4,5,6,7", "1-7", vec![1, 2, 3, 4, 5, 6, 7]),
+            (
+                "3000000000-3000000002",
+                "3000000000-3000000002",
+                vec![3000000000, 3000000001, 3000000002],
+            ),
         ];
 
         // create new
         for (runlist, exp_runlist, exp_elements) in &tests {
             let mut intspan = IntSpan::new();
-            intspan.add_runlist(*runlist);
+            intspan.add_runlist(runlist);
 
-            assert_eq!(intspan.cardinality(), exp_elements.len() as i32);
-            assert_eq!(intspan.size(), exp_elements.len() as i32);
+            assert_eq!(intspan.cardinality(), exp_elements.len() as i64);
+            assert_eq!(intspan.size(), exp_elements.len() as i64);
             assert_eq!(intspan.to_string(), *exp_runlist);
             assert_eq!(intspan.runlist(), *exp_runlist);
             assert_eq!(intspan.to_vec(), *exp_elements);
@@ -234,9 +244,9 @@ mod create {
         }
 
         for (runlist, exp_runlist, exp_elements) in &tests {
-            let intspan = IntSpan::from(*runlist);
+            let intspan = IntSpan::from(runlist);
 
-            assert_eq!(intspan.cardinality(), exp_elements.len() as i32);
+            assert_eq!(intspan.cardinality(), exp_elements.len() as i64);
             assert_eq!(intspan.to_string(), *exp_runlist);
             assert_eq!(intspan.to_vec(), *exp_elements);
         }
@@ -245,7 +255,7 @@ mod create {
             let mut intspan = IntSpan::new();
             intspan.add_vec(exp_elements);
 
-            assert_eq!(intspan.cardinality(), exp_elements.len() as i32);
+            assert_eq!(intspan.cardinality(), exp_elements.len() as i64);
             assert_eq!(intspan.to_string(), *exp_runlist);
             assert_eq!(intspan.to_vec(), *exp_elements);
         }
@@ -265,10 +275,40 @@ mod create {
 
         // create new
         for (runlist, exp) in &tests {
-            assert_eq!(IntSpan::valid(*runlist), *exp);
+            assert_eq!(IntSpan::valid(runlist), *exp);
         }
     }
 
+    #[test]
+    fn test_try_from() {
+        let tests = vec![
+            ("1-3,x", 4, RunlistErrorKind::InvalidChar('x')),
+            ("1 ,3", 1, RunlistErrorKind::InvalidChar(' ')),
+            ("1-", 2, RunlistErrorKind::MissingNumber),
+            ("1,,3", 2, RunlistErrorKind::MissingNumber),
+            ("5-3", 0, RunlistErrorKind::Reversed(5, 3)),
+            ("1,-1--2", 2, RunlistErrorKind::Reversed(-1, -2)),
+            ("1-99999999999999999999", 2, RunlistErrorKind::Overflow),
+            ("-9223372036854775808", 0, RunlistErrorKind::Overflow),
+        ];
+
+        for (runlist, pos, kind) in tests {
+            let err = IntSpan::try_from(runlist).unwrap_err();
+            assert_eq!(err.pos, pos, "{}", runlist);
+            assert_eq!(err.kind, kind, "{}", runlist);
+        }
+
+        // A trailing comma is allowed
+        let ints: IntSpan = "1-3,".parse().unwrap();
+        assert_eq!(ints.to_string(), "1-3");
+
+        // Infinite sets round trip
+        let mut ints = IntSpan::new();
+        ints.invert();
+        let parsed: IntSpan = ints.to_string().parse().unwrap();
+        assert!(parsed.is_universal());
+    }
+
     #[test]
     #[should_panic(expected = "Bad order: 1,-1")]
     fn panic_pair() {
@@ -277,6 +317,13 @@ mod create {
         println!("{:?}", set.ranges());
     }
 
+    #[test]
+    #[should_panic(expected = "Element out of range: 9223372036854775807")]
+    fn panic_extend() {
+        let set = IntSpan::from_iter([1, 2, i64::MAX]);
+        println!("{:?}", set.ranges());
+    }
+
     #[test]
     #[should_panic(expected = "Bad order: 1,-1")]
     fn panic_runlist() {
@@ -314,14 +361,8 @@ impl IntSpan {
     /// let ints = intspan::IntSpan::from("1-2,4-7");
     /// assert_eq!(ints.spans(), vec![(1, 2), (4, 7)]);
     /// ```
-    pub fn spans(&self) -> Vec<(i32, i32)> {
-        (0..self.span_size())
-            .map(|i| {
-                let lower = self.edges[i * 2];
-                let upper = self.edges[i * 2 + 1] - 1;
-                (lower, upper)
-            })
-            .collect()
+    pub fn spans(&self) -> Vec<(i64, i64)> {
+        self.iter_spans().collect()
     }
 
     /// Returns the runs in IntSpan, as a vector of lower, upper
@@ -330,7 +371,7 @@ impl IntSpan {
     /// let ints = intspan::IntSpan::from("1-2,4-7");
     /// assert_eq!(ints.ranges(), vec![1, 2, 4, 7]);
     /// ```
-    pub fn ranges(&self) -> Vec<i32> {
+    pub fn ranges(&self) -> Vec<i64> {
         self.spans()
             .into_iter()
             .flat_map(|(lower, upper)| vec![lower, upper])
@@ -386,7 +427,7 @@ mod content {
         // spans
         for (runlist, _, exp_spans) in &tests {
             let mut ints = IntSpan::new();
-            ints.add_runlist(*runlist);
+            ints.add_runlist(runlist);
 
             let res = ints.spans();
 
@@ -400,7 +441,7 @@ mod content {
 /// ----
 /// ----
 impl IntSpan {
-    pub fn cardinality(&self) -> i32 {
+    pub fn cardinality(&self) -> i64 {
         if self.is_empty() {
             return 0;
         }
@@ -447,7 +488,7 @@ impl IntSpan {
 /// ----
 /// ----
 impl IntSpan {
-    pub fn add_pair(&mut self, mut lower: i32, mut upper: i32) {
+    pub fn add_pair(&mut self, mut lower: i64, mut upper: i64) {
         if lower > upper {
             panic!("Bad order: {},{}", lower, upper)
         }
@@ -474,12 +515,12 @@ impl IntSpan {
         self.edges.insert(lower_pos + 1, upper);
     }
 
-    pub fn add_n(&mut self, n: i32) {
+    pub fn add_n(&mut self, n: i64) {
         self.add_pair(n, n);
     }
 
-    pub fn add_ranges(&mut self, ranges: &[i32]) {
-        if ranges.len() % 2 != 0 {
+    pub fn add_ranges(&mut self, ranges: &[i64]) {
+        if !ranges.len().is_multiple_of(2) {
             panic!("Number of ranges must be even")
         }
 
@@ -517,7 +558,7 @@ impl IntSpan {
         self.add_ranges(&ranges);
     }
 
-    pub fn add_vec(&mut self, ints: &[i32]) {
+    pub fn add_vec(&mut self, ints: &[i64]) {
         let ranges = self.list_to_ranges(ints);
 
         self.add_ranges(&ranges);
@@ -527,7 +568,9 @@ impl IntSpan {
     pub fn add_runlist(&mut self, runlist: &str) {
         // skip empty runlist
         if !runlist.is_empty() && !runlist.eq(&*EMPTY_STRING) {
-            let ranges = self.runlist_to_ranges(runlist).unwrap();
+            let ranges = self
+                .runlist_to_ranges(runlist)
+                .unwrap_or_else(|e| panic!("{}", e));
             self.add_ranges(&ranges);
         }
     }
@@ -555,18 +598,18 @@ impl IntSpan {
         }
     }
 
-    pub fn remove_pair(&mut self, lower: i32, upper: i32) {
+    pub fn remove_pair(&mut self, lower: i64, upper: i64) {
         self.invert();
         self.add_pair(lower, upper);
         self.invert();
     }
 
-    pub fn remove_n(&mut self, n: i32) {
+    pub fn remove_n(&mut self, n: i64) {
         self.remove_pair(n, n);
     }
 
-    pub fn remove_ranges(&mut self, ranges: &[i32]) {
-        if ranges.len() % 2 != 0 {
+    pub fn remove_ranges(&mut self, ranges: &[i64]) {
+        if !ranges.len().is_multiple_of(2) {
             panic!("Number of ranges must be even");
         }
 
@@ -581,7 +624,7 @@ impl IntSpan {
         self.remove_ranges(&ranges);
     }
 
-    pub fn remove_vec(&mut self, array: &[i32]) {
+    pub fn remove_vec(&mut self, array: &[i64]) {
         let ranges = self.list_to_ranges(array);
 
         self.remove_ranges(&ranges);
@@ -590,7 +633,9 @@ impl IntSpan {
     pub fn remove_runlist(&mut self, runlist: &str) {
         // skip empty runlist
         if !runlist.is_empty() && !runlist.eq(&*EMPTY_STRING) {
-            let ranges = self.runlist_to_ranges(runlist).unwrap();
+            let ranges = self
+                .runlist_to_ranges(runlist)
+                .unwrap_or_else(|e| panic!("{}", e));
             self.remove_ranges(&ranges);
         }
     }
@@ -602,23 +647,23 @@ mod mutate {
 
     #[test]
     fn test_mutate() {
-        let sets = vec!["-", "1", "1-2", "1,3-5"];
+        let sets = ["-", "1", "1-2", "1,3-5"];
 
-        let contains = vec![
+        let contains = [
             vec![false, false, false, false],
             vec![true, false, false, false],
             vec![true, true, false, false],
             vec![true, false, true, true],
         ];
 
-        let added = vec![
+        let added = [
             vec!["1", "2", "3", "4"],
             vec!["1", "1-2", "1,3", "1,4"],
             vec!["1-2", "1-2", "1-3", "1-2,4"],
             vec!["1,3-5", "1-5", "1,3-5", "1,3-5"],
         ];
 
-        let removed = vec![
+        let removed = [
             vec!["-", "-", "-", "-"],
             vec!["-", "1", "1", "1"],
             vec!["2", "1", "1-2", "1-2"],
@@ -637,19 +682,13 @@ mod mutate {
                 set_removed.remove_n(n);
 
                 // contains
-                assert_eq!(set.contains(n), contains[i as usize][j as usize]);
+                assert_eq!(set.contains(n), contains[i][j as usize]);
 
                 // added
-                assert_eq!(
-                    set_added.to_string(),
-                    added[i as usize][j as usize].to_string()
-                );
+                assert_eq!(set_added.to_string(), added[i][j as usize].to_string());
 
                 // removed
-                assert_eq!(
-                    set_removed.to_string(),
-                    removed[i as usize][j as usize].to_string()
-                );
+                assert_eq!(set_removed.to_string(), removed[i][j as usize].to_string());
             }
         }
     }
@@ -657,6 +696,23 @@ mod mutate {
 
 /// INTERFACE: Set binary operations (create new set)
 ///
+/// The operators `|`, `&`, `-` and `^` are `union`, `intersect`, `diff` and `xor`
+///
+/// ```
+/// # use intspan::IntSpan;
+/// let a = IntSpan::from("1-10");
+/// let b = IntSpan::from("5-15");
+/// assert_eq!((&a | &b).to_string(), "1-15");
+/// assert_eq!((&a & &b).to_string(), "5-10");
+/// assert_eq!((&a - &b).to_string(), "1-4");
+/// assert_eq!((&a ^ &b).to_string(), "1-4,11-15");
+///
+/// let mut c = a.clone();
+/// c |= &b;
+/// c -= IntSpan::from("3-12");
+/// assert_eq!(c, IntSpan::from("1-2,13-15"));
+/// ```
+///
 /// ----
 /// ----
 impl IntSpan {
@@ -757,6 +813,149 @@ mod binary {
     }
 }
 
+/// INTERFACE: k-way operations (create new set)
+///
+/// Edges of all sets are merged by a heap and swept once, counting sets covering each position.
+/// This is O(N log k) for N edges of k sets, instead of folding sets pairwise.
+///
+/// ```
+/// # use intspan::IntSpan;
+/// let sets = vec![
+///     IntSpan::from("1-10"),
+///     IntSpan::from("5-15"),
+///     IntSpan::from("8-20,30"),
+/// ];
+/// assert_eq!(IntSpan::union_all(&sets).to_string(), "1-20,30");
+/// assert_eq!(IntSpan::intersect_all(&sets).to_string(), "8-10");
+/// assert_eq!(IntSpan::at_least(&sets, 2).to_string(), "5-15");
+/// assert_eq!(IntSpan::at_least(&sets, 4).to_string(), "-");
+/// ```
+///
+/// ----
+/// ----
+impl IntSpan {
+    /// Elements in any of the sets
+    pub fn union_all<'a, I>(sets: I) -> Self
+    where
+        I: IntoIterator<Item = &'a IntSpan>,
+    {
+        Self::at_least(sets, 1)
+    }
+
+    /// Elements in all of the sets. Empty for no sets
+    pub fn intersect_all<'a, I>(sets: I) -> Self
+    where
+        I: IntoIterator<Item = &'a IntSpan>,
+    {
+        let sets: Vec<&IntSpan> = sets.into_iter().collect();
+        if sets.is_empty() {
+            return Self::new();
+        }
+        let n = sets.len();
+        Self::at_least(sets, n)
+    }
+
+    /// Elements in at least `k` of the sets. `k` of 0 is the universal set
+    pub fn at_least<'a, I>(sets: I, k: usize) -> Self
+    where
+        I: IntoIterator<Item = &'a IntSpan>,
+    {
+        if k == 0 {
+            return Self::new().complement();
+        }
+
+        let sets: Vec<&IntSpan> = sets.into_iter().filter(|s| !s.is_empty()).collect();
+        let mut new = Self::new();
+        if sets.len() < k {
+            return new;
+        }
+
+        // (edge, set, index of the edge), lower edges are at even indexes
+        let mut heap: BinaryHeap<Reverse<(i64, usize, usize)>> = sets
+            .iter()
+            .enumerate()
+            .map(|(i, s)| Reverse((s.edges[0], i, 0)))
+            .collect();
+
+        let mut depth: usize = 0;
+        while let Some(Reverse((edge, _, _))) = heap.peek().copied() {
+            let prev = depth;
+            // All edges at the same position
+            while let Some(Reverse((e, i, j))) = heap.peek().copied() {
+                if e != edge {
+                    break;
+                }
+                heap.pop();
+                if j % 2 == 0 {
+                    depth += 1;
+                } else {
+                    depth -= 1;
+                }
+                if j + 1 < sets[i].edges.len() {
+                    heap.push(Reverse((sets[i].edges[j + 1], i, j + 1)));
+                }
+            }
+
+            if (prev < k) != (depth < k) {
+                new.edges.push_back(edge);
+            }
+        }
+
+        new
+    }
+}
+
+#[cfg(test)]
+mod kway {
+    use super::*;
+
+    #[test]
+    fn test_kway() {
+        let sets: Vec<IntSpan> = vec!["1-3,5,8-11", "1-6", "-", "2-9,20-30", "10-25"]
+            .into_iter()
+            .map(IntSpan::from)
+            .collect();
+
+        // Folding pairwise
+        let mut union = IntSpan::new();
+        let mut intersect = IntSpan::from("1-100");
+        for s in &sets {
+            union = union.union(s);
+            intersect = intersect.intersect(s);
+        }
+        assert_eq!(IntSpan::union_all(&sets), union);
+        assert_eq!(IntSpan::intersect_all(&sets), intersect);
+        assert_eq!(IntSpan::intersect_all(&sets[..2]).to_string(), "1-3,5");
+
+        // Counting each element
+        for k in 1..=5 {
+            let expected: IntSpan = (0..=40)
+                .filter(|n| sets.iter().filter(|s| s.contains(*n)).count() >= k)
+                .collect();
+            assert_eq!(IntSpan::at_least(&sets, k), expected, "k = {}", k);
+        }
+
+        // Adjacent spans are joined
+        let sets = vec![IntSpan::from("1-5"), IntSpan::from("6-10")];
+        assert_eq!(IntSpan::union_all(&sets).to_string(), "1-10");
+        assert_eq!(IntSpan::union_all(&sets).span_size(), 1);
+
+        // Infinite sets
+        let mut inf = IntSpan::new();
+        inf.add_pair(1, inf.get_pos_inf());
+        let sets = vec![inf.copy(), IntSpan::from("-5-10")];
+        assert_eq!(
+            IntSpan::union_all(&sets).to_string(),
+            format!("-5-{}", inf.get_pos_inf())
+        );
+        assert_eq!(IntSpan::intersect_all(&sets).to_string(), "1-10");
+
+        assert!(IntSpan::union_all(&[]).is_empty());
+        assert!(IntSpan::intersect_all(&[]).is_empty());
+        assert!(IntSpan::at_least(&sets, 0).is_universal());
+    }
+}
+
 /// INTERFACE: Set relations
 ///
 /// ----
@@ -794,9 +993,9 @@ mod relation {
 
     #[test]
     fn test_relation() {
-        let sets = vec!["-", "1", "5", "1-5", "3-7", "1-3,8,10-23"];
+        let sets = ["-", "1", "5", "1-5", "3-7", "1-3,8,10-23"];
 
-        let equals = vec![
+        let equals = [
             vec![1, 0, 0, 0, 0, 0],
             vec![0, 1, 0, 0, 0, 0],
             vec![0, 0, 1, 0, 0, 0],
@@ -805,7 +1004,7 @@ mod relation {
             vec![0, 0, 0, 0, 0, 1],
         ];
 
-        let subset = vec![
+        let subset = [
             vec![1, 1, 1, 1, 1, 1],
             vec![0, 1, 0, 1, 0, 1],
             vec![0, 0, 1, 1, 1, 0],
@@ -814,7 +1013,7 @@ mod relation {
             vec![0, 0, 0, 0, 0, 1],
         ];
 
-        let superset = vec![
+        let superset = [
             vec![1, 0, 0, 0, 0, 0],
             vec![1, 1, 0, 0, 0, 0],
             vec![1, 0, 1, 0, 0, 0],
@@ -829,13 +1028,13 @@ mod relation {
                 let b = IntSpan::from(sets[j]);
 
                 // equals
-                assert_eq!(a.equals(&b), equals[i as usize][j as usize] != 0);
+                assert_eq!(a.equals(&b), equals[i][j] != 0);
 
                 // subset
-                assert_eq!(a.subset(&b), subset[i as usize][j as usize] != 0);
+                assert_eq!(a.subset(&b), subset[i][j] != 0);
 
                 // superset
-                assert_eq!(a.superset(&b), superset[i as usize][j as usize] != 0);
+                assert_eq!(a.superset(&b), superset[i][j] != 0);
             }
         }
     }
@@ -846,7 +1045,7 @@ mod relation {
 /// ----
 /// ----
 impl IntSpan {
-    fn at_pos(&self, index: i32) -> i32 {
+    fn at_pos(&self, index: i64) -> i64 {
         let mut element = self.min();
         let mut ele_before = 0;
 
@@ -867,7 +1066,7 @@ impl IntSpan {
         element
     }
 
-    fn at_neg(&self, index: i32) -> i32 {
+    fn at_neg(&self, index: i64) -> i64 {
         let mut element = self.max();
         let mut ele_after = 0;
 
@@ -891,14 +1090,14 @@ impl IntSpan {
     /// Returns the index-th element of set, indices start from `1`.
     ///
     /// Negative indices count backwards from the end of the set.
-    pub fn at(&self, index: i32) -> i32 {
+    pub fn at(&self, index: i64) -> i64 {
         if self.is_empty() {
             panic!("Indexing on an empty set");
         }
-        if i32::abs(index) < 1 {
+        if i64::abs(index) < 1 {
             panic!("Index can't be 0");
         }
-        if i32::abs(index) > self.cardinality() {
+        if i64::abs(index) > self.cardinality() {
             panic!("Out of max index");
         }
 
@@ -910,7 +1109,7 @@ impl IntSpan {
     }
 
     /// Returns the index of an element in the set, indices start from `1`
-    pub fn index(&self, element: i32) -> i32 {
+    pub fn index(&self, element: i64) -> i64 {
         if self.is_empty() {
             panic!("Indexing on an empty set");
         }
@@ -936,7 +1135,7 @@ impl IntSpan {
         index
     }
 
-    pub fn slice(&self, from: i32, to: i32) -> IntSpan {
+    pub fn slice(&self, from: i64, to: i64) -> IntSpan {
         if self.is_empty() {
             panic!("Indexing on an empty set");
         }
@@ -998,13 +1197,13 @@ mod index {
             let set = IntSpan::from(runlist);
 
             // at
-            if exp_index.is_some() {
-                assert_eq!(set.at(n), exp_index.unwrap());
+            if let Some(exp_index) = exp_index {
+                assert_eq!(set.at(n), exp_index);
             }
 
             // index
-            if exp_element.is_some() {
-                assert_eq!(set.index(n), exp_element.unwrap());
+            if let Some(exp_element) = exp_element {
+                assert_eq!(set.index(n), exp_element);
             }
         }
     }
@@ -1112,7 +1311,7 @@ impl IntSpan {
         new
     }
 
-    pub fn inset(&self, n: i32) -> Self {
+    pub fn inset(&self, n: i64) -> Self {
         let mut new = IntSpan::new();
 
         for i in 0..self.span_size() {
@@ -1134,15 +1333,15 @@ impl IntSpan {
         new
     }
 
-    pub fn trim(&self, n: i32) -> Self {
+    pub fn trim(&self, n: i64) -> Self {
         self.inset(n)
     }
 
-    pub fn pad(&self, n: i32) -> Self {
+    pub fn pad(&self, n: i64) -> Self {
         self.inset(-n)
     }
 
-    pub fn excise(&self, min_len: i32) -> Self {
+    pub fn excise(&self, min_len: i64) -> Self {
         let mut new = IntSpan::new();
 
         for i in 0..self.span_size() {
@@ -1158,7 +1357,7 @@ impl IntSpan {
         new
     }
 
-    pub fn fill(&self, max_len: i32) -> Self {
+    pub fn fill(&self, max_len: i64) -> Self {
         let mut new = self.copy();
         let holes = self.holes();
 
@@ -1177,7 +1376,7 @@ impl IntSpan {
 
     /// Removes elements inside the range, and all elements greater than this range are shifted
     /// towards the negative direction
-    pub fn banish(&self, start: i32, end: i32) -> Self {
+    pub fn banish(&self, start: i64, end: i64) -> Self {
         let mut new = IntSpan::new();
         let remove_len = end - start + 1;
 
@@ -1357,7 +1556,7 @@ impl IntSpan {
     /// let other = IntSpan::from("6-10");
     /// assert_eq!(set.overlap(&other), 1);
     /// ```
-    pub fn overlap(&self, other: &Self) -> i32 {
+    pub fn overlap(&self, other: &Self) -> i64 {
         self.intersect(other).cardinality()
     }
 
@@ -1401,7 +1600,7 @@ impl IntSpan {
     /// let other = IntSpan::from("17-20");
     /// assert_eq!(set.distance(&other), 2);
     /// ```
-    pub fn distance(&self, other: &Self) -> i32 {
+    pub fn distance(&self, other: &Self) -> i64 {
         if self.is_empty() || other.is_empty() {
             0
         } else {
@@ -1462,7 +1661,7 @@ impl IntSpan {
     ///     assert_eq!(res.to_string().as_str(), *exp_ints);
     /// }
     /// ```
-    pub fn find_islands_n(&self, val: i32) -> IntSpan {
+    pub fn find_islands_n(&self, val: i64) -> IntSpan {
         let mut island = Self::new();
 
         // if pos & 1, i.e. pos is an odd number, val is in the ints
@@ -1520,7 +1719,7 @@ impl IntSpan {
 /// ----
 impl IntSpan {
     #[inline]
-    pub fn size(&self) -> i32 {
+    pub fn size(&self) -> i64 {
         self.cardinality()
     }
 
@@ -1530,18 +1729,499 @@ impl IntSpan {
     }
 
     #[inline]
-    pub fn elements(&self) -> Vec<i32> {
+    pub fn elements(&self) -> Vec<i64> {
         self.to_vec()
     }
 }
 
+/// INTERFACE: Iterators
+///
+/// ----
+/// ----
+impl IntSpan {
+    /// Iterates over the runs in IntSpan, as Tuple(lower, upper), without allocating
+    ///
+    /// ```
+    /// let ints = intspan::IntSpan::from("1-2,4-7,9");
+    /// let mut iter = ints.iter_spans();
+    /// assert_eq!(iter.len(), 3);
+    /// assert_eq!(iter.next(), Some((1, 2)));
+    /// assert_eq!(iter.next_back(), Some((9, 9)));
+    /// assert_eq!(iter.collect::<Vec<_>>(), vec![(4, 7)]);
+    /// ```
+    pub fn iter_spans(&self) -> Spans<'_> {
+        Spans {
+            edges: &self.edges,
+            front: 0,
+            back: self.span_size(),
+        }
+    }
+
+    /// Iterates over the elements in IntSpan, without allocating
+    ///
+    /// ```
+    /// let ints = intspan::IntSpan::from("1-2,4-5");
+    /// assert_eq!(ints.iter().collect::<Vec<_>>(), vec![1, 2, 4, 5]);
+    /// assert_eq!(ints.iter().rev().next(), Some(5));
+    /// assert_eq!((&ints).into_iter().sum::<i64>(), 12);
+    /// ```
+    pub fn iter(&self) -> Elements<'_> {
+        Elements {
+            spans: self.iter_spans(),
+            front: None,
+            back: None,
+        }
+    }
+}
+
+/// Iterator over the runs of an IntSpan, created by [`IntSpan::iter_spans`]
+#[derive(Debug, Clone)]
+pub struct Spans<'a> {
+    edges: &'a VecDeque<i64>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a> Iterator for Spans<'a> {
+    type Item = (i64, i64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            return None;
+        }
+        let i = self.front;
+        self.front += 1;
+        Some((self.edges[i * 2], self.edges[i * 2 + 1] - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<'a> DoubleEndedIterator for Spans<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            return None;
+        }
+        self.back -= 1;
+        let i = self.back;
+        Some((self.edges[i * 2], self.edges[i * 2 + 1] - 1))
+    }
+}
+
+impl<'a> ExactSizeIterator for Spans<'a> {}
+
+impl<'a> std::iter::FusedIterator for Spans<'a> {}
+
+/// Iterator over the elements of an IntSpan, created by [`IntSpan::iter`]
+#[derive(Debug, Clone)]
+pub struct Elements<'a> {
+    spans: Spans<'a>,
+    // Remaining parts of the spans at both ends, inclusive
+    front: Option<(i64, i64)>,
+    back: Option<(i64, i64)>,
+}
+
+impl<'a> Iterator for Elements<'a> {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((lower, upper)) = self.front {
+                self.front = if lower < upper {
+                    Some((lower + 1, upper))
+                } else {
+                    None
+                };
+                return Some(lower);
+            }
+            match self.spans.next() {
+                Some(span) => self.front = Some(span),
+                // The last span may have been taken by next_back()
+                None => {
+                    let (lower, upper) = self.back?;
+                    self.back = if lower < upper {
+                        Some((lower + 1, upper))
+                    } else {
+                        None
+                    };
+                    return Some(lower);
+                }
+            }
+        }
+    }
+}
+
+impl<'a> DoubleEndedIterator for Elements<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((lower, upper)) = self.back {
+                self.back = if lower < upper {
+                    Some((lower, upper - 1))
+                } else {
+                    None
+                };
+                return Some(upper);
+            }
+            match self.spans.next_back() {
+                Some(span) => self.back = Some(span),
+                None => {
+                    let (lower, upper) = self.front?;
+                    self.front = if lower < upper {
+                        Some((lower, upper - 1))
+                    } else {
+                        None
+                    };
+                    return Some(upper);
+                }
+            }
+        }
+    }
+}
+
+impl<'a> std::iter::FusedIterator for Elements<'a> {}
+
+impl<'a> IntoIterator for &'a IntSpan {
+    type Item = i64;
+    type IntoIter = Elements<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// # Panics
+///
+/// Panics on elements beyond [`get_neg_inf()`](IntSpan::get_neg_inf) and [`get_pos_inf()`](IntSpan::get_pos_inf), like [`Extend`].
+///
+/// ```
+/// # use intspan::IntSpan;
+/// let ints: IntSpan = vec![5, 1, 2, 3, 9].into_iter().collect();
+/// assert_eq!(ints.to_string(), "1-3,5,9");
+/// ```
+impl FromIterator<i64> for IntSpan {
+    fn from_iter<I: IntoIterator<Item = i64>>(iter: I) -> Self {
+        let mut new = Self::new();
+        new.extend(iter);
+        new
+    }
+}
+
+/// # Panics
+///
+/// Panics on elements beyond [`get_neg_inf()`](IntSpan::get_neg_inf) and [`get_pos_inf()`](IntSpan::get_pos_inf),
+/// which are kept for the edges of infinite sets.
+///
+/// ```
+/// # use intspan::IntSpan;
+/// let mut ints = IntSpan::from("1-3");
+/// ints.extend(vec![4, 5, 10]);
+/// assert_eq!(ints.to_string(), "1-5,10");
+/// ints.extend(vec![20, 8, 9, 9, 7, 21]);
+/// assert_eq!(ints.to_string(), "1-5,7-10,20-21");
+/// ```
+impl Extend<i64> for IntSpan {
+    fn extend<I: IntoIterator<Item = i64>>(&mut self, iter: I) {
+        // Consecutive elements are added as a single run
+        let mut run: Option<(i64, i64)> = None;
+        for n in iter {
+            if n < self.get_neg_inf() || n > self.get_pos_inf() {
+                panic!("Element out of range: {}", n)
+            }
+            run = match run {
+                Some((lower, upper)) if n >= lower && n <= upper => Some((lower, upper)),
+                Some((lower, upper)) if upper.checked_add(1) == Some(n) => Some((lower, n)),
+                Some((lower, upper)) => {
+                    self.add_pair(lower, upper);
+                    Some((n, n))
+                }
+                None => Some((n, n)),
+            };
+        }
+        if let Some((lower, upper)) = run {
+            self.add_pair(lower, upper);
+        }
+    }
+}
+
+// Operators on both owned and borrowed IntSpans, see `Set binary operations`
+macro_rules! impl_set_op {
+    ($op:ident, $method:ident, $op_assign:ident, $method_assign:ident, $func:ident) => {
+        impl<'a, 'b> std::ops::$op<&'b IntSpan> for &'a IntSpan {
+            type Output = IntSpan;
+
+            fn $method(self, other: &'b IntSpan) -> IntSpan {
+                self.$func(other)
+            }
+        }
+
+        impl<'b> std::ops::$op<&'b IntSpan> for IntSpan {
+            type Output = IntSpan;
+
+            fn $method(self, other: &'b IntSpan) -> IntSpan {
+                self.$func(other)
+            }
+        }
+
+        impl std::ops::$op<IntSpan> for IntSpan {
+            type Output = IntSpan;
+
+            fn $method(self, other: IntSpan) -> IntSpan {
+                self.$func(&other)
+            }
+        }
+
+        impl<'b> std::ops::$op_assign<&'b IntSpan> for IntSpan {
+            fn $method_assign(&mut self, other: &'b IntSpan) {
+                *self = self.$func(other);
+            }
+        }
+
+        impl std::ops::$op_assign<IntSpan> for IntSpan {
+            fn $method_assign(&mut self, other: IntSpan) {
+                *self = self.$func(&other);
+            }
+        }
+    };
+}
+
+impl_set_op!(BitOr, bitor, BitOrAssign, bitor_assign, union);
+impl_set_op!(BitAnd, bitand, BitAndAssign, bitand_assign, intersect);
+impl_set_op!(Sub, sub, SubAssign, sub_assign, diff);
+impl_set_op!(BitXor, bitxor, BitXorAssign, bitxor_assign, xor);
+
+#[cfg(test)]
+mod traits {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_operators() {
+        let ia = IntSpan::from("1-3,5,8-11");
+        let ib = IntSpan::from("1-6");
+
+        assert_eq!(&ia | &ib, ia.union(&ib));
+        assert_eq!(&ia & &ib, ia.intersect(&ib));
+        assert_eq!(&ia - &ib, ia.diff(&ib));
+        assert_eq!(&ia ^ &ib, ia.xor(&ib));
+        assert_eq!(ia.clone() - ib.clone(), IntSpan::from("8-11"));
+
+        let mut ic = ia.clone();
+        ic &= &ib;
+        assert_eq!(ic.to_string(), "1-3,5");
+        ic ^= IntSpan::from("5-6");
+        assert_eq!(ic.to_string(), "1-3,6");
+    }
+
+    #[test]
+    fn test_eq_hash() {
+        let mut set = HashSet::new();
+        set.insert(IntSpan::from("1-3,5"));
+        set.insert(vec![5, 3, 2, 1].into_iter().collect::<IntSpan>());
+        set.insert(IntSpan::new());
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_serde() {
+        let tests = vec!["-", "1", "1-3,5,8-11", "-5--3,0,2-3"];
+        for runlist in tests {
+            let ints = IntSpan::from(runlist);
+            let s = serde_yaml::to_string(&ints).unwrap();
+            assert_eq!(serde_yaml::from_str::<IntSpan>(&s).unwrap(), ints);
+
+            let ranges = serde_json::to_string(&ints.ranges()).unwrap();
+            assert_eq!(serde_json::from_str::<IntSpan>(&ranges).unwrap(), ints);
+
+            let bytes = bincode::serialize(&ints).unwrap();
+            assert_eq!(bincode::deserialize::<IntSpan>(&bytes).unwrap(), ints);
+        }
+
+        let mut ints = IntSpan::new();
+        ints.invert();
+        let s = serde_json::to_string(&ints).unwrap();
+        assert!(serde_json::from_str::<IntSpan>(&s).unwrap().is_universal());
+
+        // Reversed and overflowed ranges
+        assert!(serde_json::from_str::<IntSpan>("[5, 1]").is_err());
+        assert!(serde_json::from_str::<IntSpan>("[1, 9223372036854775807]").is_err());
+    }
+
+    #[test]
+    fn test_iterators() {
+        let tests = vec!["-", "1", "1-3", "1-3,5,8-11", "-5--3,0,2-3"];
+
+        for runlist in tests {
+            let ints = IntSpan::from(runlist);
+            assert_eq!(ints.iter_spans().collect::<Vec<_>>(), ints.spans());
+            assert_eq!(ints.iter().count() as i64, ints.cardinality());
+
+            let mut rev: Vec<i64> = ints.iter().rev().collect();
+            rev.reverse();
+            assert_eq!(rev, ints.elements());
+
+            // Meet in the middle
+            let mut iter = ints.iter();
+            let mut seen = vec![];
+            while let Some(n) = iter.next() {
+                seen.push(n);
+                if let Some(n) = iter.next_back() {
+                    seen.push(n);
+                }
+            }
+            seen.sort_unstable();
+            assert_eq!(seen, ints.elements());
+        }
+    }
+}
+
+/// INTERFACE: Serde
+///
+/// IntSpans are serialized as runlists, and deserialized from runlists or arrays of ranges
+///
+/// ```
+/// # use intspan::IntSpan;
+/// let ints = IntSpan::from("1-3,5");
+/// assert_eq!(serde_json::to_string(&ints).unwrap(), r#""1-3,5""#);
+///
+/// let ints: IntSpan = serde_json::from_str(r#""1-3,5""#).unwrap();
+/// assert_eq!(ints.to_string(), "1-3,5");
+/// let ints: IntSpan = serde_json::from_str("[1, 3, 5, 5]").unwrap();
+/// assert_eq!(ints.to_string(), "1-3,5");
+///
+/// assert!(serde_json::from_str::<IntSpan>(r#""1-3,x""#).is_err());
+/// assert!(serde_json::from_str::<IntSpan>("[1, 3, 5]").is_err());
+///
+/// let ints: IntSpan = serde_yaml::from_str("5").unwrap();
+/// assert_eq!(ints.to_string(), "5");
+/// ```
+impl serde::Serialize for IntSpan {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for IntSpan {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Only self-describing formats can take numbers or arrays
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(IntSpanVisitor)
+        } else {
+            deserializer.deserialize_str(IntSpanVisitor)
+        }
+    }
+}
+
+struct IntSpanVisitor;
+
+impl<'de> serde::de::Visitor<'de> for IntSpanVisitor {
+    type Value = IntSpan;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "a runlist or an array of ranges")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<IntSpan, E> {
+        IntSpan::try_from(v).map_err(E::custom)
+    }
+
+    // Runlists of a single element may be numbers in YAML
+    fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<IntSpan, E> {
+        ranges_to_intspan(&[v, v]).map_err(E::custom)
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<IntSpan, E> {
+        let v = i64::try_from(v).map_err(E::custom)?;
+        self.visit_i64(v)
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<IntSpan, A::Error> {
+        let mut ranges: Vec<i64> = vec![];
+        while let Some(n) = seq.next_element::<i64>()? {
+            ranges.push(n);
+        }
+        ranges_to_intspan(&ranges).map_err(serde::de::Error::custom)
+    }
+}
+
+// Validate ranges before add_ranges(), which panics
+fn ranges_to_intspan(ranges: &[i64]) -> Result<IntSpan, String> {
+    if !ranges.len().is_multiple_of(2) {
+        return Err("Number of ranges must be even".to_string());
+    }
+
+    let mut new = IntSpan::new();
+    for pair in ranges.chunks(2) {
+        let (lower, upper) = (pair[0], pair[1]);
+        if lower > upper {
+            return Err(format!("Bad order: {},{}", lower, upper));
+        }
+        if lower < new.get_neg_inf() || upper > new.get_pos_inf() {
+            return Err(format!(
+                "Overflow past NEG_INF or POS_INF: {},{}",
+                lower, upper
+            ));
+        }
+        new.add_pair(lower, upper);
+    }
+
+    Ok(new)
+}
+
+/// Serialize IntSpans as arrays of ranges, `[lower, upper, ...]`, like [`IntSpan::ranges`].
+/// Use with `#[serde(with = "intspan::intspan_as_ranges")]`.
+///
+/// ```
+/// # use intspan::IntSpan;
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Gene {
+///     name: String,
+///     #[serde(with = "intspan::intspan_as_ranges")]
+///     exons: IntSpan,
+/// }
+///
+/// let gene = Gene {
+///     name: "g1".to_string(),
+///     exons: IntSpan::from("1-100,201-300"),
+/// };
+/// let s = serde_json::to_string(&gene).unwrap();
+/// assert_eq!(s, r#"{"name":"g1","exons":[1,100,201,300]}"#);
+///
+/// let gene: Gene = serde_json::from_str(&s).unwrap();
+/// assert_eq!(gene.exons.to_string(), "1-100,201-300");
+/// ```
+pub mod intspan_as_ranges {
+    use super::IntSpan;
+    use serde::ser::SerializeSeq;
+
+    pub fn serialize<S: serde::Serializer>(
+        ints: &IntSpan,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(ints.span_size() * 2))?;
+        for (lower, upper) in ints.iter_spans() {
+            seq.serialize_element(&lower)?;
+            seq.serialize_element(&upper)?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<IntSpan, D::Error> {
+        serde::Deserialize::deserialize(deserializer)
+    }
+}
+
 /// Private methods
 ///
 /// ----
 /// ----
 impl IntSpan {
     #[inline]
-    fn find_pos(&self, val: i32, mut low: usize) -> usize {
+    fn find_pos(&self, val: i64, mut low: usize) -> usize {
         let mut high = self.edge_size();
 
         while low < high {
@@ -1557,8 +2237,8 @@ impl IntSpan {
         low
     }
 
-    fn list_to_ranges(&self, array: &[i32]) -> Vec<i32> {
-        let mut ranges: Vec<i32> = Vec::new();
+    fn list_to_ranges(&self, array: &[i64]) -> Vec<i64> {
+        let mut ranges: Vec<i64> = Vec::new();
 
         let mut vec = array.to_owned();
         vec.sort_unstable();
@@ -1580,80 +2260,139 @@ impl IntSpan {
         ranges
     }
 
-    fn runlist_to_ranges(&self, runlist: &str) -> anyhow::Result<Vec<i32>> {
-        let mut ranges: Vec<i32> = Vec::new();
+    // Runs are `lower` or `lower-upper`, separated by commas. Numbers may be negative, e.g. `-2--1`
+    fn runlist_to_ranges(&self, runlist: &str) -> Result<Vec<i64>, RunlistError> {
+        let mut ranges: Vec<i64> = Vec::new();
 
         let bytes = runlist.as_bytes();
-
-        let radix = 10;
         let mut idx = 0; // index in runlist
-        let len = bytes.len();
 
-        let mut lower_is_neg = false;
-        let mut upper_is_neg = false;
-        let mut in_upper = false;
-
-        while idx < len {
-            let mut i = 0; // index in one run
-            if *bytes.get(idx).unwrap() == b'-' {
-                lower_is_neg = true;
-                i += 1;
+        loop {
+            let run_start = idx;
+            let lower = self.parse_number(runlist, &mut idx)?;
+            let upper = if bytes.get(idx) == Some(&b'-') {
+                idx += 1;
+                self.parse_number(runlist, &mut idx)?
+            } else {
+                lower
+            };
+            if lower > upper {
+                return Err(RunlistError::new(
+                    runlist,
+                    run_start,
+                    RunlistErrorKind::Reversed(lower, upper),
+                ));
             }
-
-            // ported from Java Integer.parseInt()
-            let mut lower: i32 = 0;
-            let mut upper: i32 = 0;
-
-            while idx + i < len {
-                let ch = bytes[idx + i];
-                if ch.is_ascii_digit() {
-                    if !in_upper {
-                        lower *= radix;
-                        lower -= (ch as char).to_digit(10).unwrap() as i32;
-                    } else {
-                        upper *= radix;
-                        upper -= (ch as char).to_digit(10).unwrap() as i32;
-                    }
-                } else if ch == b'-' {
-                    if !in_upper {
-                        in_upper = true;
-                        if *bytes.get(idx + i + 1).unwrap() == b'-' {
-                            upper_is_neg = true;
-                        }
+            ranges.push(lower);
+            ranges.push(upper);
+
+            match bytes.get(idx) {
+                None => break,
+                Some(b',') => {
+                    idx += 1;
+                    // A trailing comma is accepted, as in earlier versions
+                    if idx == bytes.len() {
+                        break;
                     }
-                } else if ch == b',' {
-                    i += 1;
-                    break; // end of run
-                } else {
-                    return Err(anyhow!(
-                        "Number format error: {} at {} of {}",
-                        ch as char,
-                        idx + i,
-                        runlist
-                    ));
                 }
-
-                i += 1;
+                Some(&ch) => {
+                    return Err(RunlistError::new(
+                        runlist,
+                        idx,
+                        RunlistErrorKind::InvalidChar(ch as char),
+                    ))
+                }
             }
+        }
 
-            if !in_upper {
-                ranges.push(if lower_is_neg { lower } else { -lower }); // add lower
-                ranges.push(if lower_is_neg { lower } else { -lower }); // add lower again
-            } else {
-                ranges.push(if lower_is_neg { lower } else { -lower }); // add lower
-                ranges.push(if upper_is_neg { upper } else { -upper }); // add upper
+        Ok(ranges)
+    }
+
+    fn parse_number(&self, runlist: &str, idx: &mut usize) -> Result<i64, RunlistError> {
+        let bytes = runlist.as_bytes();
+        let start = *idx;
+
+        let is_neg = bytes.get(*idx) == Some(&b'-');
+        if is_neg {
+            *idx += 1;
+        }
+
+        // Accumulate negatively, so NEG_INF is reachable
+        let mut n: i64 = 0;
+        let mut is_overflow = false;
+        let digits_start = *idx;
+        while let Some(ch) = bytes.get(*idx).filter(|ch| ch.is_ascii_digit()) {
+            match n
+                .checked_mul(10)
+                .and_then(|n| n.checked_sub((ch - b'0') as i64))
+            {
+                Some(x) => n = x,
+                None => is_overflow = true,
             }
+            *idx += 1;
+        }
 
-            // reset boolean flags
-            lower_is_neg = false;
-            upper_is_neg = false;
-            in_upper = false;
+        if *idx == digits_start {
+            let kind = match bytes.get(*idx) {
+                None | Some(b',') | Some(b'-') => RunlistErrorKind::MissingNumber,
+                Some(&ch) => RunlistErrorKind::InvalidChar(ch as char),
+            };
+            return Err(RunlistError::new(runlist, *idx, kind));
+        }
 
-            // start next run
-            idx += i;
+        let n = if is_neg { Some(n) } else { n.checked_neg() };
+        match n {
+            Some(n) if !is_overflow && n >= self.get_neg_inf() && n <= self.get_pos_inf() => Ok(n),
+            _ => Err(RunlistError::new(
+                runlist,
+                start,
+                RunlistErrorKind::Overflow,
+            )),
         }
+    }
+}
 
-        Ok(ranges)
+/// Constructed from a runlist, or a [`RunlistError`] with the position and the reason
+///
+/// ```
+/// # use intspan::{IntSpan, RunlistErrorKind};
+/// use std::convert::TryFrom;
+/// let ints = IntSpan::try_from("1-3,5").unwrap();
+/// assert_eq!(ints.cardinality(), 4);
+/// assert!(IntSpan::try_from("-").unwrap().is_empty());
+///
+/// let err = IntSpan::try_from("1-3,7-5").unwrap_err();
+/// assert_eq!(err.pos, 4);
+/// assert_eq!(err.kind, RunlistErrorKind::Reversed(7, 5));
+///
+/// let err = IntSpan::try_from("1-9223372036854775807").unwrap_err();
+/// assert_eq!(err.kind, RunlistErrorKind::Overflow);
+/// ```
+impl TryFrom<&str> for IntSpan {
+    type Error = RunlistError;
+
+    fn try_from(runlist: &str) -> Result<Self, Self::Error> {
+        let mut new = Self::new();
+        if !runlist.is_empty() && !runlist.eq(&*EMPTY_STRING) {
+            let ranges = new.runlist_to_ranges(runlist)?;
+            new.add_ranges(&ranges);
+        }
+
+        Ok(new)
+    }
+}
+
+/// ```
+/// # use intspan::IntSpan;
+/// let ints: IntSpan = "1-3,5".parse().unwrap();
+/// assert_eq!(ints.to_string(), "1-3,5");
+/// assert!("1-3,abc".parse::<IntSpan>().is_err());
+/// ```
+impl std::str::FromStr for IntSpan {
+    type Err = RunlistError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
     }
 }
 
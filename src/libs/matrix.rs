@@ -113,24 +113,33 @@ where
             self.data
                 .get(&(row, col))
                 .copied()
-                .unwrap_or_else(|| self.same.unwrap_or(T::default()))
+                .unwrap_or_else(|| self.same.unwrap_or_default())
         } else {
             let (r, c) = if row < col { (row, col) } else { (col, row) };
             self.data
                 .get(&(r, c))
                 .copied()
-                .unwrap_or_else(|| self.missing.unwrap_or(T::default()))
+                .unwrap_or_else(|| self.missing.unwrap_or_default())
         }
     }
 }
 
+impl<T> Default for ScoringMatrix<T>
+where
+    T: Default + Copy,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // Add a separate implementation for f32 specifically for from_pair_scores
 impl ScoringMatrix<f32> {
     pub fn from_pair_scores(infile: &str, same: f32, missing: f32) -> (Self, Vec<String>) {
         let mut names = indexmap::IndexSet::new();
         let mut matrix = Self::with_defaults(same, missing);
 
-        let reader = crate::reader(infile);
+        let reader = crate::reader(infile).unwrap();
         for line in reader.lines().map_while(Result::ok) {
             let fields: Vec<&str> = line.split('\t').collect();
             if fields.len() >= 3 {
@@ -319,7 +328,7 @@ impl NamedMatrix {
         let mut names = Vec::new();
         let mut values = Vec::new();
 
-        let reader = crate::reader(infile);
+        let reader = crate::reader(infile).unwrap();
         let mut lines = reader.lines();
 
         // Skip the optional sequence count line
@@ -350,7 +359,7 @@ impl NamedMatrix {
     }
 
     fn process_phylip_line(line: &str, names: &mut Vec<String>, values: &mut Vec<f32>) {
-        let parts: Vec<&str> = line.trim().split_whitespace().collect();
+        let parts: Vec<&str> = line.split_whitespace().collect();
         if !parts.is_empty() {
             let name = parts[0].to_string();
             names.push(name);
@@ -1,5 +1,13 @@
+pub mod bigwig;
 pub mod coverage;
+pub mod error;
+pub mod fasta;
+pub mod genome;
+pub mod gff;
+pub mod intmap;
 pub mod intspan;
 pub mod linalg;
 pub mod matrix;
 pub mod range;
+pub mod range_index;
+pub mod rlb;
@@ -1,4 +1,4 @@
-use crate::IntSpan;
+use crate::{InputError, IntSpan};
 use regex::Regex;
 use std::collections::HashMap;
 use std::fmt;
@@ -8,8 +8,8 @@ pub struct Range {
     pub name: String,
     pub chr: String,
     pub strand: String,
-    pub start: i32,
-    pub end: i32,
+    pub start: i64,
+    pub end: i64,
 }
 
 lazy_static! {
@@ -38,10 +38,10 @@ impl Range {
     pub fn strand(&self) -> &String {
         &self.strand
     }
-    pub fn start(&self) -> &i32 {
+    pub fn start(&self) -> &i64 {
         &self.start
     }
-    pub fn end(&self) -> &i32 {
+    pub fn end(&self) -> &i64 {
         &self.end
     }
 
@@ -72,7 +72,7 @@ impl Range {
     /// # assert_eq!(*range.start(), 1);
     /// # assert_eq!(*range.end(), 100);
     /// ```
-    pub fn from(chr: &str, start: i32, end: i32) -> Self {
+    pub fn from(chr: &str, start: i64, end: i64) -> Self {
         Self {
             name: "".to_string(),
             chr: chr.to_string(),
@@ -93,7 +93,7 @@ impl Range {
     /// # assert_eq!(*range.start(), 1);
     /// # assert_eq!(*range.end(), 100);
     /// ```
-    pub fn from_full(name: &str, chr: &str, strand: &str, start: i32, end: i32) -> Self {
+    pub fn from_full(name: &str, chr: &str, strand: &str, start: i64, end: i64) -> Self {
         Self {
             name: name.to_string(),
             chr: chr.to_string(),
@@ -107,37 +107,127 @@ impl Range {
     ///
     /// ```
     /// # use intspan::Range;
-    /// let range = Range::from_str("I:1-100");
+    /// let range = Range::from_str_lenient("I:1-100");
     /// # assert_eq!(*range.chr(), "I");
     /// # assert_eq!(*range.start(), 1);
     /// # assert_eq!(*range.end(), 100);
     /// # assert_eq!(range.to_string(), "I:1-100");
-    /// let range = Range::from_str("I:100");
+    /// let range = Range::from_str_lenient("I:100");
     /// # assert_eq!(*range.chr(), "I");
     /// # assert_eq!(*range.start(), 100);
     /// # assert_eq!(*range.end(), 100);
     /// # assert_eq!(range.to_string(), "I:100");
-    /// let range = Range::from_str("S288c.I(-):27070-29557");
+    /// let range = Range::from_str_lenient("S288c.I(-):27070-29557");
     /// # assert_eq!(*range.name(), "S288c");
     /// # assert_eq!(*range.strand(), "-");
     /// # assert_eq!(range.to_string(), "S288c.I(-):27070-29557");
+    /// let range = Range::from_str_lenient("1:3000000001-3000000100");
+    /// # assert_eq!(*range.start(), 3000000001);
+    /// # assert_eq!(range.intspan().cardinality(), 100);
     /// ```
-    pub fn from_str(range: &str) -> Self {
+    ///
+    /// Malformed strings give an invalid range, see [`Range::is_valid`].
+    /// Use [`str::parse`] to get an error instead.
+    ///
+    /// ```
+    /// # use intspan::Range;
+    /// assert!(!Range::from_str_lenient("I:abc").is_valid());
+    /// assert!(!Range::from_str_lenient("I:99999999999999999999").is_valid());
+    /// ```
+    pub fn from_str_lenient(range: &str) -> Self {
         let mut new = Self::new();
         new.decode(range);
 
         new
     }
 
+    /// Constructed from string, leniently. Renamed to [`Range::from_str_lenient`],
+    /// as it shadows the strict [`FromStr`](std::str::FromStr) implementation.
+    #[deprecated(note = "use `Range::from_str_lenient()`, or `str::parse()` to get an error")]
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(range: &str) -> Self {
+        Self::from_str_lenient(range)
+    }
+
+    /// Constructed from a BED line.
+    /// BED starts are 0-based and ends are exclusive, so only the start is shifted by 1.
+    /// The strand is taken from the 6th column when it is `+` or `-`.
+    ///
+    /// ```
+    /// # use intspan::Range;
+    /// let range = Range::from_bed("I\t0\t100");
+    /// # assert_eq!(*range.chr(), "I");
+    /// # assert_eq!(*range.start(), 1);
+    /// # assert_eq!(*range.end(), 100);
+    /// assert_eq!(range.to_string(), "I:1-100");
+    /// let range = Range::from_bed("I\t189\t200\tfeature\t0\t-");
+    /// assert_eq!(range.to_string(), "I(-):190-200");
+    /// let range = Range::from_bed("I\t189\t200\tfeature\t0\t.");
+    /// assert_eq!(range.to_string(), "I:190-200");
+    /// let range = Range::from_bed("track name=test");
+    /// assert!(!range.is_valid());
+    /// let range = Range::from_bed("I\t100\t100");
+    /// assert!(!range.is_valid());
+    /// ```
+    pub fn from_bed(line: &str) -> Self {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 3 {
+            return Self::new();
+        }
+
+        let (start, end) = match (fields[1].parse::<i64>(), fields[2].parse::<i64>()) {
+            (Ok(start), Ok(end)) if start >= 0 && end > start => (start + 1, end),
+            _ => return Self::new(),
+        };
+
+        let strand = match fields.get(5) {
+            Some(&"+") => "+",
+            Some(&"-") => "-",
+            _ => "",
+        };
+
+        Self {
+            name: "".to_string(),
+            chr: fields[0].to_string(),
+            strand: strand.to_string(),
+            start,
+            end,
+        }
+    }
+
+    /// To a BED line.
+    /// Ranges with a strand are written as BED6, using `.` for the name and `0` for the score.
+    ///
+    /// ```
+    /// # use intspan::Range;
+    /// let range = Range::from_str_lenient("I:1-100");
+    /// assert_eq!(range.to_bed(), "I\t0\t100");
+    /// let range = Range::from_str_lenient("S288c.I(-):190-200");
+    /// assert_eq!(range.to_bed(), "I\t189\t200\t.\t0\t-");
+    /// ```
+    pub fn to_bed(&self) -> String {
+        if self.strand.is_empty() {
+            format!("{}\t{}\t{}", self.chr, self.start - 1, self.end)
+        } else {
+            format!(
+                "{}\t{}\t{}\t.\t0\t{}",
+                self.chr,
+                self.start - 1,
+                self.end,
+                self.strand
+            )
+        }
+    }
+
     /// Valid or not
     ///
     /// ```
     /// # use intspan::Range;
     /// let range = Range::from("I", 1, 100);
     /// assert!(range.is_valid());
-    /// let range = Range::from_str("I:100");
+    /// let range = Range::from_str_lenient("I:100");
     /// assert!(range.is_valid());
-    /// let range = Range::from_str("invalid");
+    /// let range = Range::from_str_lenient("invalid");
     /// assert!(!range.is_valid());
     /// ```
     pub fn is_valid(&self) -> bool {
@@ -150,7 +240,7 @@ impl Range {
     /// # use intspan::Range;
     /// let range = Range::from("I", 1, 100);
     /// assert_eq!(range.intspan().to_string(), "1-100");
-    /// let range = Range::from_str("I:100");
+    /// let range = Range::from_str_lenient("I:100");
     /// assert_eq!(range.intspan().to_string(), "100");
     /// ```
     pub fn intspan(&self) -> IntSpan {
@@ -161,12 +251,12 @@ impl Range {
     ///
     /// ```
     /// # use intspan::Range;
-    /// let range = Range::from_str("I:100-200");
+    /// let range = Range::from_str_lenient("I:100-200");
     /// assert_eq!(range.trim(30).to_string(), "I:130-170");
     /// assert_eq!(range.trim(70).is_valid(), false);
     /// assert_eq!(range.trim(-30).to_string(), "I:70-230");
     /// ```
-    pub fn trim(&self, n: i32) -> Self {
+    pub fn trim(&self, n: i64) -> Self {
         let mut start = self.start + n;
         let mut end = self.end - n;
         Self::check(&mut start, &mut end);
@@ -184,14 +274,14 @@ impl Range {
     ///
     /// ```
     /// # use intspan::Range;
-    /// let range = Range::from_str("I(+):100-200");
+    /// let range = Range::from_str_lenient("I(+):100-200");
     /// assert_eq!(range.trim_5p(30).to_string(), "I(+):130-200");
-    /// let range = Range::from_str("I(-):100-200");
+    /// let range = Range::from_str_lenient("I(-):100-200");
     /// assert_eq!(range.trim_5p(30).to_string(), "I(-):100-170");
     /// assert_eq!(range.trim_5p(-30).to_string(), "I(-):100-230");
     /// assert_eq!(range.trim_5p(120).is_valid(), false);
     /// ```
-    pub fn trim_5p(&self, n: i32) -> Self {
+    pub fn trim_5p(&self, n: i64) -> Self {
         let mut start = if self.strand == "-" {
             self.start
         } else {
@@ -217,13 +307,13 @@ impl Range {
     ///
     /// ```
     /// # use intspan::Range;
-    /// let range = Range::from_str("I(+):100-200");
+    /// let range = Range::from_str_lenient("I(+):100-200");
     /// assert_eq!(range.trim_3p(30).to_string(), "I(+):100-170");
-    /// let range = Range::from_str("I(-):100-200");
+    /// let range = Range::from_str_lenient("I(-):100-200");
     /// assert_eq!(range.trim_3p(30).to_string(), "I(-):130-200");
     /// assert_eq!(range.trim_3p(120).is_valid(), false);
     /// ```
-    pub fn trim_3p(&self, n: i32) -> Self {
+    pub fn trim_3p(&self, n: i64) -> Self {
         let mut start = if self.strand == "-" {
             self.start + n
         } else {
@@ -249,13 +339,13 @@ impl Range {
     ///
     /// ```
     /// # use intspan::Range;
-    /// let range = Range::from_str("I(+):100-200");
+    /// let range = Range::from_str_lenient("I(+):100-200");
     /// assert_eq!(range.shift_5p(30).to_string(), "I(+):70-170");
     /// assert_eq!(range.shift_5p(-30).to_string(), "I(+):130-230");
-    /// let range = Range::from_str("I(-):100-200");
+    /// let range = Range::from_str_lenient("I(-):100-200");
     /// assert_eq!(range.shift_5p(30).to_string(), "I(-):130-230");
     /// ```
-    pub fn shift_5p(&self, n: i32) -> Self {
+    pub fn shift_5p(&self, n: i64) -> Self {
         let mut start = if self.strand == "-" {
             self.start + n
         } else {
@@ -281,13 +371,13 @@ impl Range {
     ///
     /// ```
     /// # use intspan::Range;
-    /// let range = Range::from_str("I(+):100-200");
+    /// let range = Range::from_str_lenient("I(+):100-200");
     /// assert_eq!(range.shift_3p(30).to_string(), "I(+):130-230");
     /// assert_eq!(range.shift_3p(-30).to_string(), "I(+):70-170");
-    /// let range = Range::from_str("I(-):100-200");
+    /// let range = Range::from_str_lenient("I(-):100-200");
     /// assert_eq!(range.shift_3p(30).to_string(), "I(-):70-170");
     /// ```
-    pub fn shift_3p(&self, n: i32) -> Self {
+    pub fn shift_3p(&self, n: i64) -> Self {
         self.shift_5p(-n)
     }
 
@@ -296,16 +386,16 @@ impl Range {
     ///
     /// ```
     /// # use intspan::Range;
-    /// let range = Range::from_str("I(+):100-200");
+    /// let range = Range::from_str_lenient("I(+):100-200");
     /// assert_eq!(range.flank_5p(30).to_string(), "I(+):70-99");
     /// assert_eq!(range.flank_5p(-30).to_string(), "I(+):100-129");
     /// assert_eq!(range.flank_5p(0).is_valid(), false);
-    /// let range = Range::from_str("I(-):100-200");
+    /// let range = Range::from_str_lenient("I(-):100-200");
     /// assert_eq!(range.flank_5p(30).to_string(), "I(-):201-230");
     /// assert_eq!(range.flank_5p(-30).to_string(), "I(-):171-200");
     /// assert_eq!(range.flank_5p(0).is_valid(), false);
     /// ```
-    pub fn flank_5p(&self, n: i32) -> Self {
+    pub fn flank_5p(&self, n: i64) -> Self {
         let mut start = if n > 0 {
             if self.strand == "-" {
                 self.end + 1
@@ -343,16 +433,16 @@ impl Range {
     ///
     /// ```
     /// # use intspan::Range;
-    /// let range = Range::from_str("I(+):100-200");
+    /// let range = Range::from_str_lenient("I(+):100-200");
     /// assert_eq!(range.flank_3p(30).to_string(), "I(+):201-230");
     /// assert_eq!(range.flank_3p(-30).to_string(), "I(+):171-200");
     /// assert_eq!(range.flank_3p(0).is_valid(), false);
-    /// let range = Range::from_str("I(-):100-200");
+    /// let range = Range::from_str_lenient("I(-):100-200");
     /// assert_eq!(range.flank_3p(30).to_string(), "I(-):70-99");
     /// assert_eq!(range.flank_3p(-30).to_string(), "I(-):100-129");
     /// assert_eq!(range.flank_3p(0).is_valid(), false);
     /// ```
-    pub fn flank_3p(&self, n: i32) -> Self {
+    pub fn flank_3p(&self, n: i64) -> Self {
         let mut start = if n > 0 {
             if self.strand == "-" {
                 self.start - n
@@ -404,8 +494,9 @@ impl Range {
                 "name" => self.name = dict.get(key).unwrap().to_owned(),
                 "chr" => self.chr = dict.get(key).unwrap().to_owned(),
                 "strand" => self.strand = dict.get(key).unwrap().to_owned(),
-                "start" => self.start = dict.get(key).unwrap().parse::<i32>().unwrap(),
-                "end" => self.end = dict.get(key).unwrap().parse::<i32>().unwrap(),
+                // Overflowed numbers give invalid ranges
+                "start" => self.start = dict.get(key).unwrap().parse::<i64>().unwrap_or(0),
+                "end" => self.end = dict.get(key).unwrap().parse::<i64>().unwrap_or(0),
                 _ => {}
             }
         }
@@ -446,7 +537,7 @@ impl Range {
         header
     }
 
-    fn check(start: &mut i32, end: &mut i32) {
+    fn check(start: &mut i64, end: &mut i64) {
         if *start < 0 {
             *start = 0;
         }
@@ -460,6 +551,168 @@ impl Range {
     }
 }
 
+/// Parse a whole string as a range, optionally followed by `|`-separated properties.
+/// Unlike [`Range::from_str_lenient`], other trailing characters, a zero start and reversed ranges are errors.
+///
+/// ```
+/// # use intspan::Range;
+/// let range: Range = "S288c.I(-):27070-29557".parse().unwrap();
+/// assert_eq!(range.to_string(), "S288c.I(-):27070-29557");
+///
+/// let err = "I:100-abc".parse::<Range>().unwrap_err();
+/// assert_eq!(err.to_string(), "invalid range: `I:100-abc`");
+/// assert!("I:0-100".parse::<Range>().is_err());
+/// assert!("I:200-100".parse::<Range>().is_err());
+/// assert!("I".parse::<Range>().is_err());
+/// assert!("I:50-60xyz".parse::<Range>().is_err());
+///
+/// let range: Range = "S288c.I(-):190-200|Species=Yeast".parse().unwrap();
+/// assert_eq!(range.to_string(), "S288c.I(-):190-200");
+/// ```
+impl std::str::FromStr for Range {
+    type Err = InputError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let is_whole = RE
+            .find(s)
+            .map(|m| m.start() == 0 && (m.end() == s.len() || s[m.end()..].starts_with('|')))
+            .unwrap_or(false);
+
+        let range = Range::from_str_lenient(s);
+        if !is_whole || !range.is_valid() || range.end < range.start {
+            return Err(InputError::parse("range", s));
+        }
+
+        Ok(range)
+    }
+}
+
+/// Ranges are serialized as strings, and deserialized from strings or structs
+///
+/// ```
+/// # use intspan::Range;
+/// let range = Range::from_str_lenient("S288c.I(-):190-200");
+/// assert_eq!(serde_json::to_string(&range).unwrap(), r#""S288c.I(-):190-200""#);
+///
+/// let range: Range = serde_json::from_str(r#""S288c.I(-):190-200""#).unwrap();
+/// assert_eq!(*range.start(), 190);
+/// let range: Range = serde_json::from_str(r#"{"chr": "I", "start": 1, "end": 100}"#).unwrap();
+/// assert_eq!(range.to_string(), "I:1-100");
+///
+/// assert!(serde_json::from_str::<Range>(r#""I:100-abc""#).is_err());
+/// assert!(serde_json::from_str::<Range>(r#"{"chr": "I", "start": 0, "end": 100}"#).is_err());
+///
+/// // Non-self-describing formats
+/// let range = Range::from_str_lenient("S288c.I(-):190-200");
+/// let bytes = bincode::serialize(&range).unwrap();
+/// let range: Range = bincode::deserialize(&bytes).unwrap();
+/// assert_eq!(range.to_string(), "S288c.I(-):190-200");
+/// ```
+impl serde::Serialize for Range {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Range {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Only self-describing formats can take the struct form
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(RangeVisitor)
+        } else {
+            deserializer.deserialize_str(RangeVisitor)
+        }
+    }
+}
+
+struct RangeVisitor;
+
+impl<'de> serde::de::Visitor<'de> for RangeVisitor {
+    type Value = Range;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a range string or a struct of chr, start and end")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Range, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, map: A) -> Result<Range, A::Error> {
+        let fields: RangeFields =
+            serde::Deserialize::deserialize(serde::de::value::MapAccessDeserializer::new(map))?;
+        fields.into_range().map_err(serde::de::Error::custom)
+    }
+}
+
+// The struct form
+#[derive(serde::Serialize, serde::Deserialize)]
+struct RangeFields {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    name: String,
+    chr: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    strand: String,
+    start: i64,
+    end: i64,
+}
+
+impl RangeFields {
+    fn into_range(self) -> Result<Range, InputError> {
+        let range = Range::from_full(&self.name, &self.chr, &self.strand, self.start, self.end);
+        if !range.is_valid() || range.end < range.start {
+            return Err(InputError::parse("range", &range.to_string()));
+        }
+
+        Ok(range)
+    }
+}
+
+/// Serialize Ranges as structs of `name`, `chr`, `strand`, `start` and `end`.
+/// Empty names and strands are omitted.
+/// Use with `#[serde(with = "intspan::range_as_struct")]`.
+///
+/// ```
+/// # use intspan::Range;
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Hit {
+///     #[serde(with = "intspan::range_as_struct")]
+///     range: Range,
+/// }
+///
+/// let hit = Hit {
+///     range: Range::from_str_lenient("I(+):1-100"),
+/// };
+/// let s = serde_json::to_string(&hit).unwrap();
+/// assert_eq!(s, r#"{"range":{"chr":"I","strand":"+","start":1,"end":100}}"#);
+///
+/// let hit: Hit = serde_json::from_str(&s).unwrap();
+/// assert_eq!(hit.range.to_string(), "I(+):1-100");
+/// ```
+pub mod range_as_struct {
+    use super::{Range, RangeFields};
+
+    pub fn serialize<S: serde::Serializer>(
+        range: &Range,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let fields = RangeFields {
+            name: range.name.clone(),
+            chr: range.chr.clone(),
+            strand: range.strand.clone(),
+            start: range.start,
+            end: range.end,
+        };
+        serde::Serialize::serialize(&fields, serializer)
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Range, D::Error> {
+        serde::Deserialize::deserialize(deserializer)
+    }
+}
+
 /// To string
 ///
 /// ```
@@ -485,7 +738,7 @@ fn fa_headers() {
         ("infile_0/1/0_514:19-25", "infile_0/1/0_514:19-25"),
     ];
     for (header, expected) in tests {
-        let range = Range::from_str(header);
+        let range = Range::from_str_lenient(header);
         assert_eq!(range.to_string(), expected);
     }
 }
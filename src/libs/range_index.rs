@@ -0,0 +1,389 @@
+use crate::Range;
+use rust_lapper::{Interval, Lapper};
+use std::collections::BTreeMap;
+use std::iter::FromIterator;
+
+// Interval: represent a range from [start, stop), carrying the index of the item
+type Iv = Interval<u64, usize>;
+
+/// Ranges of one chromosome
+#[derive(Debug, Clone)]
+struct ChrIndex {
+    lapper: Lapper<u64, usize>,
+    // (end, item index), sorted by ends
+    ends: Vec<(i64, usize)>,
+    // (start, item index), sorted by starts
+    starts: Vec<(i64, usize)>,
+}
+
+/// An interval index of Ranges, grouped by chromosomes.
+/// Each range carries a payload, e.g. the line it comes from.
+///
+/// Positions are 1-based and inclusive, as in `Range`.
+///
+/// ```
+/// # use intspan::{Range, RangeIndex};
+/// let index: RangeIndex<&str> = vec![
+///     (Range::from_str_lenient("I:1-100"), "a"),
+///     (Range::from_str_lenient("I(+):90-150"), "b"),
+///     (Range::from_str_lenient("I(-):190-200"), "c"),
+///     (Range::from_str_lenient("II:21294-22075"), "d"),
+/// ]
+/// .into_iter()
+/// .collect();
+///
+/// assert_eq!(index.len(), 4);
+/// assert_eq!(index.count(&Range::from_str_lenient("I:95-100")), 2);
+///
+/// let vals: Vec<&str> = index
+///     .find(&Range::from_str_lenient("I:100-189"))
+///     .iter()
+///     .map(|(_, v)| **v)
+///     .collect();
+/// assert_eq!(vals, vec!["a", "b"]);
+///
+/// let nearest = index.nearest(&Range::from_str_lenient("I:160-170"));
+/// assert_eq!(nearest.len(), 1);
+/// assert_eq!(*nearest[0].1, "b");
+/// ```
+#[derive(Debug, Clone)]
+pub struct RangeIndex<T> {
+    items: Vec<(Range, T)>,
+    index_of: BTreeMap<String, ChrIndex>,
+}
+
+impl<T> Default for RangeIndex<T> {
+    fn default() -> Self {
+        Self {
+            items: vec![],
+            index_of: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> FromIterator<(Range, T)> for RangeIndex<T> {
+    fn from_iter<I: IntoIterator<Item = (Range, T)>>(iter: I) -> Self {
+        Self::from_ranges(iter)
+    }
+}
+
+impl<T> RangeIndex<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build the index. Invalid ranges are dropped.
+    pub fn from_ranges<I: IntoIterator<Item = (Range, T)>>(iter: I) -> Self {
+        let items: Vec<(Range, T)> = iter
+            .into_iter()
+            .filter(|(range, _)| range.is_valid())
+            .collect();
+
+        // chr => Vector of Intervals
+        let mut ivs_of: BTreeMap<String, Vec<Iv>> = BTreeMap::new();
+        for (i, (range, _)) in items.iter().enumerate() {
+            let iv = Iv {
+                start: *range.start() as u64,
+                stop: *range.end() as u64 + 1,
+                val: i,
+            };
+            ivs_of.entry(range.chr().to_string()).or_default().push(iv);
+        }
+
+        let mut index_of: BTreeMap<String, ChrIndex> = BTreeMap::new();
+        for (chr, ivs) in ivs_of {
+            let mut starts: Vec<(i64, usize)> =
+                ivs.iter().map(|iv| (iv.start as i64, iv.val)).collect();
+            starts.sort_unstable();
+            let mut ends: Vec<(i64, usize)> =
+                ivs.iter().map(|iv| (iv.stop as i64 - 1, iv.val)).collect();
+            ends.sort_unstable();
+
+            let lapper = Lapper::new(ivs);
+            index_of.insert(
+                chr,
+                ChrIndex {
+                    lapper,
+                    ends,
+                    starts,
+                },
+            );
+        }
+
+        Self { items, index_of }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Chromosomes in the index, in order
+    pub fn chrs(&self) -> Vec<&String> {
+        self.index_of.keys().collect()
+    }
+
+    /// All ranges and payloads, in the order of insertion
+    pub fn items(&self) -> &[(Range, T)] {
+        &self.items
+    }
+
+    /// Ranges overlapping with `range`, ordered by positions. Strands are ignored.
+    pub fn find(&self, range: &Range) -> Vec<(&Range, &T)> {
+        let mut idxs: Vec<usize> = match self.index_of.get(range.chr()) {
+            Some(index) => index
+                .lapper
+                .find(*range.start() as u64, *range.end() as u64 + 1)
+                .map(|iv| iv.val)
+                .collect(),
+            None => vec![],
+        };
+        idxs.sort_by_key(|i| (*self.items[*i].0.start(), *self.items[*i].0.end(), *i));
+
+        idxs.into_iter()
+            .map(|i| (&self.items[i].0, &self.items[i].1))
+            .collect()
+    }
+
+    /// Number of ranges overlapping with `range`
+    ///
+    /// ```
+    /// # use intspan::{Range, RangeIndex};
+    /// let index = RangeIndex::from_ranges(vec![
+    ///     (Range::from_str_lenient("I:1-100"), ()),
+    ///     (Range::from_str_lenient("I:101-200"), ()),
+    /// ]);
+    /// assert_eq!(index.count(&Range::from_str_lenient("I:100")), 1);
+    /// assert_eq!(index.count(&Range::from_str_lenient("I:100-101")), 2);
+    /// assert_eq!(index.count(&Range::from_str_lenient("I:201-300")), 0);
+    /// assert_eq!(index.count(&Range::from_str_lenient("II:1-100")), 0);
+    /// ```
+    pub fn count(&self, range: &Range) -> usize {
+        match self.index_of.get(range.chr()) {
+            Some(index) => index
+                .lapper
+                .count(*range.start() as u64, *range.end() as u64 + 1),
+            None => 0,
+        }
+    }
+
+    /// Nearest ranges of `range`. Strands are ignored.
+    ///
+    /// Overlapping ranges are returned if there are any.
+    /// Otherwise, ranges with the smallest distance on either side are returned, ties included.
+    /// The distance of adjacent ranges is 1, as in `IntSpan::distance()`.
+    ///
+    /// ```
+    /// # use intspan::{Range, RangeIndex};
+    /// let index = RangeIndex::from_ranges(vec![
+    ///     (Range::from_str_lenient("I:1-100"), 1),
+    ///     (Range::from_str_lenient("I:50-100"), 2),
+    ///     (Range::from_str_lenient("I:200-300"), 3),
+    /// ]);
+    /// let vals = |rg| {
+    ///     index
+    ///         .nearest(&Range::from_str_lenient(rg))
+    ///         .iter()
+    ///         .map(|(_, v)| **v)
+    ///         .collect::<Vec<_>>()
+    /// };
+    /// assert_eq!(vals("I:150"), vec![1, 2, 3]);
+    /// assert_eq!(vals("I:140"), vec![1, 2]);
+    /// assert_eq!(vals("I:160-170"), vec![3]);
+    /// assert_eq!(vals("I:20-30"), vec![1]);
+    /// assert_eq!(vals("I:1000"), vec![3]);
+    /// assert!(vals("II:1000").is_empty());
+    /// ```
+    pub fn nearest(&self, range: &Range) -> Vec<(&Range, &T)> {
+        let mut hits = self.closest(range, 1, true);
+        hits.sort_by_key(|(r, _, _)| (*r.start(), *r.end()));
+
+        hits.into_iter().map(|(r, v, _)| (r, v)).collect()
+    }
+
+    /// The `k` closest ranges of `range` with their distances, sorted by distances and positions.
+    /// Ranges tied with the last one are also returned, so there may be more than `k` of them.
+    ///
+    /// Overlapping ranges have a distance of 0 and are skipped if `is_overlap` is false.
+    /// Strands are ignored.
+    ///
+    /// ```
+    /// # use intspan::{Range, RangeIndex};
+    /// let index = RangeIndex::from_ranges(vec![
+    ///     (Range::from_str_lenient("I:1-100"), 1),
+    ///     (Range::from_str_lenient("I:150-160"), 2),
+    ///     (Range::from_str_lenient("I:200-300"), 3),
+    ///     (Range::from_str_lenient("I:400-500"), 4),
+    /// ]);
+    /// let hits = |rg, k, is_overlap| {
+    ///     index
+    ///         .closest(&Range::from_str_lenient(rg), k, is_overlap)
+    ///         .iter()
+    ///         .map(|(_, v, d)| (**v, *d))
+    ///         .collect::<Vec<_>>()
+    /// };
+    /// assert_eq!(hits("I:155", 1, true), vec![(2, 0)]);
+    /// assert_eq!(hits("I:155", 1, false), vec![(3, 45)]);
+    /// assert_eq!(hits("I:155", 3, true), vec![(2, 0), (3, 45), (1, 55)]);
+    /// assert_eq!(hits("I:130", 1, true), vec![(2, 20)]);
+    /// assert_eq!(hits("I:125", 1, true), vec![(1, 25), (2, 25)]);
+    /// assert_eq!(hits("I:1000", 2, true), vec![(4, 500), (3, 700)]);
+    /// assert!(hits("II:1000", 2, true).is_empty());
+    /// ```
+    pub fn closest(&self, range: &Range, k: usize, is_overlap: bool) -> Vec<(&Range, &T, i64)> {
+        let index = match self.index_of.get(range.chr()) {
+            Some(x) => x,
+            None => return vec![],
+        };
+        if k == 0 {
+            return vec![];
+        }
+
+        // (distance, item index)
+        let mut hits: Vec<(i64, usize)> = vec![];
+        if is_overlap {
+            for iv in index
+                .lapper
+                .find(*range.start() as u64, *range.end() as u64 + 1)
+            {
+                hits.push((0, iv.val));
+            }
+        }
+
+        // Ranges on the left side, ending before `range`, with increasing distances
+        let pos = index.ends.partition_point(|e| e.0 < *range.start());
+        let mut lefts = index.ends[..pos]
+            .iter()
+            .rev()
+            .map(|e| (*range.start() - e.0, e.1))
+            .peekable();
+
+        // Ranges on the right side, starting after `range`, with increasing distances
+        let pos = index.starts.partition_point(|e| e.0 <= *range.end());
+        let mut rights = index.starts[pos..]
+            .iter()
+            .map(|e| (e.0 - *range.end(), e.1))
+            .peekable();
+
+        // Merge two sorted streams, until `k` ranges are collected and ties are exhausted
+        loop {
+            let next = match (lefts.peek(), rights.peek()) {
+                (Some(l), Some(r)) => {
+                    if l.0 <= r.0 {
+                        lefts.next()
+                    } else {
+                        rights.next()
+                    }
+                }
+                (Some(_), None) => lefts.next(),
+                (None, Some(_)) => rights.next(),
+                (None, None) => None,
+            };
+            let next = match next {
+                Some(x) => x,
+                None => break,
+            };
+
+            if hits.len() >= k && next.0 > hits.last().unwrap().0 {
+                break;
+            }
+            hits.push(next);
+        }
+
+        // Overlaps may be more than `k`, and they're all ties
+        hits.sort_by_key(|(d, i)| (*d, *self.items[*i].0.start(), *self.items[*i].0.end(), *i));
+
+        hits.into_iter()
+            .map(|(d, i)| (&self.items[i].0, &self.items[i].1, d))
+            .collect()
+    }
+
+    /// Depth of coverage along the chromosome, as (start, end, depth).
+    /// Only regions covered by ranges are returned.
+    ///
+    /// ```
+    /// # use intspan::{Range, RangeIndex};
+    /// let index = RangeIndex::from_ranges(vec![
+    ///     (Range::from_str_lenient("I:1-100"), ()),
+    ///     (Range::from_str_lenient("I:90-150"), ()),
+    ///     (Range::from_str_lenient("I:190-200"), ()),
+    /// ]);
+    /// assert_eq!(
+    ///     index.depth("I"),
+    ///     vec![(1, 89, 1), (90, 100, 2), (101, 150, 1), (190, 200, 1)]
+    /// );
+    /// assert!(index.depth("II").is_empty());
+    /// ```
+    pub fn depth(&self, chr: &str) -> Vec<(i64, i64, usize)> {
+        match self.index_of.get(chr) {
+            Some(index) => index
+                .lapper
+                .depth()
+                .map(|iv| (iv.start as i64, iv.stop as i64 - 1, iv.val as usize))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// Depth of coverage between consecutive boundaries of ranges, as (start, end, depth).
+    /// Unlike `depth()`, adjacent steps may have equal depths.
+    ///
+    /// ```
+    /// # use intspan::{Range, RangeIndex};
+    /// let index = RangeIndex::from_ranges(vec![
+    ///     (Range::from_str_lenient("I:1-100"), ()),
+    ///     (Range::from_str_lenient("I:90-150"), ()),
+    ///     (Range::from_str_lenient("I:151-200"), ()),
+    ///     (Range::from_str_lenient("I:301-400"), ()),
+    /// ]);
+    /// assert_eq!(
+    ///     index.depth_steps("I"),
+    ///     vec![
+    ///         (1, 89, 1),
+    ///         (90, 100, 2),
+    ///         (101, 150, 1),
+    ///         (151, 200, 1),
+    ///         (301, 400, 1)
+    ///     ]
+    /// );
+    /// assert!(index.depth_steps("II").is_empty());
+    /// ```
+    pub fn depth_steps(&self, chr: &str) -> Vec<(i64, i64, usize)> {
+        let index = match self.index_of.get(chr) {
+            Some(x) => x,
+            None => return vec![],
+        };
+
+        // Boundaries are the first position of each step
+        let mut boundaries: Vec<i64> = index
+            .starts
+            .iter()
+            .map(|e| e.0)
+            .chain(index.ends.iter().map(|e| e.0 + 1))
+            .collect();
+        boundaries.sort_unstable();
+        boundaries.dedup();
+
+        let mut steps = vec![];
+        let (mut i, mut j) = (0, 0);
+        let mut depth: usize = 0;
+        for w in boundaries.windows(2) {
+            while i < index.starts.len() && index.starts[i].0 == w[0] {
+                depth += 1;
+                i += 1;
+            }
+            while j < index.ends.len() && index.ends[j].0 + 1 == w[0] {
+                depth -= 1;
+                j += 1;
+            }
+            if depth > 0 {
+                steps.push((w[0], w[1] - 1, depth));
+            }
+        }
+
+        steps
+    }
+}
@@ -0,0 +1,400 @@
+//! A compact binary format of runlist files, `.rlb`.
+//!
+//! The layout mirrors JSON runlist files, single, multi-level or stranded:
+//!
+//! * magic `RLB`, version (u8) and flags (u8, bit 0 for a zlib compressed body)
+//! * body, a node
+//!     * map: tag `1`, varint number of entries, then keys (varint length and UTF-8 bytes) and nodes
+//!     * runlist: tag `0`, varint number of spans, then each span as a zigzag varint of the gap
+//!       to the previous upper bound and a varint of its length minus one
+//!
+//! Spans are sorted and disjoint, so gaps and lengths stay small for genomic coordinates.
+
+use anyhow::{anyhow, bail};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+use crate::{GenomeSet, GenomeSets, IntSpan};
+
+const RLB_MAGIC: &[u8; 3] = b"RLB";
+const RLB_VERSION: u8 = 1;
+const FLAG_ZLIB: u8 = 1;
+
+const TAG_RUNLIST: u8 = 0;
+const TAG_MAP: u8 = 1;
+
+/// Is the file a binary runlist file
+///
+/// ```
+/// assert!(intspan::is_rlb("genome.rlb"));
+/// assert!(!intspan::is_rlb("genome.json"));
+/// ```
+pub fn is_rlb(input: &str) -> bool {
+    input.to_lowercase().ends_with(".rlb")
+}
+
+/// Encode a runlist JSON into bytes
+///
+/// ```
+/// use serde_json::json;
+/// use std::collections::BTreeMap;
+/// let json: BTreeMap<String, serde_json::Value> = serde_json::from_value(json!({
+///     "I": "1-100,201-300",
+///     "II": "-",
+/// }))
+/// .unwrap();
+///
+/// let bytes = intspan::encode_rlb(&json, false).unwrap();
+/// assert_eq!(&bytes[..3], b"RLB");
+/// assert_eq!(intspan::decode_rlb(&bytes).unwrap(), json);
+///
+/// let bytes = intspan::encode_rlb(&json, true).unwrap();
+/// assert_eq!(intspan::decode_rlb(&bytes).unwrap(), json);
+/// ```
+pub fn encode_rlb(json: &BTreeMap<String, Value>, is_compressed: bool) -> anyhow::Result<Vec<u8>> {
+    let mut body: Vec<u8> = vec![];
+    encode_map(&mut body, json)?;
+
+    let mut bytes: Vec<u8> = vec![];
+    bytes.extend_from_slice(RLB_MAGIC);
+    bytes.push(RLB_VERSION);
+    if is_compressed {
+        bytes.push(FLAG_ZLIB);
+        let mut encoder = ZlibEncoder::new(bytes, Compression::default());
+        encoder.write_all(&body)?;
+        bytes = encoder.finish()?;
+    } else {
+        bytes.push(0);
+        bytes.extend(body);
+    }
+
+    Ok(bytes)
+}
+
+/// Decode bytes into a runlist JSON
+///
+/// ```
+/// assert!(intspan::decode_rlb(b"{}").is_err());
+/// assert!(intspan::decode_rlb(b"RLB\x01\x00\x01\x01").is_err());
+/// ```
+pub fn decode_rlb(bytes: &[u8]) -> anyhow::Result<BTreeMap<String, Value>> {
+    Ok(decode_body(bytes)?
+        .into_iter()
+        .map(|(key, node)| (key, node.into_value()))
+        .collect())
+}
+
+/// Decode bytes of a single or multi-level runlist file into GenomeSets, without formatting
+/// and parsing runlists
+///
+/// ```
+/// use serde_json::json;
+/// use std::collections::BTreeMap;
+/// let json: BTreeMap<String, serde_json::Value> = serde_json::from_value(json!({
+///     "Col": {"I": "1-100,201-300", "II": "-"},
+///     "Ler": {"I": "51-150"},
+/// }))
+/// .unwrap();
+///
+/// let bytes = intspan::encode_rlb(&json, true).unwrap();
+/// let sets = intspan::decode_rlb_sets(&bytes).unwrap();
+/// assert_eq!(sets.names().count(), 2);
+/// assert_eq!(sets["Col"]["I"].to_string(), "1-100,201-300");
+/// assert_eq!(sets.to_json(), json);
+///
+/// let bytes = intspan::encode_rlb(&intspan::read_json("tests/spanr/brca2.json").unwrap(), true).unwrap();
+/// assert!(intspan::decode_rlb_sets(&bytes).unwrap().is_single());
+/// ```
+pub fn decode_rlb_sets(bytes: &[u8]) -> anyhow::Result<GenomeSets> {
+    let entries = decode_body(bytes)?;
+
+    // Multi-level runlists are detected by the first entry, like `json2set_m()`
+    let is_multi = matches!(entries.first(), Some((_, Node::Map(_))));
+    if !is_multi {
+        return Ok(GenomeSets::single(node2set(entries, None)?));
+    }
+
+    let mut sets = GenomeSets::new();
+    for (name, node) in entries {
+        match node {
+            Node::Map(map) => sets.insert(&name, node2set(map, Some(&name))?),
+            Node::Runlist(_) => bail!("{}: runlists should be maps", name),
+        }
+    }
+
+    Ok(sets)
+}
+
+/// Read a binary runlist file into GenomeSets. See `decode_rlb_sets()`
+pub fn read_rlb(input: &str) -> anyhow::Result<GenomeSets> {
+    let mut bytes = vec![];
+    crate::reader(input)?
+        .read_to_end(&mut bytes)
+        .map_err(|e| anyhow!("{}: {}", input, e))?;
+    decode_rlb_sets(&bytes).map_err(|e| anyhow!("{}: {}", input, e))
+}
+
+// A decoded runlist or map
+enum Node {
+    Runlist(IntSpan),
+    Map(Vec<(String, Node)>),
+}
+
+impl Node {
+    fn into_value(self) -> Value {
+        match self {
+            Node::Runlist(ints) => Value::String(ints.to_string()),
+            Node::Map(map) => Value::Object(
+                map.into_iter()
+                    .map(|(key, node)| (key, node.into_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+// Entries of a single-level map, with the name of the map in errors
+fn node2set(entries: Vec<(String, Node)>, name: Option<&str>) -> anyhow::Result<GenomeSet> {
+    let mut set = GenomeSet::new();
+    for (chr, node) in entries {
+        match node {
+            Node::Runlist(ints) => set.insert(&chr, ints),
+            Node::Map(_) => match name {
+                Some(name) => bail!("{}.{}: runlists should be strings", name, chr),
+                None => bail!("{}: runlists should be strings", chr),
+            },
+        }
+    }
+
+    Ok(set)
+}
+
+// Entries of the top-level map
+fn decode_body(bytes: &[u8]) -> anyhow::Result<Vec<(String, Node)>> {
+    if bytes.len() < 5 || &bytes[..3] != RLB_MAGIC {
+        bail!("Not a binary runlist file");
+    }
+    if bytes[3] != RLB_VERSION {
+        bail!("Unsupported version of binary runlist files: {}", bytes[3]);
+    }
+
+    let mut body: Vec<u8> = vec![];
+    if bytes[4] & FLAG_ZLIB != 0 {
+        ZlibDecoder::new(&bytes[5..]).read_to_end(&mut body)?;
+    } else {
+        body.extend_from_slice(&bytes[5..]);
+    }
+
+    let mut pos = 0;
+    let node = decode_node(&body, &mut pos)?;
+    if pos != body.len() {
+        bail!("Trailing bytes in binary runlist file");
+    }
+    match node {
+        Node::Map(map) => Ok(map),
+        _ => Err(anyhow!(
+            "The top level of binary runlist file should be a map"
+        )),
+    }
+}
+
+fn encode_map<'a, I>(buf: &mut Vec<u8>, map: I) -> anyhow::Result<()>
+where
+    I: IntoIterator<Item = (&'a String, &'a Value)>,
+    I::IntoIter: ExactSizeIterator,
+{
+    let iter = map.into_iter();
+    buf.push(TAG_MAP);
+    write_varint(buf, iter.len() as u64);
+    for (key, value) in iter {
+        write_varint(buf, key.len() as u64);
+        buf.extend_from_slice(key.as_bytes());
+        match value {
+            Value::String(runlist) => {
+                let ints = runlist
+                    .parse::<IntSpan>()
+                    .map_err(|e| anyhow!("{}: {}", key, e))?;
+                encode_runlist(buf, &ints)
+            }
+            Value::Object(map) => encode_map(buf, map)?,
+            _ => bail!("Runlists should be strings or maps: {}", key),
+        }
+    }
+
+    Ok(())
+}
+
+fn encode_runlist(buf: &mut Vec<u8>, ints: &IntSpan) {
+    let spans = ints.spans();
+    buf.push(TAG_RUNLIST);
+    write_varint(buf, spans.len() as u64);
+
+    // Wrapping arithmetic keeps infinite sets exact
+    let mut prev: i64 = 0;
+    for (lower, upper) in spans {
+        write_varint(buf, zigzag(lower.wrapping_sub(prev)));
+        write_varint(buf, upper.wrapping_sub(lower) as u64);
+        prev = upper;
+    }
+}
+
+fn decode_node(buf: &[u8], pos: &mut usize) -> anyhow::Result<Node> {
+    let tag = *buf
+        .get(*pos)
+        .ok_or_else(|| anyhow!("Unexpected end of binary runlist file"))?;
+    *pos += 1;
+
+    match tag {
+        TAG_RUNLIST => {
+            let n = read_varint(buf, pos)?;
+            let mut ints = IntSpan::new();
+            let mut prev: i64 = 0;
+            for _ in 0..n {
+                // Deltas wrap as in encode_runlist(), so bounds are checked on the results
+                let lower = prev.wrapping_add(unzigzag(read_varint(buf, pos)?));
+                let upper = lower.wrapping_add(read_varint(buf, pos)? as i64);
+                if lower < ints.get_neg_inf() || upper > ints.get_pos_inf() {
+                    bail!("Spans of binary runlist file are out of bounds");
+                }
+                let is_after = match prev.checked_add(1) {
+                    Some(next) => lower > next,
+                    None => false,
+                };
+                if upper < lower || (ints.edge_size() > 0 && !is_after) {
+                    bail!("Spans of binary runlist file are not sorted");
+                }
+                ints.add_pair(lower, upper);
+                prev = upper;
+            }
+            Ok(Node::Runlist(ints))
+        }
+        TAG_MAP => {
+            let n = read_varint(buf, pos)?;
+            let mut map = vec![];
+            for _ in 0..n {
+                let len = read_varint(buf, pos)? as usize;
+                let key = pos
+                    .checked_add(len)
+                    .and_then(|end| buf.get(*pos..end))
+                    .ok_or_else(|| anyhow!("Unexpected end of binary runlist file"))?;
+                let key = String::from_utf8(key.to_vec())?;
+                *pos += len;
+                let value = decode_node(buf, pos)?;
+                map.push((key, value));
+            }
+            Ok(Node::Map(map))
+        }
+        _ => Err(anyhow!("Unknown tag in binary runlist file: {}", tag)),
+    }
+}
+
+fn zigzag(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
+
+fn unzigzag(n: u64) -> i64 {
+    ((n >> 1) as i64) ^ -((n & 1) as i64)
+}
+
+// LEB128
+fn write_varint(buf: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        buf.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    buf.push(n as u8);
+}
+
+fn read_varint(buf: &[u8], pos: &mut usize) -> anyhow::Result<u64> {
+    let mut n: u64 = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *buf
+            .get(*pos)
+            .ok_or_else(|| anyhow!("Unexpected end of binary runlist file"))?;
+        *pos += 1;
+        if shift > 63 {
+            bail!("Varint overflow in binary runlist file");
+        }
+        n |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(n);
+        }
+        shift += 7;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn varint_round_trip() {
+        for n in [0u64, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut buf = vec![];
+            write_varint(&mut buf, n);
+            let mut pos = 0;
+            assert_eq!(read_varint(&buf, &mut pos).unwrap(), n);
+            assert_eq!(pos, buf.len());
+        }
+        for n in [0i64, 1, -1, 1000, -1000, i64::MAX, i64::MIN] {
+            assert_eq!(unzigzag(zigzag(n)), n);
+        }
+    }
+
+    #[test]
+    fn multi_level_and_infinite() {
+        let mut ints = IntSpan::new();
+        ints.add_pair(ints.get_neg_inf(), -10);
+        ints.add_pair(5, ints.get_pos_inf());
+
+        let json: BTreeMap<String, Value> = serde_json::from_value(serde_json::json!({
+            "Atha": {"1": "1-10,20-30", "2": "-"},
+            "inf": {"1": ints.to_string()},
+            "stranded": {"I": {"+": "1-100", "-": "90-150"}},
+        }))
+        .unwrap();
+
+        let bytes = encode_rlb(&json, false).unwrap();
+        assert_eq!(decode_rlb(&bytes).unwrap(), json);
+
+        // Truncated
+        assert!(decode_rlb(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn corrupt_spans() {
+        // A map of one key to the spans, uncompressed
+        let rlb = |spans: &[(i64, i64)]| {
+            let mut bytes = RLB_MAGIC.to_vec();
+            bytes.extend([RLB_VERSION, 0, TAG_MAP, 1, 1, b'1', TAG_RUNLIST]);
+            write_varint(&mut bytes, spans.len() as u64);
+            let mut prev: i64 = 0;
+            for (lower, upper) in spans {
+                write_varint(&mut bytes, zigzag(lower.wrapping_sub(prev)));
+                write_varint(&mut bytes, upper.wrapping_sub(*lower) as u64);
+                prev = *upper;
+            }
+            bytes
+        };
+
+        assert!(decode_rlb(&rlb(&[(1, 10), (20, 30)])).is_ok());
+        assert!(decode_rlb(&rlb(&[(1, 10), (5, 30)])).is_err());
+        assert!(decode_rlb(&rlb(&[(1, i64::MAX)])).is_err());
+        assert!(decode_rlb(&rlb(&[(i64::MIN, 10)])).is_err());
+        assert!(decode_rlb(&rlb(&[(1, i64::MAX), (i64::MIN, 10)])).is_err());
+    }
+
+    #[test]
+    fn smaller_than_json() {
+        let json = crate::read_json("tests/spanr/Atha.json").unwrap();
+        let s = serde_json::to_string(&json).unwrap();
+        let bytes = encode_rlb(&json, false).unwrap();
+        assert!(bytes.len() < s.len() / 2);
+        assert_eq!(decode_rlb(&bytes).unwrap(), json);
+    }
+}
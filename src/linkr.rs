@@ -15,7 +15,9 @@ fn main() -> anyhow::Result<()> {
         .subcommand(cmd_linkr::sort::make_subcommand())
         .subcommand(cmd_linkr::filter::make_subcommand())
         .subcommand(cmd_linkr::clean::make_subcommand())
-        .subcommand(cmd_linkr::connect::make_subcommand());
+        .subcommand(cmd_linkr::connect::make_subcommand())
+        .subcommand(cmd_linkr::bedpe2link::make_subcommand())
+        .subcommand(cmd_linkr::link2bedpe::make_subcommand());
 
     // Check which subcomamnd the user ran...
     match app.get_matches().subcommand() {
@@ -24,9 +26,10 @@ fn main() -> anyhow::Result<()> {
         Some(("filter", sub_matches)) => cmd_linkr::filter::execute(sub_matches),
         Some(("clean", sub_matches)) => cmd_linkr::clean::execute(sub_matches),
         Some(("connect", sub_matches)) => cmd_linkr::connect::execute(sub_matches),
+        Some(("bedpe2link", sub_matches)) => cmd_linkr::bedpe2link::execute(sub_matches),
+        Some(("link2bedpe", sub_matches)) => cmd_linkr::link2bedpe::execute(sub_matches),
         _ => unreachable!(),
-    }
-    .unwrap();
+    }?;
 
     Ok(())
 }
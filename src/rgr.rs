@@ -12,10 +12,12 @@ fn main() -> anyhow::Result<()> {
         .propagate_version(true)
         .arg_required_else_help(true)
         .color(ColorChoice::Auto)
+        .subcommand(cmd_rgr::closest::make_subcommand())
         .subcommand(cmd_rgr::count::make_subcommand())
         .subcommand(cmd_rgr::dedup::make_subcommand())
         .subcommand(cmd_rgr::field::make_subcommand())
         .subcommand(cmd_rgr::filter::make_subcommand())
+        .subcommand(cmd_rgr::intersect::make_subcommand())
         .subcommand(cmd_rgr::keep::make_subcommand())
         .subcommand(cmd_rgr::md::make_subcommand())
         .subcommand(cmd_rgr::merge::make_subcommand())
@@ -24,8 +26,10 @@ fn main() -> anyhow::Result<()> {
         .subcommand(cmd_rgr::replace::make_subcommand())
         .subcommand(cmd_rgr::runlist::make_subcommand())
         .subcommand(cmd_rgr::select::make_subcommand())
+        .subcommand(cmd_rgr::seq::make_subcommand())
         .subcommand(cmd_rgr::sort::make_subcommand())
         .subcommand(cmd_rgr::span::make_subcommand())
+        .subcommand(cmd_rgr::window::make_subcommand())
         .after_help(
             r###"
 File formats
@@ -38,7 +42,7 @@ Subcommand groups:
 * Generic .tsv
     * dedup / keep / md / replace / filter / select
 * Single range field
-    * field / sort / count / prop / span / runlist
+    * field / sort / count / closest / window / intersect / prop / span / runlist / seq
 * Multiple range fields
     * merge / pl-2rmp
 
@@ -58,17 +62,18 @@ Subcommand groups:
         Some(("field", sub_matches)) => cmd_rgr::field::execute(sub_matches),
         Some(("sort", sub_matches)) => cmd_rgr::sort::execute(sub_matches),
         Some(("count", sub_matches)) => cmd_rgr::count::execute(sub_matches),
+        Some(("closest", sub_matches)) => cmd_rgr::closest::execute(sub_matches),
+        Some(("window", sub_matches)) => cmd_rgr::window::execute(sub_matches),
+        Some(("intersect", sub_matches)) => cmd_rgr::intersect::execute(sub_matches),
         Some(("prop", sub_matches)) => cmd_rgr::prop::execute(sub_matches),
         Some(("span", sub_matches)) => cmd_rgr::span::execute(sub_matches),
         Some(("runlist", sub_matches)) => cmd_rgr::runlist::execute(sub_matches),
+        Some(("seq", sub_matches)) => cmd_rgr::seq::execute(sub_matches),
         // Multiple range fields
         Some(("merge", sub_matches)) => cmd_rgr::merge::execute(sub_matches),
         Some(("pl-2rmp", sub_matches)) => cmd_rgr::pl_2rmp::execute(sub_matches),
         _ => unreachable!(),
-    }
-    .unwrap();
+    }?;
 
     Ok(())
 }
-
-// TODO: --bed for `rgr field`
@@ -12,6 +12,8 @@ fn main() -> anyhow::Result<()> {
         .arg_required_else_help(true)
         .color(ColorChoice::Auto)
         .subcommand(cmd_spanr::genome::make_subcommand())
+        .subcommand(cmd_spanr::window::make_subcommand())
+        .subcommand(cmd_spanr::winstat::make_subcommand())
         .subcommand(cmd_spanr::some::make_subcommand())
         .subcommand(cmd_spanr::merge::make_subcommand())
         .subcommand(cmd_spanr::split::make_subcommand())
@@ -23,11 +25,14 @@ fn main() -> anyhow::Result<()> {
         .subcommand(cmd_spanr::cover::make_subcommand())
         .subcommand(cmd_spanr::coverage::make_subcommand())
         .subcommand(cmd_spanr::gff::make_subcommand())
+        .subcommand(cmd_spanr::features::make_subcommand())
         .subcommand(cmd_spanr::convert::make_subcommand());
 
     // Check which subcomamnd the user ran...
     match app.get_matches().subcommand() {
         Some(("genome", sub_matches)) => cmd_spanr::genome::execute(sub_matches),
+        Some(("window", sub_matches)) => cmd_spanr::window::execute(sub_matches),
+        Some(("winstat", sub_matches)) => cmd_spanr::winstat::execute(sub_matches),
         Some(("some", sub_matches)) => cmd_spanr::some::execute(sub_matches),
         Some(("merge", sub_matches)) => cmd_spanr::merge::execute(sub_matches),
         Some(("split", sub_matches)) => cmd_spanr::split::execute(sub_matches),
@@ -39,10 +44,10 @@ fn main() -> anyhow::Result<()> {
         Some(("cover", sub_matches)) => cmd_spanr::cover::execute(sub_matches),
         Some(("coverage", sub_matches)) => cmd_spanr::coverage::execute(sub_matches),
         Some(("gff", sub_matches)) => cmd_spanr::gff::execute(sub_matches),
+        Some(("features", sub_matches)) => cmd_spanr::features::execute(sub_matches),
         Some(("convert", sub_matches)) => cmd_spanr::convert::execute(sub_matches),
         _ => unreachable!(),
-    }
-    .unwrap();
+    }?;
 
     Ok(())
 }
@@ -60,5 +65,5 @@ fn main() -> anyhow::Result<()> {
 // res: result, single name IntSpan set
 //      BTreeMap<String, IntSpan>
 // res_of: BTreeMap<String, BTreeMap<String, IntSpan>>
-// sizes: chr.sizes, BTreeMap<String, i32>
+// sizes: chr.sizes, BTreeMap<String, i64>
 // iv_of: BTreeMap<String, Vec<Iv>>
@@ -1,4 +1,4 @@
-use crate::{IntSpan, Range};
+use crate::{decode_rlb, encode_rlb, is_rlb, FaidxReader, InputError, IntSpan, Range};
 use anyhow::anyhow;
 use path_clean::PathClean;
 use std::cmp::Reverse;
@@ -7,25 +7,25 @@ use std::io::{BufRead, BufReader, BufWriter, Read, Write};
 
 /// ```
 /// use std::io::BufRead;
-/// let reader = intspan::reader("tests/spanr/S288c.chr.sizes");
+/// let reader = intspan::reader("tests/spanr/S288c.chr.sizes").unwrap();
 /// let mut lines = vec![];
 /// for line in reader.lines() {
 ///     lines.push(line);
 /// }
 /// assert_eq!(lines.len(), 16);
 ///
-/// let reader = intspan::reader("tests/spanr/S288c.chr.sizes");
+/// let reader = intspan::reader("tests/spanr/S288c.chr.sizes").unwrap();
 /// assert_eq!(reader.lines().collect::<Vec<_>>().len(), 16);
+///
+/// let err = intspan::reader("tests/spanr/not_exists.txt").err().unwrap();
+/// assert!(err.to_string().starts_with("could not open tests/spanr/not_exists.txt: "));
 /// ```
-pub fn reader(input: &str) -> Box<dyn BufRead> {
+pub fn reader(input: &str) -> Result<Box<dyn BufRead>, InputError> {
     let reader: Box<dyn BufRead> = if input == "stdin" {
         Box::new(BufReader::new(std::io::stdin()))
     } else {
         let path = std::path::Path::new(input);
-        let file = match std::fs::File::open(path) {
-            Err(why) => panic!("could not open {}: {}", path.display(), why),
-            Ok(file) => file,
-        };
+        let file = std::fs::File::open(path).map_err(|e| InputError::file(input, e))?;
 
         if path.extension() == Some(std::ffi::OsStr::new("gz")) {
             Box::new(BufReader::new(flate2::read::MultiGzDecoder::new(file)))
@@ -34,65 +34,95 @@ pub fn reader(input: &str) -> Box<dyn BufRead> {
         }
     };
 
-    reader
+    Ok(reader)
 }
 
 /// ```
-/// let lines = intspan::read_lines("tests/spanr/S288c.chr.sizes");
+/// let lines = intspan::read_lines("tests/spanr/S288c.chr.sizes").unwrap();
 /// assert_eq!(lines.len(), 16);
 /// ```
-pub fn read_lines(input: &str) -> Vec<String> {
-    let mut reader = reader(input);
-    let mut s = String::new();
-    reader.read_to_string(&mut s).expect("Read error");
-    s.lines().map(|s| s.to_string()).collect::<Vec<String>>()
+pub fn read_lines(input: &str) -> anyhow::Result<Vec<String>> {
+    let reader = reader(input)?;
+    let mut lines = vec![];
+    for line in reader.lines() {
+        lines.push(line.map_err(|e| anyhow!("{}: {}", input, e))?);
+    }
+
+    Ok(lines)
 }
 
+/// Parse a size in bytes, with an optional suffix of K, M or G
+///
+/// ```
+/// assert_eq!(intspan::parse_size("100").unwrap(), 100);
+/// assert_eq!(intspan::parse_size("64K").unwrap(), 64 * 1024);
+/// assert_eq!(intspan::parse_size("1g").unwrap(), 1024 * 1024 * 1024);
+/// assert!(intspan::parse_size("1T").is_err());
+/// assert!(intspan::parse_size("").is_err());
+/// assert!(intspan::parse_size("99999999999999999999G").is_err());
+/// assert!(intspan::parse_size(&format!("{}G", usize::MAX / 1024)).is_err());
 /// ```
-/// let sizes = intspan::read_sizes("tests/spanr/S288c.chr.sizes");
+pub fn parse_size(size: &str) -> anyhow::Result<usize> {
+    let size = size.trim().to_uppercase();
+    let (num, unit) = match size.chars().last() {
+        Some('K') => (&size[..size.len() - 1], 1024),
+        Some('M') => (&size[..size.len() - 1], 1024 * 1024),
+        Some('G') => (&size[..size.len() - 1], 1024 * 1024 * 1024),
+        _ => (&size[..], 1),
+    };
+    num.parse::<usize>()
+        .ok()
+        .and_then(|num| num.checked_mul(unit))
+        .ok_or_else(|| anyhow!("Invalid size: {}", size))
+}
+
+/// ```
+/// let sizes = intspan::read_sizes("tests/spanr/S288c.chr.sizes").unwrap();
 /// assert_eq!(sizes.len(), 16);
 /// assert_eq!(*sizes.get("II").unwrap(), 813184);
 /// ```
-pub fn read_sizes(input: &str) -> BTreeMap<String, i32> {
-    let mut sizes: BTreeMap<String, i32> = BTreeMap::new();
+pub fn read_sizes(input: &str) -> anyhow::Result<BTreeMap<String, i64>> {
+    let mut sizes: BTreeMap<String, i64> = BTreeMap::new();
 
-    for line in read_lines(input) {
+    for (i, line) in read_lines(input)?.iter().enumerate() {
         let fields: Vec<&str> = line.split('\t').collect();
         if fields.len() == 2 {
-            sizes.insert(fields[0].to_string(), fields[1].parse::<i32>().unwrap());
+            let size = fields[1].parse::<i64>().map_err(|_| {
+                InputError::parse("chromosome size", fields[1]).at(input, i + 1, Some(2))
+            })?;
+            sizes.insert(fields[0].to_string(), size);
         }
     }
 
-    sizes
+    Ok(sizes)
 }
 
 /// ```
-/// let chrs = intspan::read_first_column("tests/spanr/S288c.chr.sizes");
+/// let chrs = intspan::read_first_column("tests/spanr/S288c.chr.sizes").unwrap();
 /// assert_eq!(chrs.len(), 16);
 /// assert_eq!(*chrs.get(1).unwrap(), "II");
 /// assert_eq!(*chrs.get(15).unwrap(), "XVI");
 /// ```
-pub fn read_first_column(input: &str) -> Vec<String> {
-    let reader = reader(input);
+pub fn read_first_column(input: &str) -> anyhow::Result<Vec<String>> {
     let mut rows: Vec<String> = Vec::new();
 
-    for line in reader.lines() {
-        let field = line.unwrap().split('\t').next().unwrap().to_string();
+    for line in read_lines(input)? {
+        let field = line.split('\t').next().unwrap().to_string();
         rows.push(field);
     }
 
-    rows
+    Ok(rows)
 }
 
 /// ```
-/// let replaces = intspan::read_replaces("tests/spanr/S288c.chr.sizes");
+/// let replaces = intspan::read_replaces("tests/spanr/S288c.chr.sizes").unwrap();
 /// assert_eq!(replaces.len(), 16);
 /// assert_eq!(*replaces.get("II").unwrap().get(0).unwrap(), "813184");
 /// ```
-pub fn read_replaces(input: &str) -> BTreeMap<String, Vec<String>> {
+pub fn read_replaces(input: &str) -> anyhow::Result<BTreeMap<String, Vec<String>>> {
     let mut replaces: BTreeMap<String, Vec<String>> = BTreeMap::new();
 
-    for line in read_lines(input) {
+    for line in read_lines(input)? {
         let mut fields: Vec<&str> = line.split('\t').collect();
 
         let left = fields.split_off(1);
@@ -103,29 +133,118 @@ pub fn read_replaces(input: &str) -> BTreeMap<String, Vec<String>> {
         );
     }
 
-    replaces
+    Ok(replaces)
 }
 
-pub fn read_json(input: &str) -> BTreeMap<String, serde_json::Value> {
-    let mut reader = reader(input);
+/// Read a runlist file
+///
+/// * `.rlb` files are read as binary runlist files
+/// * `.yml` and `.yaml` files are read as YAML, e.g. files of the Perl `runlist` tools
+///
+/// ```
+/// let json = intspan::read_json("tests/spanr/Atha.yml").unwrap();
+/// assert_eq!(json, intspan::read_json("tests/spanr/Atha.json").unwrap());
+///
+/// let err = intspan::read_json("tests/spanr/S288c.rg").err().unwrap();
+/// assert!(err.to_string().starts_with("tests/spanr/S288c.rg: "));
+///
+/// let err = intspan::read_json("tests/spanr/sequence.yml").err().unwrap();
+/// assert_eq!(
+///     err.to_string(),
+///     "tests/spanr/sequence.yml: Runlists should be strings or maps: II"
+/// );
+/// ```
+pub fn read_json(input: &str) -> anyhow::Result<BTreeMap<String, serde_json::Value>> {
+    let mut reader = reader(input)?;
+    if is_rlb(input) {
+        let mut bytes = vec![];
+        reader
+            .read_to_end(&mut bytes)
+            .map_err(|e| anyhow!("{}: {}", input, e))?;
+        return decode_rlb(&bytes).map_err(|e| anyhow!("{}: {}", input, e));
+    }
+
     let mut s = String::new();
-    reader.read_to_string(&mut s).expect("Read error");
+    reader
+        .read_to_string(&mut s)
+        .map_err(|e| anyhow!("{}: {}", input, e))?;
+
+    if is_yaml(input) {
+        let yaml: serde_yaml::Value =
+            serde_yaml::from_str(&s).map_err(|e| anyhow!("{}: {}", input, e))?;
+        return match yaml2json(yaml, "").map_err(|e| anyhow!("{}: {}", input, e))? {
+            serde_json::Value::Object(map) => Ok(map.into_iter().collect()),
+            _ => Err(anyhow!("{}: not a runlist file", input)),
+        };
+    }
+
+    serde_json::from_str(&s).map_err(|e| anyhow!("{}: {}", input, e))
+}
+
+/// ```
+/// assert!(intspan::is_yaml("tests/rgr/intergenic.yml"));
+/// assert!(intspan::is_yaml("Atha.YAML"));
+/// assert!(!intspan::is_yaml("Atha.json"));
+/// ```
+pub fn is_yaml(input: &str) -> bool {
+    let input = input.to_lowercase();
+    input.ends_with(".yml") || input.ends_with(".yaml")
+}
+
+/// Is the file a runlist file, JSON, YAML or binary, by the extension
+///
+/// ```
+/// assert!(intspan::is_runlist("tests/spanr/Atha.json"));
+/// assert!(intspan::is_runlist("tests/spanr/Atha.yml"));
+/// assert!(intspan::is_runlist("Atha.rlb"));
+/// assert!(!intspan::is_runlist("tests/spanr/Atha.chr.sizes"));
+/// ```
+pub fn is_runlist(input: &str) -> bool {
+    is_yaml(input) || is_rlb(input) || input.to_lowercase().ends_with(".json")
+}
 
-    serde_json::from_str(&s).unwrap()
+// Keys and scalars are all strings in runlist files, e.g. `1: 100` is `"1": "100"`
+fn yaml2json(yaml: serde_yaml::Value, key: &str) -> anyhow::Result<serde_json::Value> {
+    match yaml {
+        serde_yaml::Value::Mapping(mapping) => {
+            let mut map = serde_json::Map::new();
+            for (key, value) in mapping {
+                let key = yaml2string(key, "")?;
+                let value = yaml2json(value, &key)?;
+                map.insert(key, value);
+            }
+            Ok(serde_json::Value::Object(map))
+        }
+        serde_yaml::Value::Tagged(tagged) => yaml2json(tagged.value, key),
+        value => Ok(serde_json::Value::String(yaml2string(value, key)?)),
+    }
 }
 
-pub fn writer(output: &str) -> Box<dyn Write> {
+fn yaml2string(yaml: serde_yaml::Value, key: &str) -> anyhow::Result<String> {
+    match yaml {
+        serde_yaml::Value::String(s) => Ok(s),
+        serde_yaml::Value::Number(n) => Ok(n.to_string()),
+        serde_yaml::Value::Bool(b) => Ok(b.to_string()),
+        // `~` is an empty set
+        serde_yaml::Value::Null => Ok("-".to_string()),
+        serde_yaml::Value::Tagged(tagged) => yaml2string(tagged.value, key),
+        _ => Err(anyhow!("Runlists should be strings or maps: {}", key)),
+    }
+}
+
+pub fn writer(output: &str) -> Result<Box<dyn Write>, InputError> {
     let writer: Box<dyn Write> = if output == "stdout" {
         Box::new(BufWriter::new(std::io::stdout()))
     } else {
-        Box::new(BufWriter::new(std::fs::File::create(output).unwrap()))
+        let file = std::fs::File::create(output).map_err(|e| InputError::file(output, e))?;
+        Box::new(BufWriter::new(file))
     };
 
-    writer
+    Ok(writer)
 }
 
-pub fn write_lines(output: &str, lines: &Vec<String>) -> Result<(), std::io::Error> {
-    let mut writer = writer(output);
+pub fn write_lines(output: &str, lines: &Vec<String>) -> anyhow::Result<()> {
+    let mut writer = writer(output)?;
 
     for line in lines {
         writer.write_all(format!("{}\n", line).as_ref())?;
@@ -134,12 +253,25 @@ pub fn write_lines(output: &str, lines: &Vec<String>) -> Result<(), std::io::Err
     Ok(())
 }
 
-pub fn write_json(
-    output: &str,
-    json: &BTreeMap<String, serde_json::Value>,
-) -> Result<(), std::io::Error> {
-    let mut writer = writer(output);
-    let mut s = serde_json::to_string_pretty(json).unwrap();
+/// Write a runlist file
+///
+/// * `.rlb` files are written as compressed binary runlist files
+/// * `.yml` and `.yaml` files are written as YAML
+pub fn write_json(output: &str, json: &BTreeMap<String, serde_json::Value>) -> anyhow::Result<()> {
+    let mut writer = writer(output)?;
+    if is_rlb(output) {
+        let bytes = encode_rlb(json, true)?;
+        writer.write_all(&bytes)?;
+        return Ok(());
+    }
+    if is_yaml(output) {
+        let s = serde_yaml::to_string(json)?;
+        writer.write_all(b"---\n")?;
+        writer.write_all(s.as_bytes())?;
+        return Ok(());
+    }
+
+    let mut s = serde_json::to_string_pretty(json)?;
     s.push('\n');
     writer.write_all(s.as_bytes())?;
 
@@ -153,18 +285,37 @@ pub fn write_json(
 /// let mut runlists: BTreeMap<String, Value> = BTreeMap::new();
 /// runlists.insert("I".to_string(), value);
 ///
-/// let sets = intspan::json2set(&runlists);
+/// let sets = intspan::json2set(&runlists).unwrap();
 /// assert!(sets.values().next().unwrap().contains(28550));
+///
+/// runlists.insert("II".to_string(), serde_json::to_value("1-3,x").unwrap());
+/// let err = intspan::json2set(&runlists).unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "II: Number format error: x at 4 of 1-3,x"
+/// );
 /// ```
-pub fn json2set(json: &BTreeMap<String, serde_json::Value>) -> BTreeMap<String, IntSpan> {
+pub fn json2set(
+    json: &BTreeMap<String, serde_json::Value>,
+) -> anyhow::Result<BTreeMap<String, IntSpan>> {
     let mut set: BTreeMap<String, IntSpan> = BTreeMap::new();
 
     for (chr, value) in json {
-        let intspan = IntSpan::from(value.as_str().unwrap());
-        set.insert(chr.into(), intspan);
+        set.insert(chr.into(), value2set(chr, value)?);
     }
 
-    set
+    Ok(set)
+}
+
+// A runlist value, with the key in errors
+fn value2set(key: &str, value: &serde_json::Value) -> anyhow::Result<IntSpan> {
+    let runlist = value
+        .as_str()
+        .ok_or_else(|| anyhow!("{}: runlists should be strings", key))?;
+
+    runlist
+        .parse::<IntSpan>()
+        .map_err(|e| anyhow!("{}: {}", key, e))
 }
 
 /// ```
@@ -208,7 +359,7 @@ pub fn set2json_m(
 
 pub fn json2set_m(
     json: &BTreeMap<String, serde_json::Value>,
-) -> BTreeMap<String, BTreeMap<String, IntSpan>> {
+) -> anyhow::Result<BTreeMap<String, BTreeMap<String, IntSpan>>> {
     let is_multi: bool = json.values().next().unwrap().is_object();
 
     let mut s_of: BTreeMap<String, BTreeMap<String, IntSpan>> = BTreeMap::new();
@@ -217,15 +368,177 @@ pub fn json2set_m(
             let string = serde_json::to_string(value).unwrap();
             let runlist_one: BTreeMap<String, serde_json::Value> =
                 serde_json::from_str(string.as_str()).unwrap();
-            let set_one = json2set(&runlist_one);
+            let set_one = json2set(&runlist_one).map_err(|e| anyhow!("{}.{}", key, e))?;
             s_of.insert(key.to_string(), set_one);
         }
     } else {
-        let set_one = json2set(json);
+        let set_one = json2set(json)?;
         s_of.insert("__single".to_string(), set_one);
     }
 
-    s_of
+    Ok(s_of)
+}
+
+/// Stranded runlists are `chr => {"+": runlist, "-": runlist}`
+///
+/// ```
+/// use serde_json::json;
+/// use std::collections::BTreeMap;
+/// let stranded: BTreeMap<String, serde_json::Value> =
+///     serde_json::from_value(json!({"I": {"+": "1-100", "-": "-"}})).unwrap();
+/// assert!(intspan::is_stranded(&stranded));
+///
+/// let multi: BTreeMap<String, serde_json::Value> =
+///     serde_json::from_value(json!({"name": {"I": "1-100"}})).unwrap();
+/// assert!(!intspan::is_stranded(&multi));
+///
+/// let empty: BTreeMap<String, serde_json::Value> =
+///     serde_json::from_value(json!({"name": {}})).unwrap();
+/// assert!(!intspan::is_stranded(&empty));
+///
+/// let single: BTreeMap<String, serde_json::Value> =
+///     serde_json::from_value(json!({"I": "1-100"})).unwrap();
+/// assert!(!intspan::is_stranded(&single));
+/// ```
+pub fn is_stranded(json: &BTreeMap<String, serde_json::Value>) -> bool {
+    !json.is_empty()
+        && json.values().all(|value| match value.as_object() {
+            Some(obj) => !obj.is_empty() && obj.keys().all(|k| k == "+" || k == "-"),
+            None => false,
+        })
+}
+
+/// Stranded runlists to sets of each strand, strand => chr => IntSpan.
+/// Both strands are always present, like a multi-level runlist with names `+` and `-`.
+///
+/// ```
+/// use serde_json::json;
+/// use std::collections::BTreeMap;
+/// let json: BTreeMap<String, serde_json::Value> = serde_json::from_value(json!({
+///     "I": {"+": "1-100", "-": "201-300"},
+///     "II": {"+": "1-10"}
+/// }))
+/// .unwrap();
+///
+/// let set_of = intspan::json2set_stranded(&json).unwrap();
+/// assert_eq!(set_of.len(), 2);
+/// assert_eq!(set_of["+"]["II"].to_string(), "1-10");
+/// assert_eq!(set_of["-"]["I"].to_string(), "201-300");
+/// assert!(set_of["-"]["II"].is_empty());
+///
+/// let merged = intspan::merge_strands(&set_of);
+/// assert_eq!(merged["I"].to_string(), "1-100,201-300");
+///
+/// let out = intspan::set2json_stranded(&set_of);
+/// assert_eq!(out["II"], json!({"+": "1-10", "-": "-"}));
+/// ```
+pub fn json2set_stranded(
+    json: &BTreeMap<String, serde_json::Value>,
+) -> anyhow::Result<BTreeMap<String, BTreeMap<String, IntSpan>>> {
+    let mut set_of: BTreeMap<String, BTreeMap<String, IntSpan>> = BTreeMap::new();
+    set_of.insert("+".to_string(), BTreeMap::new());
+    set_of.insert("-".to_string(), BTreeMap::new());
+
+    for (chr, value) in json {
+        for strand in ["+", "-"] {
+            let intspan = match value.get(strand) {
+                Some(runlist) => value2set(&format!("{}.{}", chr, strand), runlist)?,
+                None => IntSpan::new(),
+            };
+            set_of
+                .get_mut(strand)
+                .unwrap()
+                .insert(chr.to_string(), intspan);
+        }
+    }
+
+    Ok(set_of)
+}
+
+/// Sets of each strand to stranded runlists. See `json2set_stranded()`
+pub fn set2json_stranded(
+    set_of: &BTreeMap<String, BTreeMap<String, IntSpan>>,
+) -> BTreeMap<String, serde_json::Value> {
+    let mut json: BTreeMap<String, serde_json::Value> = BTreeMap::new();
+
+    for strand in ["+", "-"] {
+        let set = match set_of.get(strand) {
+            Some(x) => x,
+            None => continue,
+        };
+        for (chr, intspan) in set {
+            let entry = json
+                .entry(chr.to_string())
+                .or_insert_with(|| serde_json::json!({"+": "-", "-": "-"}));
+            entry[strand] = serde_json::Value::String(intspan.to_string());
+        }
+    }
+
+    json
+}
+
+/// Union of sets of all strands, chr => IntSpan
+pub fn merge_strands(
+    set_of: &BTreeMap<String, BTreeMap<String, IntSpan>>,
+) -> BTreeMap<String, IntSpan> {
+    let mut set: BTreeMap<String, IntSpan> = BTreeMap::new();
+
+    for s in set_of.values() {
+        for (chr, intspan) in s {
+            set.entry(chr.to_string()).or_default().merge(intspan);
+        }
+    }
+
+    set
+}
+
+/// Apply `f` to items, e.g. chromosomes, with `parallel` threads.
+/// Results are in the order of items, so outputs are deterministic.
+///
+/// ```
+/// let chrs = vec!["I", "II", "III", "IV", "V"];
+/// let lens = intspan::par_map(&chrs, 3, |chr| chr.len());
+/// assert_eq!(lens, vec![1, 2, 3, 2, 1]);
+/// assert_eq!(intspan::par_map(&chrs, 1, |chr| chr.len()), lens);
+/// ```
+pub fn par_map<T, R, F>(items: &[T], parallel: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    if parallel <= 1 || items.len() <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    // Indices of items as a job queue
+    let (snd_idx, rcv_idx) = crossbeam::channel::bounded::<usize>(items.len());
+    for i in 0..items.len() {
+        snd_idx.send(i).unwrap();
+    }
+    drop(snd_idx);
+
+    let (snd_res, rcv_res) = crossbeam::channel::unbounded::<(usize, R)>();
+    crossbeam::scope(|s| {
+        for _ in 0..parallel.min(items.len()) {
+            let rcv_idx = rcv_idx.clone();
+            let snd_res = snd_res.clone();
+            let f = &f;
+            s.spawn(move |_| {
+                for i in rcv_idx.iter() {
+                    snd_res.send((i, f(&items[i]))).unwrap();
+                }
+            });
+        }
+    })
+    .unwrap();
+    drop(snd_res);
+
+    let mut results: Vec<Option<R>> = (0..items.len()).map(|_| None).collect();
+    for (i, res) in rcv_res.iter() {
+        results[i] = Some(res);
+    }
+    results.into_iter().map(|res| res.unwrap()).collect()
 }
 
 pub fn fill_up_m(
@@ -263,7 +576,7 @@ pub fn chrs_in_sets(set_of: &BTreeMap<String, BTreeMap<String, IntSpan>>) -> BTr
 
 pub fn build_range_of_part(line: &str, range_of_str: &mut HashMap<String, Range>) {
     for part in line.split('\t') {
-        let range = Range::from_str(part);
+        let range = Range::from_str_lenient(part);
         if !range.is_valid() {
             continue;
         }
@@ -348,52 +661,20 @@ pub fn sort_links(lines: &[String]) -> Vec<String> {
     among_links
 }
 
+/// Get the sequence of a range from a plain or bgzipped FASTA file, via the `.fai` index
+///
 /// ```
-/// match which::which("samtools") {
-///     Ok(_) => {
-///         let seq = intspan::get_seq_faidx("tests/fasr/NC_000932.fa", "NC_000932:1-10").unwrap();
-///         assert_eq!(seq, "ATGGGCGAAC".to_string());
-///         let res = intspan::get_seq_faidx("tests/fasr/NC_000932.fa", "FAKE:1-10");
-///         assert_eq!(format!("{}", res.unwrap_err()), "Command executed with failing error code");
-///     }
-///     Err(_) => {}
-/// }
+/// let seq = intspan::get_seq_faidx("tests/fasr/NC_000932.fa", "NC_000932:1-10").unwrap();
+/// assert_eq!(seq, "ATGGGCGAAC".to_string());
+/// let seq = intspan::get_seq_faidx("tests/fasr/NC_000932.fa.gz", "NC_000932(-):1-10").unwrap();
+/// assert_eq!(seq, "GTTCGCCCAT".to_string());
+/// let res = intspan::get_seq_faidx("tests/fasr/NC_000932.fa", "FAKE:1-10");
+/// assert_eq!(format!("{}", res.unwrap_err()), "Sequence not found: `FAKE`");
 /// ```
 // cargo test --doc utils::get_seq_faidx
 pub fn get_seq_faidx(file: &str, range: &str) -> anyhow::Result<String> {
-    let mut bin = String::new();
-    for e in &["samtools"] {
-        if let Ok(pth) = which::which(e) {
-            bin = pth.to_string_lossy().to_string();
-            break;
-        }
-    }
-
-    if bin.is_empty() {
-        return Err(anyhow!("Can't find the external command"));
-    }
-
-    let mut seq = String::new();
-    let output = std::process::Command::new(bin)
-        .arg("faidx")
-        .arg(file)
-        .arg(range)
-        .output()?;
-
-    if !output.status.success() {
-        return Err(anyhow!("Command executed with failing error code"));
-    }
-
-    for line in output.stdout.lines().map_while(Result::ok) {
-        // header
-        if line.starts_with('>') {
-            continue;
-        }
-
-        seq += line.as_str();
-    }
-
-    Ok(seq)
+    let mut reader = FaidxReader::from_path(file)?;
+    reader.fetch_range(&Range::from_str_lenient(range))
 }
 
 pub fn basename(path: impl AsRef<std::path::Path>) -> std::io::Result<String> {
@@ -444,7 +725,7 @@ mod read_write {
         )
         .expect("Write error");
 
-        let lines = read_lines(&filename);
+        let lines = read_lines(&filename).unwrap();
         assert_eq!(lines.len(), 4);
     }
 
@@ -458,17 +739,17 @@ mod read_write {
             .into_string()
             .unwrap();
 
-        let json = read_json("tests/spanr/Atha.json");
+        let json = read_json("tests/spanr/Atha.json").unwrap();
 
         write_json(&filename, &json).expect("Write error");
 
-        let lines = read_lines(&filename);
+        let lines = read_lines(&filename).unwrap();
         assert!(lines.len() == 17 || lines.len() == 18);
     }
 }
 
 pub fn ints_to_idx(str: &str) -> Vec<usize> {
-    let mut ints: Vec<i32> = vec![];
+    let mut ints: Vec<i64> = vec![];
     let parts: Vec<&str> = str.split(',').collect();
     for p in parts {
         let intspan = IntSpan::from(p);
@@ -482,14 +763,13 @@ pub fn named_field_to_idx(
     str: &str,
     idx_of: &HashMap<String, usize>,
 ) -> anyhow::Result<Vec<usize>> {
-    let mut ints: Vec<i32> = vec![];
+    let mut ints: Vec<i64> = vec![];
     let parts: Vec<&str> = str.split(',').collect();
     for p in parts {
-        if IntSpan::valid(p) {
-            let intspan = IntSpan::from(p);
+        if let Ok(intspan) = p.parse::<IntSpan>() {
             intspan.elements().iter().for_each(|e| ints.push(*e));
         } else if idx_of.contains_key(p) {
-            ints.push(*idx_of.get(p).unwrap() as i32)
+            ints.push(*idx_of.get(p).unwrap() as i64)
         } else {
             return Err(anyhow!("Field not found in file header: `{}`", p));
         }
@@ -508,29 +788,129 @@ pub fn fields_to_ints(str: &str) -> IntSpan {
     ints
 }
 
-pub fn extract_rg(line: &str, opt_idx_range: usize) -> Option<Range> {
-    let parts: Vec<&str> = line.split('\t').collect();
+/// BED files are recognized by the extension, `.bed` or `.bed.gz`
+///
+/// ```
+/// assert!(intspan::is_bed("tests/rgr/S288c.bed"));
+/// assert!(intspan::is_bed("genes.BED.gz"));
+/// assert!(!intspan::is_bed("tests/rgr/S288c.rg"));
+/// assert!(!intspan::is_bed("stdin"));
+/// ```
+pub fn is_bed(input: &str) -> bool {
+    let input = input.to_lowercase();
+    input.ends_with(".bed") || input.ends_with(".bed.gz")
+}
 
-    let range = if opt_idx_range == 0 {
-        parts.iter().find_map(|part| {
-            let rg = Range::from_str(part);
-            if rg.is_valid() {
-                Some(rg)
-            } else {
-                None
-            }
-        })
+/// Empty lines, comments, and track or browser lines of BED files
+///
+/// ```
+/// assert!(intspan::is_comment("", false));
+/// assert!(intspan::is_comment("# comment", false));
+/// assert!(intspan::is_comment("track name=test", true));
+/// assert!(!intspan::is_comment("track name=test", false));
+/// assert!(!intspan::is_comment("I:1-100", false));
+/// ```
+pub fn is_comment(line: &str, is_bed: bool) -> bool {
+    line.trim().is_empty()
+        || line.starts_with('#')
+        || (is_bed && (line.starts_with("track") || line.starts_with("browser")))
+}
+
+/// Parse a line of a `.rg` or `.bed` file into a range
+///
+/// ```
+/// let rg = intspan::parse_rg("I\t89\t150", true).unwrap();
+/// assert_eq!(rg.to_string(), "I:90-150");
+/// let rg = intspan::parse_rg("I:90-150", false).unwrap();
+/// assert_eq!(rg.to_string(), "I:90-150");
+///
+/// let err = intspan::parse_rg("I\t150\t89", true).unwrap_err();
+/// assert_eq!(err.to_string(), "invalid BED line: `I\t150\t89`");
+/// let err = intspan::parse_rg("I:abc", false).unwrap_err();
+/// assert_eq!(err.to_string(), "invalid range: `I:abc`");
+/// assert!(intspan::parse_rg("I:200-100", false).is_err());
+/// assert!(intspan::parse_rg("I:50-60xyz", false).is_err());
+/// assert!(intspan::parse_rg("I:0-100", false).is_err());
+/// ```
+pub fn parse_rg(line: &str, is_bed: bool) -> Result<Range, InputError> {
+    if !is_bed {
+        return line.parse::<Range>();
+    }
+
+    let range = Range::from_bed(line);
+    if range.is_valid() && range.start() <= range.end() {
+        Ok(range)
     } else {
-        let part = parts.get(opt_idx_range - 1).unwrap();
-        let rg = Range::from_str(part);
-        if rg.is_valid() {
-            Some(rg)
-        } else {
-            None
+        Err(InputError::parse("BED line", line))
+    }
+}
+
+/// Parse ranges of a line of links, tab-separated ranges, e.g. `II(+):1-2018\tXII(+):204-2215\t+`.
+/// Fields containing `:` should be ranges and others, like hit strands, are ignored.
+///
+/// ```
+/// let links = intspan::parse_links("II(+):1-2018\tXII(+):204-2215\t+").unwrap();
+/// assert_eq!(links.len(), 2);
+/// assert_eq!(links[1].0, "XII(+):204-2215");
+/// assert_eq!(links[1].1.to_string(), "XII(+):204-2215");
+///
+/// let err = intspan::parse_links("II(+):1-2018\tI:abc").unwrap_err();
+/// assert_eq!(err.to_string(), "invalid range: `I:abc`");
+/// let err = intspan::parse_links("+").unwrap_err();
+/// assert_eq!(err.to_string(), "invalid link: `+`");
+/// ```
+pub fn parse_links(line: &str) -> Result<Vec<(&str, Range)>, InputError> {
+    let mut links = vec![];
+    for part in line.split('\t') {
+        if part.contains(':') {
+            links.push((part, parse_rg(part, false)?));
         }
-    };
+    }
+
+    if links.is_empty() {
+        Err(InputError::parse("link", line))
+    } else {
+        Ok(links)
+    }
+}
+
+/// Extract the range from a line. Reversed ranges are invalid, see `parse_rg()`.
+/// With `opt_idx_range` of 0, the first field containing a valid range is used.
+///
+/// ```
+/// let rg = intspan::extract_rg("foo\tI:90-150", 0).unwrap();
+/// assert_eq!(rg.to_string(), "I:90-150");
+/// let rg = intspan::extract_rg("I:1-100\tI:90-150", 2).unwrap();
+/// assert_eq!(rg.to_string(), "I:90-150");
+/// assert!(intspan::extract_rg("I:1-100", 2).is_none());
+/// assert!(intspan::extract_rg("I:200-100", 0).is_none());
+/// ```
+pub fn extract_rg(line: &str, opt_idx_range: usize) -> Option<Range> {
+    extract_rg_bed(line, opt_idx_range, false)
+}
 
-    range
+/// Extract the range from a line, like `extract_rg()`, or from a BED line with `is_bed`.
+/// With `is_bed`, the first three columns of the BED line are used and `opt_idx_range` is ignored.
+///
+/// ```
+/// let rg = intspan::extract_rg_bed("I\t89\t150\t.\t0\t+", 0, true).unwrap();
+/// assert_eq!(rg.to_string(), "I(+):90-150");
+/// let rg = intspan::extract_rg_bed("foo\tI:90-150", 0, false).unwrap();
+/// assert_eq!(rg.to_string(), "I:90-150");
+/// assert!(intspan::extract_rg_bed("track name=test", 0, true).is_none());
+/// ```
+pub fn extract_rg_bed(line: &str, opt_idx_range: usize, is_bed: bool) -> Option<Range> {
+    if is_bed {
+        return parse_rg(line, true).ok();
+    }
+
+    let parts: Vec<&str> = line.split('\t').collect();
+
+    if opt_idx_range == 0 {
+        parts.iter().find_map(|part| parse_rg(part, false).ok())
+    } else {
+        parse_rg(parts.get(opt_idx_range - 1)?, false).ok()
+    }
 }
 
 // rewrite from https://metacpan.org/dist/Number-Format/source/Format.pm
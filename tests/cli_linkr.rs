@@ -13,6 +13,35 @@ fn command_invalid() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn command_sort_strict() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("linkr")?;
+    let output = cmd
+        .arg("sort")
+        .arg("tests/linkr/malformed.tsv")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(stdout.lines().count(), 2);
+    assert!(!stdout.contains("I:abc"));
+    assert!(stderr.contains("Skipped 2 malformed line(s)"));
+
+    let mut cmd = Command::cargo_bin("linkr")?;
+    cmd.arg("sort")
+        .arg("tests/linkr/malformed.tsv")
+        .arg("--strict")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "tests/linkr/malformed.tsv: line 2: invalid range: `I:abc`",
+        ));
+
+    Ok(())
+}
+
 #[test]
 fn command_circos() -> anyhow::Result<()> {
     let mut cmd = Command::cargo_bin("linkr")?;
@@ -175,3 +204,47 @@ fn command_connect() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn command_bedpe2link() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("linkr")?;
+    let output = cmd
+        .arg("bedpe2link")
+        .arg("tests/linkr/II.bedpe")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 6);
+    assert!(
+        stdout.contains("II(+):1-2018\tXII(+):204-2215\n"),
+        "0-based starts"
+    );
+    assert!(stdout.contains("II(-):144228-145732"), "strand2");
+    assert!(
+        stdout.contains("II:1990-5850\tXII:7326-11200\n"),
+        "unknown strands"
+    );
+    assert!(!stdout.contains("unpaired"), "invalid ranges");
+
+    Ok(())
+}
+
+#[test]
+fn command_link2bedpe() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("linkr")?;
+    let output = cmd
+        .arg("link2bedpe")
+        .arg("tests/linkr/II.connect.tsv")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    // same as the number of circos links
+    assert_eq!(stdout.lines().count(), 10);
+    assert!(stdout.lines().all(|e| e.split('\t').count() == 10));
+    assert!(stdout.contains("XIII\t6394\t7947\t"), "0-based start");
+    assert!(stdout.contains("\t.\t0\t+\t-\n"), "strands");
+
+    Ok(())
+}
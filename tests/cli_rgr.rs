@@ -3,6 +3,103 @@ use predicates::prelude::*; // Used for writing assertions
 use std::process::Command;
 use tempfile::TempDir; // Run programs
 
+#[test]
+fn command_intersect() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("intersect")
+        .arg("tests/rgr/S288c.rg")
+        .arg("tests/rgr/genes.rg")
+        .arg("--bp")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout, "I:1-100\tI(+):1-20\t20\n");
+
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("intersect")
+        .arg("tests/rgr/S288c.rg")
+        .arg("tests/rgr/genes.rg")
+        .arg("-r")
+        .arg("0.5")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.is_empty(), "reciprocal fraction");
+
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("intersect")
+        .arg("tests/rgr/S288c.rg")
+        .arg("tests/rgr/genes.rg")
+        .arg("--mode")
+        .arg("left")
+        .arg("--bp")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 6);
+    assert!(stdout.contains("I:1-100\tI(+):1-20\t20\n"));
+    assert!(stdout.contains("II:21294-22075\t.\t0\n"));
+
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("intersect")
+        .arg("tests/rgr/S288c.rg")
+        .arg("tests/rgr/ctg.range.tsv")
+        .arg("--mode")
+        .arg("left")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.lines().all(|e| e.split('\t').count() == 4));
+    assert!(
+        stdout.contains("II:21294-22075\t.\t.\t.\n"),
+        "one . per column"
+    );
+
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("intersect")
+        .arg("tests/rgr/S288c.rg")
+        .arg("tests/rgr/genes.rg")
+        .arg("--mode")
+        .arg("anti")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 5);
+    assert!(!stdout.contains("I:1-100\n"));
+    assert!(stdout.contains("II:21294-22075\n"));
+
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("intersect")
+        .arg("tests/rgr/ctg.range.tsv")
+        .arg("tests/rgr/ctg.range.tsv")
+        .arg("-H")
+        .arg("-f")
+        .arg("3")
+        .arg("--bp")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(
+        stdout.lines().next().unwrap(),
+        "length\tID\trange\tlength\tID\trange\toverlap"
+    );
+    assert!(stdout.contains("ctg:I:1\tI:1-100000\t100000\tctg:I:1\tI:1-100000\t100000\n"));
+
+    Ok(())
+}
+
 #[test]
 fn command_merge() -> anyhow::Result<()> {
     let mut cmd = Command::cargo_bin("rgr")?;
@@ -20,6 +117,71 @@ fn command_merge() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn command_merge_parallel() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("merge")
+        .arg("tests/rgr/S288c.rg")
+        .arg("-c")
+        .arg("0")
+        .output()
+        .unwrap();
+    let expected = String::from_utf8(output.stdout).unwrap();
+
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("merge")
+        .arg("tests/rgr/S288c.rg")
+        .arg("-c")
+        .arg("0")
+        .arg("--parallel")
+        .arg("4")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 4);
+    assert_eq!(stdout, expected, "the same order");
+
+    Ok(())
+}
+
+#[test]
+fn command_merge_coverage() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("merge")
+        .arg("tests/rgr/II.links.tsv")
+        .arg("-c")
+        .arg("0")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 11);
+    assert!(
+        stdout.contains("II(+):1-2018\tII(+):1-5850"),
+        "any overlaps"
+    );
+    assert!(stdout.contains("II(+):810776-812328\tII(+):804880-813096"));
+
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("merge")
+        .arg("tests/rgr/II.links.tsv")
+        .arg("-c")
+        .arg("0.9999")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 2);
+    assert!(stdout.contains("II(-):144228-145732\tII(+):144228-145732"));
+
+    Ok(())
+}
+
 #[test]
 fn command_sort() -> anyhow::Result<()> {
     let mut cmd = Command::cargo_bin("rgr")?;
@@ -73,6 +235,55 @@ fn command_sort() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn command_sort_buffer() -> anyhow::Result<()> {
+    let cases: Vec<Vec<&str>> = vec![
+        vec![
+            "tests/rgr/S288c.rg",
+            "tests/rgr/S288c.rg",
+            "tests/rgr/genes.rg",
+        ],
+        vec!["tests/rgr/ranges.tsv.gz", "-H", "-f", "2"],
+        vec!["tests/rgr/ctg.range.tsv", "-H", "-f", "3", "-g", "2"],
+    ];
+
+    for case in cases {
+        let mut cmd = Command::cargo_bin("rgr")?;
+        let output = cmd.arg("sort").args(&case).output().unwrap();
+        let expected = String::from_utf8(output.stdout).unwrap();
+
+        // Chunks are spilled to temp files
+        let mut cmd = Command::cargo_bin("rgr")?;
+        let output = cmd
+            .arg("sort")
+            .args(&case)
+            .arg("-S")
+            .arg("200")
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+
+        assert!(!stdout.is_empty());
+        assert_eq!(stdout, expected, "{}", case[0]);
+    }
+
+    // Duplicated lines are written once
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("sort")
+        .arg("tests/rgr/S288c.rg")
+        .arg("tests/rgr/S288c.rg")
+        .arg("-S")
+        .arg("100")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 6);
+
+    Ok(())
+}
+
 #[test]
 fn command_runlist() -> anyhow::Result<()> {
     let mut cmd = Command::cargo_bin("rgr")?;
@@ -146,6 +357,36 @@ fn command_runlist() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn command_runlist_stranded() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("runlist")
+        .arg("tests/rgr/stranded.json")
+        .arg("tests/rgr/S288c.rg")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 3);
+    assert!(!stdout.contains("S288c.I(-):190-200"), "negative strand");
+
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("runlist")
+        .arg("tests/rgr/stranded.json")
+        .arg("tests/rgr/S288c.rg")
+        .arg("--merge-strands")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 5);
+    assert!(stdout.contains("S288c.I(-):190-200"), "merged strands");
+
+    Ok(())
+}
+
 #[test]
 fn command_runlist_invalid() -> anyhow::Result<()> {
     let mut cmd = Command::cargo_bin("rgr")?;
@@ -213,6 +454,34 @@ fn command_field() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn command_field_bed() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("field")
+        .arg("tests/rgr/S288c.bed")
+        .arg("--chr")
+        .arg("1")
+        .arg("--start")
+        .arg("2")
+        .arg("--end")
+        .arg("3")
+        .arg("--strand")
+        .arg("6")
+        .arg("--bed")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 5);
+    assert!(stdout.contains("I:1-100\n"), "0-based start");
+    assert!(stdout.contains("I(-):190-200"), "strand");
+    assert!(!stdout.contains("(.)"), "unknown strand");
+    assert!(!stdout.contains("track"), "track line");
+
+    Ok(())
+}
+
 #[test]
 fn command_count() -> anyhow::Result<()> {
     let mut cmd = Command::cargo_bin("rgr")?;
@@ -253,6 +522,371 @@ fn command_count() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn command_count_strict() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("rgr")?;
+    cmd.arg("count")
+        .arg("tests/rgr/not_exists.rg")
+        .arg("tests/rgr/S288c.rg")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "could not open tests/rgr/not_exists.rg",
+        ))
+        .stderr(predicate::str::contains("panicked").not());
+
+    let mut cmd = Command::cargo_bin("rgr")?;
+    cmd.arg("count")
+        .arg("tests/spanr/malformed.rg")
+        .arg("tests/rgr/S288c.rg")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("I:1-100\t2"))
+        .stderr(predicate::str::contains("Skipped 2 malformed line(s)"));
+
+    let mut cmd = Command::cargo_bin("rgr")?;
+    cmd.arg("count")
+        .arg("tests/rgr/S288c.rg")
+        .arg("tests/spanr/malformed.rg")
+        .arg("--strict")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "malformed.rg: line 2: invalid range",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn command_sort_strict() -> anyhow::Result<()> {
+    // Trailing characters are not part of a range
+    let mut cmd = Command::cargo_bin("rgr")?;
+    cmd.arg("sort")
+        .arg("tests/rgr/trailing.rg")
+        .arg("--strict")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "trailing.rg: line 2: invalid range: `I:100-abc`",
+        ));
+
+    let mut cmd = Command::cargo_bin("rgr")?;
+    cmd.arg("count")
+        .arg("tests/rgr/S288c.rg")
+        .arg("tests/rgr/trailing.rg")
+        .arg("--strict")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "trailing.rg: line 2: invalid range: `I:100-abc`",
+        ));
+
+    // Lines without valid ranges are kept at the end
+    let mut cmd = Command::cargo_bin("rgr")?;
+    cmd.arg("sort")
+        .arg("tests/rgr/trailing.rg")
+        .assert()
+        .success()
+        .stdout("I:1-10\nI:50-60\nI:100-abc\nI:50-60xyz\n")
+        .stderr(predicate::str::contains("Skipped 2 malformed line(s)"));
+
+    Ok(())
+}
+
+#[test]
+fn command_intersect_strict() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("rgr")?;
+    cmd.arg("intersect")
+        .arg("tests/spanr/malformed.rg")
+        .arg("tests/rgr/S288c.rg")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("I:1-100\tI:1-100"))
+        .stderr(predicate::str::contains("Skipped 2 malformed line(s)"));
+
+    let mut cmd = Command::cargo_bin("rgr")?;
+    cmd.arg("intersect")
+        .arg("tests/rgr/S288c.rg")
+        .arg("tests/spanr/malformed.rg")
+        .arg("--strict")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "tests/spanr/malformed.rg: line 2: invalid range: `I:abc`",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn command_count_giant() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("count")
+        .arg("tests/rgr/giant.rg")
+        .arg("tests/rgr/giant.rg")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 4);
+    assert!(stdout.contains("1:2200000001-2300000000\t2"));
+    assert!(stdout.contains("1(+):3100000001-3200000000\t1"));
+
+    Ok(())
+}
+
+#[test]
+fn command_count_bed() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("count")
+        .arg("tests/rgr/S288c.bed")
+        .arg("tests/rgr/S288c.bed")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 5);
+    assert!(stdout.contains("I\t0\t100\trg1\t0\t.\t2"));
+    assert!(stdout.contains("II\t21293\t22075\trg4\t0\t.\t1"));
+
+    Ok(())
+}
+
+#[test]
+fn command_seq() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("seq")
+        .arg("tests/fasr/NC_000932.fa")
+        .arg("tests/rgr/NC_000932.rg")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 8);
+    assert!(stdout.contains(">NC_000932:1-10\nATGGGCGAAC\n"));
+    assert!(
+        stdout.contains(">NC_000932(-):1-10\nGTTCGCCCAT\n"),
+        "reverse complement"
+    );
+    assert!(
+        stdout.contains(">NC_000932(+):49-52\nCTGC\n"),
+        "across lines"
+    );
+    assert!(!stdout.contains("invalid"));
+
+    // bgzipped
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("seq")
+        .arg("tests/fasr/NC_000932.fa.gz")
+        .arg("tests/rgr/NC_000932.tsv")
+        .arg("-H")
+        .arg("-f")
+        .arg("2")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 4);
+    assert!(stdout.contains(">NC_000932(-):1-10\nGTTCGCCCAT\n"));
+
+    // Ranges outside of the sequences
+    let mut cmd = Command::cargo_bin("rgr")?;
+    cmd.arg("seq")
+        .arg("tests/fasr/NC_000932.fa")
+        .arg("tests/rgr/NC_000932.outside.rg")
+        .assert()
+        .success()
+        .stdout(">NC_000932:1-10\nATGGGCGAAC\n")
+        .stderr(predicate::str::contains("Skipped 2 malformed line(s)"));
+
+    let mut cmd = Command::cargo_bin("rgr")?;
+    cmd.arg("seq")
+        .arg("tests/fasr/NC_000932.fa")
+        .arg("tests/rgr/NC_000932.outside.rg")
+        .arg("--strict")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "line 1: invalid range of the FASTA file: `XX:1-10`",
+        ));
+
+    // The index is written only with --fai
+    let tempdir = TempDir::new().unwrap();
+    let fasta = tempdir.path().join("t.fa");
+    std::fs::copy("tests/fasr/NC_000932.fa", &fasta)?;
+    let fai = tempdir.path().join("t.fa.fai");
+
+    let mut cmd = Command::cargo_bin("rgr")?;
+    cmd.arg("seq")
+        .arg(&fasta)
+        .arg("tests/rgr/NC_000932.rg")
+        .assert()
+        .success();
+    assert!(!fai.is_file());
+
+    let mut cmd = Command::cargo_bin("rgr")?;
+    cmd.arg("seq")
+        .arg(&fasta)
+        .arg("tests/rgr/NC_000932.rg")
+        .arg("--fai")
+        .assert()
+        .success();
+    assert!(fai.is_file());
+
+    Ok(())
+}
+
+#[test]
+fn command_closest() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("closest")
+        .arg("tests/rgr/S288c.rg")
+        .arg("tests/rgr/genes.rg")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 7);
+    assert!(stdout.contains("I:1-100\tI(+):1-20\t0\n"), "overlap");
+    assert!(
+        stdout.contains("I(+):90-150\tI(+):160-170\t10\n"),
+        "downstream"
+    );
+    assert!(
+        stdout.contains("S288c.I(-):190-200\tI(+):160-170\t20\n"),
+        "negative strand"
+    );
+    assert!(
+        stdout.contains("II:21294-22075\tII(+):21200-21269\t-25\n"),
+        "upstream"
+    );
+    assert!(
+        stdout.contains("II:21294-22075\tII(+):22100-23000\t25\n"),
+        "ties"
+    );
+
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("closest")
+        .arg("tests/rgr/S288c.rg")
+        .arg("tests/rgr/genes.rg")
+        .arg("--ties")
+        .arg("last")
+        .arg("--no-overlap")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 6);
+    assert!(
+        stdout.contains("I:1-100\tI(+):160-170\t60\n"),
+        "no overlaps"
+    );
+    assert!(!stdout.contains("II(+):21200-21269"), "last tie");
+
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("closest")
+        .arg("tests/rgr/ctg.range.tsv")
+        .arg("tests/rgr/ctg.range.tsv")
+        .arg("-H")
+        .arg("-f")
+        .arg("3")
+        .arg("--no-overlap")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout
+        .lines()
+        .next()
+        .unwrap()
+        .ends_with("\tclosest\tdistance"));
+    assert_eq!(stdout.lines().count(), 4);
+    assert!(stdout.contains("\tMito:1-85779\t.\t.\n"), "no ranges");
+    assert!(
+        stdout.contains("\tI:1-100000\t130218\tctg:I:2\tI:100001-230218\t1\n"),
+        "adjacent"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn command_window() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("window")
+        .arg("tests/rgr/S288c.rg")
+        .arg("tests/rgr/genes.rg")
+        .arg("-w")
+        .arg("50")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 8);
+    assert!(stdout.contains("I:1-100\tI(+):1-20\n"), "overlap");
+    assert!(
+        stdout.contains("S288c.I(-):190-200\tI(-):230-300\n"),
+        "within 50 bp"
+    );
+    assert!(!stdout.contains("II:23537-24097"), "no pairs");
+
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("window")
+        .arg("tests/rgr/S288c.rg")
+        .arg("tests/rgr/genes.rg")
+        .arg("-l")
+        .arg("100")
+        .arg("-r")
+        .arg("0")
+        .arg("--strand")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 5);
+    assert!(stdout.contains("I(+):90-150\tI(+):1-20\n"), "upstream");
+    assert!(
+        stdout.contains("S288c.I(-):190-200\tI(-):230-300\n"),
+        "upstream of (-)"
+    );
+    assert!(
+        !stdout.contains("S288c.I(-):190-200\tI(+):160-170\n"),
+        "downstream of (-)"
+    );
+
+    let mut cmd = Command::cargo_bin("rgr")?;
+    let output = cmd
+        .arg("window")
+        .arg("tests/rgr/ctg.range.tsv")
+        .arg("tests/rgr/ctg.range.tsv")
+        .arg("-H")
+        .arg("-f")
+        .arg("3")
+        .arg("-w")
+        .arg("1")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(
+        stdout.lines().next().unwrap(),
+        "length\tID\trange\tlength\tID\trange"
+    );
+    assert!(stdout.contains("ctg:I:1\tI:1-100000\t130218\tctg:I:2\tI:100001-230218\n"));
+
+    Ok(())
+}
+
 #[test]
 fn command_span() -> anyhow::Result<()> {
     let mut cmd = Command::cargo_bin("rgr")?;
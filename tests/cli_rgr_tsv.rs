@@ -114,6 +114,34 @@ fn command_dedup() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn command_dedup_buffer() -> anyhow::Result<()> {
+    for fields in ["", "2,3"].iter() {
+        let mut args = vec![
+            "dedup",
+            "tests/rgr/ranges.tsv.gz",
+            "tests/rgr/ranges.tsv.gz",
+        ];
+        if !fields.is_empty() {
+            args.push("-f");
+            args.push(fields);
+        }
+
+        let mut cmd = Command::cargo_bin("rgr")?;
+        let expected = String::from_utf8(cmd.args(&args).output()?.stdout)?;
+
+        // Hashes are spilled to temp files
+        let mut cmd = Command::cargo_bin("rgr")?;
+        let output = cmd.args(&args).arg("--buffer-size").arg("1K").output()?;
+        let stdout = String::from_utf8(output.stdout)?;
+
+        assert!(!stdout.is_empty());
+        assert_eq!(stdout, expected, "the same order");
+    }
+
+    Ok(())
+}
+
 #[test]
 fn command_filter_str() -> anyhow::Result<()> {
     let mut cmd = Command::cargo_bin("rgr")?;
@@ -58,6 +58,185 @@ fn command_genome() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn command_window() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("window")
+        .arg("tests/spanr/S288c.chr.sizes")
+        .arg("-s")
+        .arg("100000")
+        .arg("-t")
+        .arg("50000")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 232);
+    assert!(stdout.starts_with(
+        "I:1-100000\nI:50001-150000\nI:100001-200000\nI:150001-230218\nII:1-100000\n"
+    ));
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("window")
+        .arg("tests/spanr/I.json")
+        .arg("-s")
+        .arg("500")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 26);
+    assert!(
+        stdout.contains("I:16744-17133\nI:20043-20542\n"),
+        "inside spans"
+    );
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("window")
+        .arg("tests/spanr/I.json")
+        .arg("-s")
+        .arg("500")
+        .arg("--full")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 19);
+    assert!(!stdout.contains("I:16744-17133\n"), "partial");
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("window")
+        .arg("tests/spanr/I.json")
+        .arg("-s")
+        .arg("500")
+        .arg("--mode")
+        .arg("bases")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(
+        stdout.contains("I:16744-20152\nI:20153-20652\n"),
+        "across gaps"
+    );
+    assert!(stdout.ends_with("I:71659-71785\n"));
+
+    Ok(())
+}
+
+#[test]
+fn command_window_sizes() -> anyhow::Result<()> {
+    // Sizes less than 1 are malformed
+    let mut cmd = Command::cargo_bin("spanr")?;
+    cmd.arg("window")
+        .arg("tests/spanr/zero.chr.sizes")
+        .arg("-s")
+        .arg("50")
+        .assert()
+        .success()
+        .stdout("II:1-50\nII:51-100\n")
+        .stderr(predicate::str::contains("Skipped 2 malformed line(s)"))
+        .stderr(predicate::str::contains("panicked").not());
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    cmd.arg("window")
+        .arg("tests/spanr/zero.chr.sizes")
+        .arg("-s")
+        .arg("50")
+        .arg("--strict")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "zero.chr.sizes: line 1: invalid chr.sizes line",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn command_window_yaml_rlb() -> anyhow::Result<()> {
+    let tempdir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let expected = cmd
+        .arg("window")
+        .arg("tests/spanr/I.json")
+        .arg("-s")
+        .arg("500")
+        .output()
+        .unwrap()
+        .stdout;
+    assert_eq!(String::from_utf8(expected.clone())?.lines().count(), 26);
+
+    for file in ["I.yml", "I.rlb"] {
+        let mut cmd = Command::cargo_bin("spanr")?;
+        cmd.arg("convert")
+            .arg("tests/spanr/I.json")
+            .arg("-o")
+            .arg(tempdir.path().join(file))
+            .assert()
+            .success();
+
+        let mut cmd = Command::cargo_bin("spanr")?;
+        let output = cmd
+            .arg("window")
+            .arg(tempdir.path().join(file))
+            .arg("-s")
+            .arg("500")
+            .output()
+            .unwrap();
+        assert_eq!(output.stdout, expected, "{}", file);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn command_winstat() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("winstat")
+        .arg("tests/spanr/S288c.chr.sizes")
+        .arg("tests/spanr/S288c.rg")
+        .arg("-r")
+        .arg("tests/spanr/intergenic.json")
+        .arg("-s")
+        .arg("100000")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 129);
+    assert_eq!(
+        stdout.lines().next().unwrap(),
+        "chr\tstart\tend\tlength\tbases\tcoverage\tcount\tdensity\tcovered"
+    );
+    assert!(stdout.contains("I\t1\t100000\t100000\t13011\t0.1301\t4\t0.0400\t161\n"));
+    assert!(stdout.contains("II\t1\t100000\t100000\t6230\t0.0623\t2\t0.0200\t1343\n"));
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("winstat")
+        .arg("tests/spanr/S288c.chr.sizes")
+        .arg("tests/spanr/S288c.rg")
+        .arg("-s")
+        .arg("100")
+        .arg("-m")
+        .arg("2")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("I\t1\t100\t100\t2\t20.0000\t11\n"));
+    assert!(stdout.contains("I\t101\t200\t100\t3\t30.0000\t11\n"));
+
+    Ok(())
+}
+
 #[test]
 fn command_some() -> anyhow::Result<()> {
     let mut cmd = Command::cargo_bin("spanr")?;
@@ -147,6 +326,21 @@ fn command_split_to() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn command_stat_malformed() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("spanr")?;
+    cmd.arg("stat")
+        .arg("tests/spanr/S288c.chr.sizes")
+        .arg("tests/spanr/malformed.json")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "II: Number format error: x at 4 of 1-3,x",
+        ));
+
+    Ok(())
+}
+
 #[test]
 fn command_stat() -> anyhow::Result<()> {
     let mut cmd = Command::cargo_bin("spanr")?;
@@ -169,6 +363,56 @@ fn command_stat() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn command_stat_stranded() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("stat")
+        .arg("tests/spanr/S288c.chr.sizes")
+        .arg("tests/spanr/stranded.json")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 7);
+    assert!(stdout.contains("strand,chr,chrLength,size,coverage"));
+    assert!(stdout.contains("+,I,230218,306,0.0013"));
+    assert!(stdout.contains("-,II,813184,782,0.0010"));
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("stat")
+        .arg("tests/spanr/S288c.chr.sizes")
+        .arg("tests/spanr/stranded.json")
+        .arg("--merge-strands")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 4);
+    assert!(stdout.contains("I,230218,356,0.0015"));
+
+    Ok(())
+}
+
+#[test]
+fn command_stat_giant() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("stat")
+        .arg("tests/spanr/giant.chr.sizes")
+        .arg("tests/spanr/giant.json")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 4, "line count");
+    assert!(stdout.contains("1,3200000000,200000000,0.0625"));
+    assert!(stdout.contains("all,5700000000,200000100,"));
+
+    Ok(())
+}
+
 #[test]
 fn command_stat_all() -> anyhow::Result<()> {
     let mut cmd = Command::cargo_bin("spanr")?;
@@ -301,6 +545,84 @@ fn command_combine() -> anyhow::Result<()> {
     assert!(stdout.lines().count() == 2 || stdout.lines().count() == 3);
     assert!(stdout.contains("21294-22075,"), "no changes");
 
+    // Chromosomes missing from the second set are unchanged
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("combine")
+        .arg("tests/spanr/missing.json")
+        .arg("--op")
+        .arg("intersect")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("\"I\": \"51-100\""));
+    assert!(stdout.contains("\"II\": \"1-10\""));
+    assert!(stdout.contains("\"III\": \"-\""));
+
+    // Unknown op
+    let mut cmd = Command::cargo_bin("spanr")?;
+    cmd.arg("combine")
+        .arg("tests/spanr/brca2.json")
+        .arg("--op")
+        .arg("foo")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value 'foo'"));
+
+    Ok(())
+}
+
+#[test]
+fn command_combine_atleast() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("combine")
+        .arg("tests/spanr/Atha.json")
+        .arg("--op")
+        .arg("atleast")
+        .arg("-k")
+        .arg("2")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.lines().count() == 4 || stdout.lines().count() == 5);
+    assert!(
+        stdout.contains("\"1\": \"6790-7069,7157-7232,"),
+        "in 2 of 5"
+    );
+    assert!(stdout.contains("\"2\": \"-\""), "in 1 of 5");
+
+    // -k 1 is union
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("combine")
+        .arg("tests/spanr/Atha.json")
+        .arg("--op")
+        .arg("atleast")
+        .output()
+        .unwrap();
+    let union = Command::cargo_bin("spanr")?
+        .arg("combine")
+        .arg("tests/spanr/Atha.json")
+        .output()
+        .unwrap();
+    assert_eq!(output.stdout, union.stdout);
+
+    // -k 0 would be the universal set
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("combine")
+        .arg("tests/spanr/Atha.json")
+        .arg("--op")
+        .arg("atleast")
+        .arg("--k")
+        .arg("0")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
     Ok(())
 }
 
@@ -386,6 +708,50 @@ fn command_compare() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn command_compare_stranded() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("compare")
+        .arg("tests/spanr/stranded.json")
+        .arg("tests/spanr/stranded.json")
+        .arg("--op")
+        .arg("diff")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(!stdout.contains("21294-22075"), "same strand");
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("compare")
+        .arg("tests/spanr/stranded.json")
+        .arg("tests/spanr/intergenic.json")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("\"-\": \"21294-22075\""), "both strands");
+    assert!(!stdout.contains("\"+\": \"21294-22075\""), "both strands");
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("compare")
+        .arg("tests/spanr/stranded.json")
+        .arg("tests/spanr/stranded.json")
+        .arg("--op")
+        .arg("union")
+        .arg("--merge-strands")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("\"I\": \"1-150,195-400\""), "merged");
+
+    Ok(())
+}
+
 #[test]
 fn command_span() -> anyhow::Result<()> {
     // cover
@@ -519,6 +885,73 @@ fn command_cover() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn command_cover_bed() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("cover")
+        .arg("tests/spanr/S288c.bed")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.lines().count() == 3 || stdout.lines().count() == 4);
+    assert!(
+        stdout.contains("\"I\": \"1-150,190-200\""),
+        "0-based starts"
+    );
+    assert!(stdout.contains("21294-22075"), "covered");
+
+    Ok(())
+}
+
+#[test]
+fn command_cover_strict() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("cover")
+        .arg("tests/spanr/malformed.rg")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(output.status.success());
+    assert!(stdout.contains("\"II\": \"21294-22075\""));
+    assert!(stderr.contains("Skipped 2 malformed line(s)"));
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    cmd.arg("cover")
+        .arg("tests/spanr/malformed.rg")
+        .arg("--strict")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "tests/spanr/malformed.rg: line 2: invalid range: `I:abc`",
+        ));
+
+    // Sequences after ##FASTA are ignored
+    let mut cmd = Command::cargo_bin("spanr")?;
+    cmd.arg("gff")
+        .arg("tests/spanr/malformed.gff3")
+        .arg("--tag")
+        .arg("exon")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1001-1200"))
+        .stderr(predicate::str::contains("Skipped 2 malformed line(s)"));
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    cmd.arg("gff")
+        .arg("tests/spanr/malformed.gff3")
+        .arg("--strict")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("line 5: invalid start: `abc`"));
+
+    Ok(())
+}
+
 #[test]
 fn command_coverage() -> anyhow::Result<()> {
     let mut cmd = Command::cargo_bin("spanr")?;
@@ -563,6 +996,112 @@ fn command_coverage_detailed() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn command_coverage_bedgraph() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("coverage")
+        .arg("tests/spanr/S288c.rg")
+        .arg("--bedgraph")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 6);
+    assert!(stdout.starts_with("I\t0\t89\t1\nI\t89\t100\t2\nI\t100\t150\t1\n"));
+    assert!(stdout.contains("I\t189\t200\t2\n"), "depth 2");
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("coverage")
+        .arg("tests/spanr/adjacent.rg")
+        .arg("--bedgraph")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 4);
+    assert!(stdout.contains("I\t49\t100\t2\nI\t100\t150\t2\n"));
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("coverage")
+        .arg("tests/spanr/adjacent.rg")
+        .arg("--bedgraph")
+        .arg("--merge")
+        .arg("--normalize")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    // 301 bases in total
+    assert_eq!(stdout.lines().count(), 3);
+    assert!(stdout.contains("I\t49\t150\t6644.5181\n"));
+
+    Ok(())
+}
+
+#[test]
+fn command_coverage_bigwig() -> anyhow::Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let outfile = tempdir.path().join("S288c.bw");
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    cmd.arg("coverage")
+        .arg("tests/spanr/S288c.rg")
+        .arg("--bigwig")
+        .arg("-s")
+        .arg("tests/spanr/S288c.chr.sizes")
+        .arg("-o")
+        .arg(outfile.to_str().unwrap())
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    let bytes = std::fs::read(&outfile)?;
+    assert_eq!(&bytes[0..4], &[0x26, 0xFC, 0x8F, 0x88]);
+
+    tempdir.close()?;
+    Ok(())
+}
+
+#[test]
+fn command_parallel() -> anyhow::Result<()> {
+    let cases: Vec<Vec<&str>> = vec![
+        vec![
+            "compare",
+            "--op",
+            "intersect",
+            "tests/spanr/intergenic.json",
+            "tests/spanr/repeat.json",
+        ],
+        vec![
+            "statop",
+            "--op",
+            "intersect",
+            "tests/spanr/Atha.chr.sizes",
+            "tests/spanr/Atha.json",
+            "tests/spanr/paralog.json",
+        ],
+        vec!["coverage", "tests/spanr/S288c.rg", "-d"],
+    ];
+
+    for case in cases {
+        let mut cmd = Command::cargo_bin("spanr")?;
+        let output = cmd.args(&case).output().unwrap();
+        let expected = String::from_utf8(output.stdout).unwrap();
+
+        let mut cmd = Command::cargo_bin("spanr")?;
+        let output = cmd.args(&case).arg("-p").arg("4").output().unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+
+        assert!(!stdout.is_empty());
+        assert_eq!(stdout, expected, "{}", case[0]);
+    }
+
+    Ok(())
+}
+
 #[test]
 fn command_gff() -> anyhow::Result<()> {
     let mut cmd = Command::cargo_bin("spanr")?;
@@ -580,6 +1119,183 @@ fn command_gff() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn command_gff_stranded() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("gff")
+        .arg("tests/spanr/NC_007942.gff")
+        .arg("--tag")
+        .arg("tRNA")
+        .arg("--stranded")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 6);
+    assert!(stdout.contains("\"+\": \"9832-9870,"), "positive strand");
+    assert!(stdout.contains("\"-\": \"3-77,"), "negative strand");
+
+    Ok(())
+}
+
+#[test]
+fn command_gff_transcript() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("gff")
+        .arg("tests/spanr/genes.gff3")
+        .arg("--by")
+        .arg("transcript")
+        .arg("--feature")
+        .arg("intron")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 14);
+    assert!(stdout.contains("\"I\": \"1201-1500,1701-1800\""), "t1.1");
+    assert!(stdout.contains("\"I\": \"1201-1800\""), "t1.2");
+    assert!(!stdout.contains("r4.1"), "no introns");
+
+    // GTF gives the same 5' UTRs
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("gff")
+        .arg("tests/spanr/genes.gtf")
+        .arg("--by")
+        .arg("transcript")
+        .arg("--feature")
+        .arg("5utr")
+        .arg("--rg")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 3);
+    assert!(stdout.contains("I(+):1001-1150\tg1\tt1.2\n"));
+    assert!(stdout.contains("I(-):5901-6000\tg3\tt3.1\n"));
+
+    Ok(())
+}
+
+#[test]
+fn command_gff_gene() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("gff")
+        .arg("tests/spanr/genes.gff3")
+        .arg("--by")
+        .arg("gene")
+        .arg("--attr")
+        .arg("biotype=protein_coding")
+        .arg("--rg")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 5);
+    assert!(stdout.contains("I(+):1501-1700\tg1\n"));
+    assert!(
+        stdout.contains("I(-):5001-5500\tg3\n"),
+        "exons from CDS and UTRs"
+    );
+    assert!(!stdout.contains("g2"));
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("gff")
+        .arg("tests/spanr/NC_007942.gff")
+        .arg("--by")
+        .arg("gene")
+        .arg("--feature")
+        .arg("intron")
+        .arg("--rg")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(
+        stdout.contains("NC_007942(-):1673-4249\tGlmaCt002"),
+        "split tRNA"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn command_features() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("features")
+        .arg("tests/spanr/genes.chr.sizes")
+        .arg("tests/spanr/genes.gff3")
+        .arg("--promoter")
+        .arg("500")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 26);
+    assert!(
+        stdout.contains("\"I\": \"1201-1500,1701-1800,3301-3700,5501-5700\""),
+        "intron"
+    );
+    assert!(
+        stdout.contains("\"I\": \"501-1000,4001-4500,6001-6300\""),
+        "promoter"
+    );
+    assert!(
+        stdout.contains("\"II\": \"1-7000,7101-8000\""),
+        "intergenic"
+    );
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("features")
+        .arg("tests/spanr/genes.chr.sizes")
+        .arg("tests/spanr/genes.gtf")
+        .arg("--attr")
+        .arg("gene_biotype=protein_coding")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(
+        stdout.contains("\"I\": \"1-1000,2001-5000,6001-6300\""),
+        "intergenic"
+    );
+    assert!(stdout.contains("\"II\": \"-\""));
+
+    Ok(())
+}
+
+#[test]
+fn command_features_ends() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("features")
+        .arg("tests/spanr/ends.chr.sizes")
+        .arg("tests/spanr/ends.gff3")
+        .arg("--promoter")
+        .arg("50")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("\"I\": \"950-1000\""), "cds");
+    assert!(stdout.contains("\"I\": \"1-100,900-1000\""), "gene");
+    assert!(stdout.contains("\"I\": \"101-899\""), "intergenic");
+    assert!(!stdout.contains("1001-"), "beyond the end");
+    assert_eq!(
+        stdout.matches("\"I\": \"-\"").count(),
+        2,
+        "intron, promoter"
+    );
+
+    Ok(())
+}
+
 #[test]
 fn command_gff_merge() -> anyhow::Result<()> {
     let tempdir = TempDir::new().unwrap();
@@ -590,7 +1306,7 @@ fn command_gff_merge() -> anyhow::Result<()> {
         .arg("--tag")
         .arg("CDS")
         .arg("-o")
-        .arg(&tempdir.path().join("cds.json"))
+        .arg(tempdir.path().join("cds.json"))
         .assert()
         .success()
         .stdout(predicate::str::is_empty());
@@ -601,7 +1317,7 @@ fn command_gff_merge() -> anyhow::Result<()> {
     cmd.arg("gff")
         .arg("tests/spanr/NC_007942.rm.gff")
         .arg("-o")
-        .arg(&tempdir.path().join("repeat.json"))
+        .arg(tempdir.path().join("repeat.json"))
         .assert()
         .success()
         .stdout(predicate::str::is_empty());
@@ -611,8 +1327,8 @@ fn command_gff_merge() -> anyhow::Result<()> {
     let mut cmd = Command::cargo_bin("spanr")?;
     let output = cmd
         .arg("merge")
-        .arg(&tempdir.path().join("cds.json"))
-        .arg(&tempdir.path().join("repeat.json"))
+        .arg(tempdir.path().join("cds.json"))
+        .arg(tempdir.path().join("repeat.json"))
         .output()
         .unwrap();
     let stdout = String::from_utf8(output.stdout).unwrap();
@@ -653,3 +1369,165 @@ fn command_convert() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn command_convert_rlb() -> anyhow::Result<()> {
+    let tempdir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    cmd.arg("convert")
+        .arg("tests/spanr/repeat.json")
+        .arg("--to")
+        .arg("rlb")
+        .arg("-o")
+        .arg(tempdir.path().join("repeat.rlb"))
+        .assert()
+        .success();
+
+    let bytes = std::fs::read(tempdir.path().join("repeat.rlb"))?;
+    assert_eq!(&bytes[..3], b"RLB");
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("convert")
+        .arg(tempdir.path().join("repeat.rlb"))
+        .arg("--to")
+        .arg("json")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let expected = std::fs::read_to_string("tests/spanr/repeat.json")?;
+    let json: serde_json::Value = serde_json::from_str(&stdout)?;
+    assert_eq!(json, serde_json::from_str::<serde_json::Value>(&expected)?);
+
+    // Outputs of other subcommands
+    let mut cmd = Command::cargo_bin("spanr")?;
+    cmd.arg("merge")
+        .arg("tests/spanr/I.json")
+        .arg("tests/spanr/II.json")
+        .arg("-o")
+        .arg(tempdir.path().join("merge.rlb"))
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("stat")
+        .arg("tests/spanr/S288c.chr.sizes")
+        .arg(tempdir.path().join("merge.rlb"))
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 5);
+    assert!(stdout.contains("II,II,813184,4834,0.0059"));
+
+    // Multi-level runlists are read directly into sets
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("combine")
+        .arg(tempdir.path().join("merge.rlb"))
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout)?;
+    assert_eq!(
+        json["II"],
+        "21294-22075,23537-24097,28547-29194,36489-36988,44919-45977,63330-63875,71128-71865"
+    );
+
+    // --to is inferred from the extension
+    let mut cmd = Command::cargo_bin("spanr")?;
+    cmd.arg("convert")
+        .arg("tests/spanr/repeat.json")
+        .arg("-o")
+        .arg(tempdir.path().join("inferred.rlb"))
+        .assert()
+        .success();
+
+    let bytes = std::fs::read(tempdir.path().join("inferred.rlb"))?;
+    assert_eq!(&bytes[..3], b"RLB");
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    cmd.arg("convert")
+        .arg("tests/spanr/repeat.json")
+        .arg("--to")
+        .arg("ranges")
+        .arg("-o")
+        .arg(tempdir.path().join("ranges.rlb"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("doesn't match"));
+
+    Ok(())
+}
+
+#[test]
+fn command_yaml() -> anyhow::Result<()> {
+    let tempdir = TempDir::new().unwrap();
+
+    // Multi-level YAML of the Perl tools
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("stat")
+        .arg("tests/spanr/Atha.chr.sizes")
+        .arg("tests/spanr/Atha.yml")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("stat")
+        .arg("tests/spanr/Atha.chr.sizes")
+        .arg("tests/spanr/Atha.json")
+        .output()
+        .unwrap();
+    assert_eq!(stdout, String::from_utf8(output.stdout).unwrap());
+
+    // Write and read back
+    let mut cmd = Command::cargo_bin("spanr")?;
+    cmd.arg("merge")
+        .arg("tests/spanr/I.json")
+        .arg("tests/spanr/II.json")
+        .arg("-o")
+        .arg(tempdir.path().join("merge.yml"))
+        .assert()
+        .success();
+
+    let yaml = std::fs::read_to_string(tempdir.path().join("merge.yml"))?;
+    assert!(yaml.starts_with("---\n"));
+    assert!(yaml.contains("\n  II: 21294-22075,"));
+
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("convert")
+        .arg(tempdir.path().join("merge.yml"))
+        .arg("--to")
+        .arg("json")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 8);
+    assert!(stdout.contains("\"II\": \"21294-22075,"));
+
+    Ok(())
+}
+
+#[test]
+fn command_convert_bed() -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("spanr")?;
+    let output = cmd
+        .arg("convert")
+        .arg("tests/spanr/repeat.json")
+        .arg("--bed")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 28);
+    assert!(stdout.contains("II\t327068\t327703\n"), "first range");
+
+    Ok(())
+}